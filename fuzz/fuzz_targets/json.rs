@@ -0,0 +1,22 @@
+// Request bodies for the jobs API, which anyone who can reach the server can send: parsed as JSON,
+// read as job parameters, and written back out.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use polar_mandelbrot::jobs::Parameters;
+use polar_mandelbrot::json::Value;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(source) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let Ok(value) = Value::parse(source) else {
+        return;
+    };
+
+    let _ = Parameters::from_json(&value);
+
+    let _ = value.to_string();
+});
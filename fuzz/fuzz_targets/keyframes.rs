@@ -0,0 +1,23 @@
+// Keyframe files, which are parsed and then, if they parse, interpolated at their first, middle, and
+// last frames and one past the end.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use polar_mandelbrot::keyframes::Keyframes;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(source) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let Ok(keyframes) = Keyframes::parse(source) else {
+        return;
+    };
+
+    let frames = keyframes.frames();
+
+    for frame in [0, frames / 2, frames - 1, frames] {
+        keyframes.state(frame);
+    }
+});
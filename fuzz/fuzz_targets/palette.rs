@@ -0,0 +1,25 @@
+// Palette files, which are parsed and then, if they parse, cycled through and blended at turns
+// inside, at the ends of, and far outside the first turn.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use polar_mandelbrot::palette::Palette;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(source) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let Ok(palette) = Palette::parse(source) else {
+        return;
+    };
+
+    for n in [0, 1, palette.colors.len() as u32, u32::MAX] {
+        palette.cycle(n);
+    }
+
+    for t in [0.0, 0.5, 1.0 - f32::EPSILON, 1.0, -0.25, -1e-9, 1e10, f32::NAN] {
+        palette.at(t);
+    }
+});
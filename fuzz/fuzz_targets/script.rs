@@ -0,0 +1,31 @@
+// Fractal scripts, which are parsed and then, if they parse, run and colored at a few points with a
+// low iteration limit, so that evaluating whatever the parser accepts is covered too.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use num::complex::Complex;
+use polar_mandelbrot::fractal::{Fractal, Limited};
+use polar_mandelbrot::render::Colorer;
+use polar_mandelbrot::script::Script;
+use std::sync::Arc;
+
+const POINTS: [(f32, f32); 4] = [(0.0, 0.0), (-0.75, 0.1), (1.0, 1.0), (-2.0, 0.0)];
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(source) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    let Ok(script) = Script::parse(source) else {
+        return;
+    };
+
+    let script = Arc::new(script);
+
+    let limited = Limited { fractal: script.clone(), iterations: 64 };
+
+    for (re, im) in POINTS {
+        script.color(&limited.sample(Complex::new(re, im)));
+    }
+});
@@ -0,0 +1,72 @@
+/*
+ * C API for polar_mandelbrot, implemented in src/ffi.rs. Build the shared library with
+ *
+ *     cargo rustc --lib --release --features ffi --crate-type cdylib
+ *
+ * and link against target/release/libpolar_mandelbrot.so (or .dylib, or .dll). Functions returning
+ * int return 0 on success and -1 if an argument is invalid.
+ */
+
+#ifndef POLAR_MANDELBROT_H
+#define POLAR_MANDELBROT_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct pm_renderer pm_renderer;
+
+/*
+ * Creates a renderer for the named fractal (mandelbrot, magnet1, or magnet2, or mandelbrot if name
+ * is NULL), iterating at most iterations times per point, or the default if it's 0. Returns NULL
+ * if the name is unknown.
+ */
+pm_renderer *pm_renderer_new(const char *name, uint32_t iterations);
+
+/* Creates a renderer for the Julia set of k = re + im·i. */
+pm_renderer *pm_renderer_new_julia(float re, float im, uint32_t iterations);
+
+/*
+ * Renders the view centered on (re, im) and view_width wide into buffer, which must hold
+ * width * height * 3 bytes, as 8-bit RGB rows from top to bottom. Members are white and everything
+ * else black. samples is the number of jittered samples averaged per pixel. Fails if the image is
+ * too large to render in memory; very large images have to be rendered a view at a time.
+ */
+int pm_render(
+    const pm_renderer *renderer,
+    float re,
+    float im,
+    float view_width,
+    uint32_t width,
+    uint32_t height,
+    uint32_t samples,
+    uint8_t *buffer
+);
+
+/*
+ * Marches angles rays evenly spaced around the full circle from (re, im) in steps of ray_step,
+ * writing each ray's angle to thetas and the radius at which it leaves the set to radii, which must
+ * each hold angles floats. ray_step must be at least 2^-15 (about 0.0000305) and is otherwise
+ * rejected with -1, as finer steps would march too many points along each ray.
+ */
+int pm_polar_profile(
+    const pm_renderer *renderer,
+    float re,
+    float im,
+    float ray_step,
+    size_t angles,
+    float *thetas,
+    float *radii
+);
+
+/* Frees a renderer. NULL is ignored. */
+void pm_renderer_free(pm_renderer *renderer);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
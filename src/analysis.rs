@@ -0,0 +1,427 @@
+use crate::fractal::Fractal;
+use crate::polar::{Crossing, Domain, Ray, MAX_RADIUS, RAY_STEP};
+use crate::random::Random;
+use num::complex::Complex;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Summary {
+    pub count: usize,
+    pub min: f32,
+    pub max: f32,
+    pub theta_max: f32,
+    pub mean: f32,
+    pub variance: f32
+}
+
+impl Summary {
+    // None if a ray starts outside the set, as around many Julia sets and origins off the set.
+    pub fn ratio(&self) -> Option<f32> {
+        (self.min > 0.0).then(|| self.max / self.min)
+    }
+}
+
+// The angle each sample accounts for: half the gap to each neighbor, wrapping around over the whole
+// turn. For evenly spaced samples this is 2π / n throughout; over a sub-range it's the trapezoid
+// rule, so the two ends count half.
+pub fn angular_weights(profile: &[(f32, f32)], domain: Domain) -> Vec<f64> {
+    let n = profile.len();
+
+    let tau = std::f64::consts::PI * 2.0;
+
+    if n == 1 {
+        return vec![match domain {
+            Domain::Full => tau,
+            Domain::Range(start, end) => (end - start) as f64
+        }];
+    }
+
+    (0..n)
+        .map(|i| {
+            let theta = profile[i].0 as f64;
+
+            let next = match domain {
+                _ if i + 1 < n => profile[i + 1].0 as f64,
+                Domain::Full => profile[0].0 as f64 + tau,
+                Domain::Range(..) => theta
+            };
+
+            let previous = match domain {
+                _ if i > 0 => profile[i - 1].0 as f64,
+                Domain::Full => profile[n - 1].0 as f64 - tau,
+                Domain::Range(..) => theta
+            };
+
+            (next - previous) / 2.0
+        })
+        .collect()
+}
+
+pub fn summarize(profile: &[(f32, f32)], domain: Domain) -> Summary {
+    let (theta_max, max) = profile
+        .iter()
+        .copied()
+        .fold((f32::NAN, f32::NEG_INFINITY), |best, sample| if sample.1 > best.1 { sample } else { best });
+
+    let weights = angular_weights(profile, domain);
+
+    let total = weights.iter().sum::<f64>();
+
+    let mean = profile.iter().zip(&weights).map(|(&(_, r), weight)| r as f64 * weight).sum::<f64>() / total;
+
+    let variance = profile
+        .iter()
+        .zip(&weights)
+        .map(|(&(_, r), weight)| (r as f64 - mean).powi(2) * weight)
+        .sum::<f64>()
+        / total;
+
+    Summary {
+        count: profile.len(),
+        min: profile.iter().map(|&(_, r)| r).fold(f32::INFINITY, f32::min),
+        max,
+        theta_max,
+        mean: mean as f32,
+        variance: variance as f32
+    }
+}
+
+// Linearly interpolates the profile, which must be sorted by θ, at the angle θ + 2πk that falls in
+// it, wrapping around over the whole turn. Over a sub-range there's nothing past its ends to
+// interpolate from.
+pub fn radius_at(profile: &[(f32, f32)], domain: Domain, theta: f32) -> Option<f32> {
+    let tau = std::f32::consts::PI * 2.0;
+
+    let (first, last) = (*profile.first()?, *profile.last()?);
+
+    let theta = first.0 + (theta - first.0).rem_euclid(tau);
+
+    let wrapped = match domain {
+        Domain::Full => (first.0 + tau, first.1),
+        Domain::Range(..) if theta > last.0 => return None,
+        Domain::Range(..) => last
+    };
+
+    let i = profile.partition_point(|&(sample, _)| sample <= theta);
+
+    let (a, b) = (profile[i - 1], profile.get(i).copied().unwrap_or(wrapped));
+
+    if b.0 <= a.0 {
+        return Some(a.1);
+    }
+
+    Some(a.1 + (b.1 - a.1) * (theta - a.0) / (b.0 - a.0))
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Symmetry {
+    pub compared: usize,
+    pub max: f32,
+    pub theta_max: f32,
+    pub rms: f32,
+    // How many samples differ from their mirror image by more than the scan's resolution.
+    pub exceeding: usize,
+    pub resolution: f32
+}
+
+// The Mandelbrot set is symmetric about the real axis, so around an origin on it r(θ) = r(−θ) and
+// any difference is error in the scan.
+pub fn symmetry(profile: &[(f32, f32)], domain: Domain, resolution: f32) -> Symmetry {
+    let differences = profile
+        .iter()
+        .filter_map(|&(theta, r)| radius_at(profile, domain, -theta).map(|mirror| (theta, (r - mirror).abs())))
+        .collect::<Vec<_>>();
+
+    let (theta_max, max) = differences
+        .iter()
+        .copied()
+        .fold((f32::NAN, 0.0), |best, difference| if difference.1 > best.1 { difference } else { best });
+
+    let rms = (differences.iter().fold(0.0, |sum, &(_, difference)| sum + (difference as f64).powi(2))
+        / differences.len().max(1) as f64)
+        .sqrt();
+
+    Symmetry {
+        compared: differences.len(),
+        max,
+        theta_max,
+        rms: rms as f32,
+        exceeding: differences.iter().filter(|&&(_, difference)| difference > resolution).count(),
+        resolution
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Harmonic {
+    pub order: usize,
+    pub cosine: f32,
+    pub sine: f32
+}
+
+impl Harmonic {
+    pub fn amplitude(&self) -> f32 {
+        self.cosine.hypot(self.sine)
+    }
+
+    pub fn phase(&self) -> f32 {
+        self.sine.atan2(self.cosine)
+    }
+}
+
+// The profile must be sampled uniformly over [0, 2π), as polar::angles does, for the sums below
+// to approximate the Fourier integrals.
+pub fn fourier(profile: &[(f32, f32)], max_order: usize) -> Vec<Harmonic> {
+    let n = profile.len() as f64;
+
+    (0..=max_order)
+        .map(|order| {
+            let (cosine, sine) = profile.iter().fold((0.0, 0.0), |(cosine, sine), &(theta, r)| {
+                let angle = order as f64 * theta as f64;
+
+                (cosine + r as f64 * angle.cos(), sine + r as f64 * angle.sin())
+            });
+
+            let scale = if order == 0 { 1.0 / n } else { 2.0 / n };
+
+            Harmonic {
+                order,
+                cosine: (cosine * scale) as f32,
+                sine: (sine * scale) as f32
+            }
+        })
+        .collect()
+}
+
+pub fn dominant_harmonics(harmonics: &[Harmonic], count: usize) -> Vec<Harmonic> {
+    let mut dominant = harmonics.iter().filter(|harmonic| harmonic.order > 0).copied().collect::<Vec<_>>();
+
+    dominant.sort_by(|a, b| b.amplitude().total_cmp(&a.amplitude()));
+    dominant.truncate(count);
+
+    dominant
+}
+
+pub fn reconstruct(harmonics: &[Harmonic], theta: f32) -> f32 {
+    harmonics
+        .iter()
+        .map(|harmonic| {
+            let angle = harmonic.order as f32 * theta;
+
+            harmonic.cosine * angle.cos() + harmonic.sine * angle.sin()
+        })
+        .sum()
+}
+
+// The power in each harmonic, half its squared amplitude, so the powers above order 0 sum to the
+// variance of r(θ).
+pub fn power_spectrum(harmonics: &[Harmonic]) -> Vec<(usize, f32)> {
+    harmonics
+        .iter()
+        .filter(|harmonic| harmonic.order > 0)
+        .map(|harmonic| (harmonic.order, harmonic.amplitude().powi(2) / 2.0))
+        .collect()
+}
+
+// The circular autocorrelation of r(θ) about its mean at lags from 0 up to half a turn, normalized
+// so that lag 0 is 1. The profile must be sampled uniformly over the whole turn.
+pub fn autocorrelation(profile: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let n = profile.len();
+
+    let mean = profile.iter().map(|&(_, r)| r as f64).sum::<f64>() / n.max(1) as f64;
+
+    let deviations = profile.iter().map(|&(_, r)| r as f64 - mean).collect::<Vec<_>>();
+
+    let variance = deviations.iter().map(|deviation| deviation * deviation).sum::<f64>();
+
+    let step = std::f64::consts::PI * 2.0 / n.max(1) as f64;
+
+    (0..=n / 2)
+        .map(|lag| {
+            let covariance = (0..n).map(|i| deviations[i] * deviations[(i + lag) % n]).sum::<f64>();
+
+            let correlation = if variance > 0.0 { covariance / variance } else { 0.0 };
+
+            ((lag as f64 * step) as f32, correlation as f32)
+        })
+        .collect()
+}
+
+// The highest local maximum of the autocorrelation past lag 0, the most prominent angular period.
+pub fn autocorrelation_peak(autocorrelation: &[(f32, f32)]) -> Option<(f32, f32)> {
+    autocorrelation
+        .windows(3)
+        .filter(|window| window[1].1 > window[0].1 && window[1].1 >= window[2].1)
+        .map(|window| window[1])
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Area {
+    pub star: f32,
+    pub intervals: f32,
+    pub pixels: Option<f32>
+}
+
+// Förstemann's 2012 pixel-counting estimate.
+pub const PUBLISHED_AREA: f32 = 1.506_591_8;
+
+// Both estimates integrate ½∫r² dθ with the rectangle rule; the second every membership interval
+// along each ray rather than assuming the set is star-shaped.
+pub fn polar_area(profile: &[(f32, f32)], crossings: &[Crossing]) -> Area {
+    let star = profile
+        .iter()
+        .zip(angular_weights(profile, Domain::Full))
+        .map(|(&(_, r), weight)| (r as f64).powi(2) * weight)
+        .sum::<f64>()
+        / 2.0;
+
+    let intervals = crossings
+        .iter()
+        .flat_map(|(_, intervals)| intervals)
+        .map(|&(start, end)| (end as f64).powi(2) - (start as f64).powi(2))
+        .sum::<f64>()
+        * (std::f64::consts::PI * 2.0 / crossings.len() as f64)
+        / 2.0;
+
+    Area {
+        star: star as f32,
+        intervals: intervals as f32,
+        pixels: None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MonteCarlo {
+    pub samples: usize,
+    pub estimate: f32,
+    pub standard_error: f32
+}
+
+impl MonteCarlo {
+    pub fn confidence_interval(&self) -> (f32, f32) {
+        (self.estimate - 1.96 * self.standard_error, self.estimate + 1.96 * self.standard_error)
+    }
+}
+
+pub fn monte_carlo_area(
+    fractal: &dyn Fractal,
+    (min, max): (Complex<f32>, Complex<f32>),
+    samples: usize,
+    random: &mut Random
+) -> MonteCarlo {
+    let size = max - min;
+
+    let hits = (0..samples)
+        .filter(|_| {
+            let c = min + Complex::new(size.re * random.next_f32(), size.im * random.next_f32());
+
+            fractal.sample(c).is_member()
+        })
+        .count();
+
+    let box_area = (size.re * size.im).abs() as f64;
+
+    let p = hits as f64 / samples as f64;
+
+    MonteCarlo {
+        samples,
+        estimate: (p * box_area) as f32,
+        standard_error: (box_area * (p * (1.0 - p) / samples as f64).sqrt()) as f32
+    }
+}
+
+// Central differences, wrapping around since r(θ) is periodic.
+pub fn derivative(profile: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let n = profile.len();
+
+    if n < 3 {
+        return profile.iter().map(|&(theta, _)| (theta, 0.0)).collect();
+    }
+
+    let step = std::f32::consts::PI * 2.0 / n as f32;
+
+    (0..n)
+        .map(|i| (profile[i].0, (profile[(i + 1) % n].1 - profile[(i + n - 1) % n].1) / (2.0 * step)))
+        .collect()
+}
+
+// The signed curvature of the polar curve, (r² + 2r'² − r r″) / (r² + r'²)^{3/2}, positive where it
+// turns counterclockwise. It spikes at cusps and where bulbs meet.
+pub fn curvature(profile: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let first = derivative(profile);
+
+    let second = derivative(&first);
+
+    profile
+        .iter()
+        .zip(first.iter().zip(&second))
+        .map(|(&(theta, r), (&(_, dr), &(_, ddr)))| {
+            let (r, dr, ddr) = (r as f64, dr as f64, ddr as f64);
+
+            let speed = r.hypot(dr);
+
+            let curvature = if speed > 0.0 { (r * r + 2.0 * dr * dr - r * ddr) / speed.powi(3) } else { 0.0 };
+
+            (theta, curvature as f32)
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ArcLength {
+    pub polar: f32,
+    pub pixels: Option<f32>
+}
+
+pub fn polar_arc_length(profile: &[(f32, f32)]) -> f32 {
+    let step = std::f64::consts::PI * 2.0 / profile.len() as f64;
+
+    profile
+        .iter()
+        .zip(derivative(profile))
+        .map(|(&(_, r), (_, dr))| (r as f64).hypot(dr as f64))
+        .sum::<f64>() as f32
+        * step as f32
+}
+
+pub fn pixel_arc_length((horizontal, vertical): (usize, usize), (width, height): (f32, f32)) -> f32 {
+    // A horizontally adjacent pair is separated by a vertical edge, and vice versa.
+    horizontal as f32 * height + vertical as f32 * width
+}
+
+// The main cardioid is traced by c = e^{it}/2 − e^{2it}/4, which is exactly where 1 − √(1 − 4c) has
+// unit modulus; the period-2 bulb is the disk of radius ¼ around −1.
+pub fn in_main_components(c: Complex<f32>) -> bool {
+    (1.0 - (1.0 - 4.0 * c).sqrt()).norm() <= 1.0 || (c + 1.0).norm() <= 0.25
+}
+
+// Where the ray first leaves the main cardioid and period-2 bulb, or 0 if it starts outside them.
+pub fn analytic_radius(ray: Ray) -> f32 {
+    let mut inside = 0.0;
+
+    if !in_main_components(ray.point(inside)) {
+        return inside;
+    }
+
+    let mut outside = inside + RAY_STEP;
+
+    while in_main_components(ray.point(outside)) {
+        if outside >= MAX_RADIUS {
+            return MAX_RADIUS;
+        }
+
+        inside = outside;
+        outside += RAY_STEP;
+    }
+
+    for _ in 0..24 {
+        let middle = (inside + outside) / 2.0;
+
+        if in_main_components(ray.point(middle)) {
+            inside = middle;
+        } else {
+            outside = middle;
+        }
+    }
+
+    inside
+}
@@ -0,0 +1,517 @@
+// Renders animations, either zooms, Julia sets morphing along a path, or keyframed camera moves,
+// frame by frame and writes them out as numbered PNGs, as an animated GIF or APNG, as a video by
+// piping raw frames to ffmpeg, or as a stream of PPM or YUV4MPEG2 frames on standard output.
+
+use crate::fractal::{Fractal, Julia, Limited};
+use crate::keyframes::{Keyframe, Keyframes};
+use crate::palette::Palette;
+use crate::plot::{self, PlotStyle, PolarPlot};
+use crate::polar::{self, Domain, Ray};
+use crate::render::{self, Colorer, PaletteColorer};
+use crate::viewport::View;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, DynamicImage, Frame, RgbImage};
+use num::complex::Complex;
+use std::fs::File;
+use std::io::{BufWriter, Stdout, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::str::FromStr;
+use std::sync::Arc;
+
+pub const ZOOM: f32 = 1000.0;
+pub const ITERATION_GROWTH: u32 = 100;
+pub const FRAME_SIZE: (u32, u32) = (1280, 720);
+pub const FRAME_RATE: u32 = 30;
+
+// NeuQuant's sampling factor when reducing each GIF frame to 256 colors, from 1 (slowest and best) to
+// 30 (fastest).
+pub const GIF_SPEED: i32 = 10;
+
+// An exponential zoom into the target, which stays centered, from the default view's width down to
+// 1 / magnification of it, so every frame zooms in by the same factor.
+#[derive(Clone, Copy, Debug)]
+pub struct Zoom {
+    pub target: Complex<f32>,
+    pub magnification: f32,
+    pub frames: usize,
+    pub iterations: u32,
+    // Extra iterations for every doubling of the magnification, since the boundary's detail takes
+    // more iterations to resolve the deeper the zoom.
+    pub iteration_growth: u32,
+    pub samples: u32,
+    pub size: (u32, u32)
+}
+
+// How far through the animation a frame is, from 0 at the first to 1 at the last.
+fn progress(frame: usize, frames: usize) -> f32 {
+    if frames > 1 { frame as f32 / (frames - 1) as f32 } else { 0.0 }
+}
+
+impl Zoom {
+    pub fn view(&self, frame: usize) -> View {
+        View {
+            center: self.target,
+            width: View::default().width / self.magnification.powf(progress(frame, self.frames))
+        }
+    }
+
+    pub fn iterations(&self, frame: usize) -> u32 {
+        let octaves = self.magnification.log2() * progress(frame, self.frames);
+
+        // The conversion saturates, and so does the sum, for a large iteration_growth.
+        self.iterations.saturating_add((self.iteration_growth as f32 * octaves).round() as u32)
+    }
+}
+
+// The magnification from one keyframe of a dive to the next.
+const DIVE_STEP: f32 = 10.0;
+
+// A keyframed zoom from the default view into a target, with iteration limits picked along the way.
+pub struct Dive {
+    pub target: Complex<f32>,
+    // More than 1.
+    pub magnification: f32,
+    pub frames: usize,
+    // The size of the frames it's meant for, which the iteration limits are picked at.
+    pub size: (u32, u32)
+}
+
+// Keyframes for the dive: one every DIVE_STEP of magnification, spread over the frames in
+// proportion to how far the zoom has come so that it zooms in at a constant rate. All but the first
+// are centered on the target, which the interpolation between keyframes keeps about where it starts
+// on screen as it drifts to the center. Each limit is picked from a coarse pass of its keyframe's
+// view, as render::auto_iterations does, and never drops below the one before.
+pub async fn dive(fractal: Arc<dyn Fractal>, dive: &Dive) -> Keyframes {
+    let last = dive.frames - 1;
+
+    let octaves = dive.magnification.ln();
+
+    let mut zooms = std::iter::successors(Some(1.0), |zoom| Some(zoom * DIVE_STEP))
+        .take_while(|&zoom| zoom < dive.magnification)
+        .collect::<Vec<f32>>();
+
+    zooms.push(dive.magnification);
+
+    let mut keys = Vec::<Keyframe>::new();
+
+    for (i, &zoom) in zooms.iter().enumerate() {
+        let frame = (last as f32 * zoom.ln() / octaves).round() as usize;
+
+        let center = if i == 0 { View::default().center } else { dive.target };
+
+        let view = View { center, width: View::default().width / zoom };
+
+        let iterations = render::view_auto_iterations(fractal.clone(), dive.size, view).await;
+
+        let iterations = keys.last().map_or(iterations, |previous| iterations.max(previous.iterations));
+
+        let key = Keyframe { frame, center, zoom, iterations, ..Keyframe::default() };
+
+        eprintln!("dive: keyframe at frame {}, zoom {}, {} iterations", frame, zoom, iterations);
+
+        // With fewer frames than keyframes, later ones take the place of those they'd share a frame
+        // with, except for the first, which the dive has to start from.
+        match keys.last_mut() {
+            Some(previous) if previous.frame < frame => keys.push(key),
+            Some(previous) if previous.frame > 0 => *previous = Keyframe { frame: previous.frame, ..key },
+            Some(_) => {}
+            None => keys.push(key)
+        }
+    }
+
+    Keyframes::new(keys)
+}
+
+// A path for the Julia parameter k to follow over the animation.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Morph {
+    Line(Complex<f32>, Complex<f32>),
+    // The image of the circle |λ| = radius under λ ↦ λ/2 − λ²/4, which maps the multipliers of the
+    // main cardioid's fixed point to k, so a radius of 1 traces its boundary once and a radius just
+    // below it stays inside the set.
+    Cardioid(f32),
+    // Straight segments through each point in turn, each taking an equal share of the frames.
+    Points(Vec<Complex<f32>>)
+}
+
+impl FromStr for Morph {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!("invalid morph `{}`; expected line:<k0>:<k1>, cardioid[:<radius>], or points:<k>,...", s)
+        };
+
+        let complex = |value: &str| value.parse::<Complex<f32>>().map_err(|_| invalid());
+
+        let (kind, arguments) = s.split_once(':').unwrap_or((s, ""));
+
+        match kind {
+            "line" => {
+                let (from, to) = arguments.split_once(':').ok_or_else(invalid)?;
+
+                Ok(Self::Line(complex(from)?, complex(to)?))
+            }
+
+            "cardioid" if arguments.is_empty() => Ok(Self::Cardioid(1.0)),
+
+            "cardioid" => match arguments.parse::<f32>() {
+                Ok(radius) if radius.is_finite() && radius > 0.0 => Ok(Self::Cardioid(radius)),
+                _ => Err(invalid())
+            },
+
+            "points" => {
+                let points = arguments.split(',').map(complex).collect::<Result<Vec<_>, _>>()?;
+
+                if points.len() < 2 {
+                    return Err(invalid());
+                }
+
+                Ok(Self::Points(points))
+            }
+
+            _ => Err(invalid())
+        }
+    }
+}
+
+impl Morph {
+    pub fn point(&self, t: f32) -> Complex<f32> {
+        match self {
+            Self::Line(from, to) => from + (to - from) * t,
+
+            Self::Cardioid(radius) => {
+                let multiplier = Complex::from_polar(*radius, std::f32::consts::TAU * t);
+
+                multiplier / 2.0 - multiplier * multiplier / 4.0
+            }
+
+            Self::Points(points) => {
+                let segments = points.len() - 1;
+
+                let position = t * segments as f32;
+
+                let segment = (position as usize).min(segments - 1);
+
+                let local = position - segment as f32;
+
+                points[segment] + (points[segment + 1] - points[segment]) * local
+            }
+        }
+    }
+}
+
+// Where and how densely to march the inset r(θ) plot of each frame's Julia set.
+#[derive(Clone, Copy, Debug)]
+pub struct Inset {
+    pub origin: Complex<f32>,
+    pub samples: usize,
+    pub ray_step: f32
+}
+
+// The filled Julia sets along a morph, each rendered in the default view.
+#[derive(Clone, Debug)]
+pub struct JuliaMorph {
+    pub morph: Morph,
+    pub frames: usize,
+    pub iterations: u32,
+    pub samples: u32,
+    pub size: (u32, u32),
+    pub inset: Option<Inset>
+}
+
+impl JuliaMorph {
+    pub fn k(&self, frame: usize) -> Complex<f32> {
+        self.morph.point(progress(frame, self.frames))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Encoding {
+    pub frame_rate: u32,
+    // Milliseconds each GIF or APNG frame is shown, which defaults to one frame at the frame rate.
+    pub frame_delay: Option<u32>,
+    pub gif_speed: i32,
+    // Whether frames streamed to standard output are YUV4MPEG2 rather than PPM.
+    pub y4m: bool
+}
+
+impl Default for Encoding {
+    fn default() -> Self {
+        Self { frame_rate: FRAME_RATE, frame_delay: None, gif_speed: GIF_SPEED, y4m: false }
+    }
+}
+
+// Converts to full-resolution BT.601 Y, Cb, and Cr planes in studio range, one after another as
+// a 4:4:4 YUV4MPEG2 frame holds them.
+fn yuv444(image: &RgbImage) -> Vec<u8> {
+    let pixels = image.pixels().map(|pixel| pixel.0.map(|channel| channel as f32 / 255.0)).collect::<Vec<_>>();
+
+    let planes: [fn([f32; 3]) -> f32; 3] = [
+        |[r, g, b]| 16.0 + 65.481 * r + 128.553 * g + 24.966 * b,
+        |[r, g, b]| 128.0 - 37.797 * r - 74.203 * g + 112.0 * b,
+        |[r, g, b]| 128.0 + 112.0 * r - 93.786 * g - 18.214 * b
+    ];
+
+    planes.iter().flat_map(|plane| pixels.iter().map(|&pixel| plane(pixel).round() as u8)).collect()
+}
+
+pub enum Sink {
+    Frames,
+    Gif { encoder: Box<GifEncoder<BufWriter<File>>>, delay: Delay },
+    Apng { writer: png::Writer<BufWriter<File>> },
+    Ffmpeg { child: Child, path: PathBuf },
+    Stdout { stdout: BufWriter<Stdout>, y4m: bool }
+}
+
+impl Sink {
+    // Without a path, frames are written to output_frame_0000.png onward. A path ending in .gif or
+    // .png is encoded here, `-` streams them to standard output, and any other is left to ffmpeg.
+    // An APNG declares its frame count up front, so exactly that many frames must be written.
+    pub fn create(
+        path: Option<&Path>,
+        (width, height): (u32, u32),
+        frames: usize,
+        encoding: Encoding
+    ) -> std::io::Result<Self> {
+        let Some(path) = path else {
+            return Ok(Self::Frames);
+        };
+
+        if path == Path::new("-") {
+            let mut stdout = BufWriter::new(std::io::stdout());
+
+            if encoding.y4m {
+                let (numerator, denominator) = match encoding.frame_delay {
+                    Some(delay) => (1000, delay),
+                    None => (encoding.frame_rate, 1)
+                };
+
+                writeln!(stdout, "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C444", width, height, numerator, denominator)?;
+            }
+
+            return Ok(Self::Stdout { stdout, y4m: encoding.y4m });
+        }
+
+        if path.extension().is_some_and(|extension| extension == "gif") {
+            let mut encoder = GifEncoder::new_with_speed(BufWriter::new(File::create(path)?), encoding.gif_speed);
+
+            encoder.set_repeat(Repeat::Infinite).map_err(|error| std::io::Error::other(error.to_string()))?;
+
+            let delay = encoding.frame_delay.unwrap_or(1000 / encoding.frame_rate);
+
+            return Ok(Self::Gif { encoder: Box::new(encoder), delay: Delay::from_numer_denom_ms(delay, 1) });
+        }
+
+        if path.extension().is_some_and(|extension| extension == "png") {
+            let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+
+            let (numerator, denominator) = match encoding.frame_delay {
+                Some(delay) => (delay, 1000),
+                None => (1, encoding.frame_rate)
+            };
+
+            let delay = (u16::try_from(numerator), u16::try_from(denominator));
+
+            let (Ok(numerator), Ok(denominator)) = delay else {
+                return Err(std::io::Error::other("an APNG frame delay can be at most 65535 ms"));
+            };
+
+            encoder.set_animated(frames as u32, 0).map_err(std::io::Error::other)?;
+            encoder.set_frame_delay(numerator, denominator).map_err(std::io::Error::other)?;
+
+            return Ok(Self::Apng { writer: encoder.write_header().map_err(std::io::Error::other)? });
+        }
+
+        let child = Command::new("ffmpeg")
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pix_fmt", "rgb24"])
+            .args(["-s", &format!("{}x{}", width, height), "-r", &encoding.frame_rate.to_string(), "-i", "-"])
+            .args(["-pix_fmt", "yuv420p"])
+            .arg(path)
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|error| std::io::Error::new(error.kind(), format!("couldn't start ffmpeg: {}", error)))?;
+
+        Ok(Self::Ffmpeg { child, path: path.to_path_buf() })
+    }
+
+    pub fn write(&mut self, frame: usize, image: &RgbImage) -> std::io::Result<()> {
+        match self {
+            Self::Frames => image
+                .save(format!("output_frame_{:04}.png", frame))
+                .map_err(|error| std::io::Error::other(error.to_string())),
+
+            Self::Gif { encoder, delay } => {
+                let rgba = DynamicImage::ImageRgb8(image.clone()).into_rgba8();
+
+                encoder
+                    .encode_frame(Frame::from_parts(rgba, 0, 0, *delay))
+                    .map_err(|error| std::io::Error::other(error.to_string()))
+            }
+
+            Self::Apng { writer } => writer.write_image_data(image.as_raw()).map_err(std::io::Error::other),
+
+            Self::Ffmpeg { child, .. } => {
+                child.stdin.as_mut().expect("ffmpeg's input is piped").write_all(image.as_raw())
+            }
+
+            // Each frame is flushed whole, so a reader never waits on part of one.
+            Self::Stdout { stdout, y4m: true } => {
+                stdout.write_all(b"FRAME\n")?;
+                stdout.write_all(&yuv444(image))?;
+                stdout.flush()
+            }
+
+            Self::Stdout { stdout, y4m: false } => {
+                write!(stdout, "P6\n{} {}\n255\n", image.width(), image.height())?;
+                stdout.write_all(image.as_raw())?;
+                stdout.flush()
+            }
+        }
+    }
+
+    pub fn finish(self) -> std::io::Result<()> {
+        match self {
+            Self::Frames => Ok(()),
+
+            // The trailer is written and the file flushed when the encoder is dropped.
+            Self::Gif { encoder, .. } => {
+                drop(encoder);
+
+                Ok(())
+            }
+
+            Self::Apng { writer } => writer.finish().map_err(std::io::Error::other),
+
+            Self::Stdout { mut stdout, .. } => stdout.flush(),
+
+            Self::Ffmpeg { mut child, path } => {
+                drop(child.stdin.take());
+
+                let status = child.wait()?;
+
+                if status.success() {
+                    Ok(())
+                } else {
+                    Err(std::io::Error::other(format!("ffmpeg failed to write {} ({})", path.display(), status)))
+                }
+            }
+        }
+    }
+}
+
+// Plots the Julia set's r(θ) into the lower right corner of the frame, a third of its size.
+fn draw_inset(image: &mut RgbImage, julia: &Julia, inset: Inset) {
+    let size = (image.width() / 3, image.height() / 3);
+
+    let profile = polar::sample_profile(Domain::Full, inset.samples, |theta| {
+        polar::march_radius(julia, Ray::new(inset.origin, theta), inset.ray_step).inside
+    });
+
+    let caption = format!("k = {}", julia.k);
+
+    let style = PlotStyle { size, dpi: (96 * size.1 / 480).max(24), ..Default::default() };
+
+    let plot = PolarPlot { profile: &profile, caption: Some(&caption), style: Some(&style), ..Default::default() };
+
+    let plot = plot::plot_polar_image(&plot).expect("plotting into memory can't fail");
+
+    let (x, y) = (image.width() - size.0, image.height() - size.1);
+
+    image::imageops::overlay(image, &plot, x as i64, y as i64);
+}
+
+pub async fn morph(colorer: Arc<dyn Colorer>, morph: &JuliaMorph, sink: &mut Sink) -> std::io::Result<()> {
+    for frame in 0..morph.frames {
+        let julia = Julia { k: morph.k(frame) };
+
+        let limited = Arc::new(Limited { fractal: Arc::new(Julia { k: julia.k }), iterations: morph.iterations });
+
+        let mut image = render::render_view(limited, colorer.clone(), morph.size, View::default(), morph.samples).await;
+
+        if let Some(inset) = morph.inset {
+            draw_inset(&mut image, &julia, inset);
+        }
+
+        sink.write(frame, &image)?;
+
+        eprintln!("animate: frame {} of {}, k = {}", frame + 1, morph.frames, julia.k);
+    }
+
+    Ok(())
+}
+
+pub async fn zoom(
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    zoom: &Zoom,
+    sink: &mut Sink
+) -> std::io::Result<()> {
+    for frame in 0..zoom.frames {
+        let limited = Arc::new(Limited { fractal: fractal.clone(), iterations: zoom.iterations(frame) });
+
+        let image = render::render_view(limited, colorer.clone(), zoom.size, zoom.view(frame), zoom.samples).await;
+
+        sink.write(frame, &image)?;
+
+        eprintln!(
+            "animate: frame {} of {}, width {}, {} iterations",
+            frame + 1,
+            zoom.frames,
+            zoom.view(frame).width,
+            zoom.iterations(frame)
+        );
+    }
+
+    Ok(())
+}
+
+// Renders the fractal, or the Julia set of the interpolated k if the keyframes set one, with the
+// palette, or the theme, at the interpolated phase if they set one and the given colorer otherwise.
+pub async fn keyframes(
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    theme: Option<Palette>,
+    keyframes: &Keyframes,
+    samples: u32,
+    size: (u32, u32),
+    sink: &mut Sink
+) -> std::io::Result<()> {
+    let frames = keyframes.frames();
+
+    for frame in 0..frames {
+        let state = keyframes.state(frame);
+
+        let fractal = match state.k {
+            Some(k) => Arc::new(Julia { k }),
+            None => fractal.clone()
+        };
+
+        let limited = Arc::new(Limited { fractal, iterations: state.iterations });
+
+        let colorer = if keyframes.palette {
+            Arc::new(PaletteColorer { phase: state.phase, theme: theme.clone() })
+        } else {
+            colorer.clone()
+        };
+
+        let image = render::render_view(limited, colorer, size, state.view, samples).await;
+
+        sink.write(frame, &image)?;
+
+        eprintln!(
+            "animate: frame {} of {}, center {}, width {}, {} iterations",
+            frame + 1,
+            frames,
+            state.view.center,
+            state.view.width,
+            state.iterations
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,144 @@
+// Traces boundaries through a grid with marching squares. Each cell of the grid joins four
+// neighboring pixel centers, and a boundary crosses any cell edge whose two pixels disagree on
+// which side they're on.
+
+use crate::render::IterationBuffer;
+use crate::viewport::pixel_to_complex;
+use num::complex::Complex;
+use std::collections::HashMap;
+
+// A cell edge, identified by the pixel at its upper or left end and whether it runs downward from
+// there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Edge {
+    x: i64,
+    y: i64,
+    vertical: bool
+}
+
+impl Edge {
+    fn horizontal(x: i64, y: i64) -> Self {
+        Self { x, y, vertical: false }
+    }
+
+    fn vertical(x: i64, y: i64) -> Self {
+        Self { x, y, vertical: true }
+    }
+
+    fn midpoint(&self) -> (f32, f32) {
+        if self.vertical {
+            (self.x as f32, self.y as f32 + 0.5)
+        } else {
+            (self.x as f32 + 0.5, self.y as f32)
+        }
+    }
+}
+
+// Where two diagonally opposite corners are inside, they're treated as separate, so no edge is ever
+// shared by more than two segments.
+fn cell_segments(inside: &dyn Fn(i64, i64) -> bool, x: i64, y: i64) -> Vec<(Edge, Edge)> {
+    let top = Edge::horizontal(x, y);
+    let bottom = Edge::horizontal(x, y + 1);
+    let left = Edge::vertical(x, y);
+    let right = Edge::vertical(x + 1, y);
+
+    let case = (inside(x, y) as u8) << 3
+        | (inside(x + 1, y) as u8) << 2
+        | (inside(x + 1, y + 1) as u8) << 1
+        | inside(x, y + 1) as u8;
+
+    match case {
+        1 | 14 => vec![(left, bottom)],
+        2 | 13 => vec![(bottom, right)],
+        3 | 12 => vec![(left, right)],
+        4 | 11 => vec![(top, right)],
+        5 => vec![(left, bottom), (top, right)],
+        6 | 9 => vec![(top, bottom)],
+        7 | 8 => vec![(left, top)],
+        10 => vec![(left, top), (bottom, right)],
+        _ => Vec::new()
+    }
+}
+
+// Returns polylines in pixel coordinates, longest first. The grid is padded by one pixel on every
+// side, so whether the polylines close depends on what `inside` says beyond the edges.
+pub fn trace(width: u32, height: u32, inside: &dyn Fn(i64, i64) -> bool) -> Vec<Vec<(f32, f32)>> {
+    let mut segments = Vec::new();
+
+    for y in -1..height as i64 {
+        for x in -1..width as i64 {
+            segments.extend(cell_segments(inside, x, y));
+        }
+    }
+
+    let mut neighbors: HashMap<Edge, Vec<usize>> = HashMap::new();
+
+    for (i, &(a, b)) in segments.iter().enumerate() {
+        neighbors.entry(a).or_default().push(i);
+        neighbors.entry(b).or_default().push(i);
+    }
+
+    let mut visited = vec![false; segments.len()];
+
+    let walk = |mut current: Edge, visited: &mut [bool]| {
+        let mut edges = Vec::new();
+
+        while let Some(&next) = neighbors[&current].iter().find(|&&i| !visited[i]) {
+            visited[next] = true;
+
+            let (a, b) = segments[next];
+
+            current = if a == current { b } else { a };
+
+            edges.push(current);
+        }
+
+        edges
+    };
+
+    let mut polylines = Vec::new();
+
+    for start in 0..segments.len() {
+        if visited[start] {
+            continue;
+        }
+
+        visited[start] = true;
+
+        let (first, last) = segments[start];
+
+        let mut edges = walk(last, &mut visited);
+
+        edges.insert(0, last);
+
+        // Open polylines can start partway along, so they also have to be followed backward.
+        let mut before = walk(first, &mut visited);
+
+        before.reverse();
+        before.push(first);
+        before.extend(edges);
+
+        polylines.push(before.iter().map(Edge::midpoint).collect::<Vec<_>>());
+    }
+
+    polylines.sort_by_key(|polyline| std::cmp::Reverse(polyline.len()));
+
+    polylines
+}
+
+// Returns every boundary of the set as a closed polyline in the complex plane, whose first and
+// last points are the same, longest first. Pixels outside the image count as escaped.
+pub fn extract(iterations: &IterationBuffer) -> Vec<Vec<Complex<f32>>> {
+    let inside = |x: i64, y: i64| {
+        x >= 0
+            && y >= 0
+            && x < iterations.width() as i64
+            && y < iterations.height() as i64
+            && iterations.is_member(x as u32, y as u32)
+    };
+
+    trace(iterations.width(), iterations.height(), &inside)
+        .into_iter()
+        .map(|polyline| polyline.into_iter().map(|(x, y)| pixel_to_complex(x, y)).collect())
+        .collect()
+}
@@ -0,0 +1,84 @@
+// Keeps rendered iteration buffers and images on disk, named by a hash of everything that decides
+// the render, so runs that only change how the result is analyzed or plotted can skip rendering.
+
+use crate::render::IterationBuffer;
+use image::RgbImage;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+// Written first, so files from an incompatible layout are never read as buffers.
+const MAGIC: &[u8; 8] = b"PMCACHE3";
+
+// FNV-1a over each part in turn, each preceded by its length so that different splits of the same
+// bytes hash differently. Unlike std's hasher, it's the same from one build to the next.
+pub fn key(parts: &[&[u8]]) -> String {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+
+    for part in parts {
+        for &byte in (part.len() as u64).to_le_bytes().iter().chain(part.iter()) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    format!("{:016x}", hash)
+}
+
+fn path(dir: &Path, key: &str) -> PathBuf {
+    dir.join(format!("{}.bin", key))
+}
+
+// The cached render and the iteration limit it ran to, or None if there isn't one or it can't be
+// read.
+pub fn load(dir: &Path, key: &str) -> Option<(IterationBuffer, RgbImage, u32)> {
+    let mut reader = BufReader::new(File::open(path(dir, key)).ok()?);
+
+    let mut magic = [0; MAGIC.len()];
+
+    reader.read_exact(&mut magic).ok()?;
+
+    if &magic != MAGIC {
+        return None;
+    }
+
+    let mut limit = [0; 4];
+
+    reader.read_exact(&mut limit).ok()?;
+
+    let iterations = IterationBuffer::read_from(&mut reader).ok()?;
+
+    let mut pixels = vec![0; iterations.width() as usize * iterations.height() as usize * 3];
+
+    reader.read_exact(&mut pixels).ok()?;
+
+    let image = RgbImage::from_raw(iterations.width(), iterations.height(), pixels)?;
+
+    Some((iterations, image, u32::from_le_bytes(limit)))
+}
+
+// Written to a temporary file first and renamed, so a run that's interrupted can't leave half a
+// buffer behind for the next to read.
+pub fn store(
+    dir: &Path,
+    key: &str,
+    (iterations, image, limit): (&IterationBuffer, &RgbImage, u32)
+) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+
+    let temporary = dir.join(format!("{}.tmp", key));
+
+    let mut writer = BufWriter::new(File::create(&temporary)?);
+
+    writer.write_all(MAGIC)?;
+    writer.write_all(&limit.to_le_bytes())?;
+
+    iterations.write_to(&mut writer)?;
+
+    writer.write_all(image.as_raw())?;
+    writer.flush()?;
+
+    drop(writer);
+
+    std::fs::rename(temporary, path(dir, key))
+}
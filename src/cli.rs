@@ -0,0 +1,1548 @@
+use polar_mandelbrot::animation;
+use polar_mandelbrot::cvd::Deficiency;
+use polar_mandelbrot::discover;
+use polar_mandelbrot::dzi;
+use polar_mandelbrot::export;
+use polar_mandelbrot::external::Angle;
+use polar_mandelbrot::fit::Model;
+use polar_mandelbrot::fixed::Fixed;
+use polar_mandelbrot::fractal::{self, Escape};
+use polar_mandelbrot::mesh;
+use polar_mandelbrot::palette::{self, Palette};
+use polar_mandelbrot::plot::{self, PlotStyle};
+use polar_mandelbrot::polar::Domain;
+use polar_mandelbrot::poster;
+use polar_mandelbrot::render;
+use polar_mandelbrot::smoothing::Smoothing;
+use polar_mandelbrot::strips;
+use polar_mandelbrot::terminal::Protocol;
+use polar_mandelbrot::validate;
+use polar_mandelbrot::viewport::IMG_WIDTH;
+use num::complex::Complex;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+pub const USAGE: &str = "\
+Usage: polar_mandelbrot [command] [options]
+
+Commands:
+    render     Render the set to output_set.png and plot r(θ) from it (default)
+    analyze    Plot r(θ) by marching along each ray, without rendering an image
+    plot       Overlay previously exported profiles, given with --compare, on one r(θ) plot
+    animate    Render an exponential zoom into --target, Julia sets along --morph, or the camera
+               move in a --keyframes file, one frame each to output_frame_0000.png onward
+    serve      Serve slippy-map tiles of the fractal at http://localhost:8080/{z}/{x}/{y}.png,
+               rendered on demand, and run render and analysis jobs submitted to /jobs
+    validate area
+               Estimate the area at increasing resolutions and iteration limits, extrapolate
+               where the estimates converge, and compare it with the published area
+    verify     Sample --samples random pixels again in double precision with 16 times the
+               iterations and report how often the render disagrees
+    selftest   Run quick checks with known answers, such as points known to be in the set and
+               the radius at the cusp, and report which pass
+    stitch <dir>
+               Check that the tiles render --poster wrote to the directory agree where they overlap
+               and join them into one image at --output
+    diff <a> <b>
+               Compare two renders pixel by pixel, both images or both iteration buffers saved by
+               --save-iterations, summarize the differences, and draw them as a heatmap to --output
+    from-image <path>
+               Render the view recorded in a PNG written by an earlier run again, with the same
+               fractal, iteration limit, and theme, at --width pixels across, to output_set.png;
+               options after the path override the recorded ones
+    recolor <path>
+               Color an iteration buffer saved by render --save-iterations again, with --palette,
+               to --output
+    discover   Look for views near the boundary, scored by how much their escape counts vary, and
+               print the best as --target and --zoom, with thumbnails in output_discover_1.png
+               onward
+    misiurewicz
+               Find the Misiurewicz point of --preperiod and --period nearest --near by Newton's
+               method and print it in double precision
+    nucleus    Find the center of the hyperbolic component of --period nearest --near by Newton's
+               method and print it in double precision
+    dive       Write a keyframe file for animate --keyframes that zooms from the default view into
+               --target by --zoom over --frames, with iteration limits picked along the way, to
+               --output
+
+Options:
+    --compare <path>... In plot, the CSV files written by --polar-csv to overlay, labeled by file name
+    --fractal <name>    Render a built-in fractal: mandelbrot (default), magnet1, or magnet2
+    --script <path>     Read the fractal definition and coloring from a script file
+    --julia <k>         Render the filled Julia set of z² + k, e.g. -0.8+0.156i, and report whether
+                        it's connected
+    --escape <cond>     Count an orbit as escaped once |z| reaches r with norm:<r>, or once |Re z|,
+                        |Im z|, either, or |Re z| + |Im z| reaches n with real:<n>, imaginary:<n>,
+                        either:<n>, or sum:<n> (default norm:2, or norm:100 for the magnets)
+    --julia-sweep <k0> <k1>
+                        In analyze, plot r(θ) for Julia sets with k running from k0 to k1, one
+                        frame each to output_sweep_0000.png onward
+    --frames <n>        Number of frames in a sweep or animation (default 30)
+    --target <c>        In animate and dive, the point to zoom into, which stays centered
+    --morph <path>      In animate, render the Julia set of every k along a path instead: line:<k0>:<k1>,
+                        cardioid[:<radius>] around the main cardioid (radius 1 is its boundary),
+                        or points:<k>,... through each point in turn
+    --keyframes <path>  In animate, interpolate the center, zoom, iterations, palette phase, and Julia
+                        parameter between the keyframes in a file, which also sets the frame count
+    --inset             Plot each morph frame's r(θ) in its lower right corner
+    --zoom <factor>     Magnification of the last frame of the animation (default 1000)
+    --iteration-growth <n>
+                        Extra iterations per doubling of the magnification (default 100)
+    --antialias <n>     Average n jittered samples per pixel in each frame (default 1)
+    --frame-size <WxH>  Size of each frame in pixels (default 1280x720)
+    --frame-rate <n>    Frames per second of the video (default 30)
+    --animation <path>  Write the frames as an animated GIF (.gif) or APNG (.png), pipe them to
+                        ffmpeg to encode them as a video at the given path, or stream them to
+                        standard output as PPM images with -
+    --y4m               Stream frames to --animation - as YUV4MPEG2 instead of PPM
+    --frame-delay <ms>  How long each GIF or APNG frame is shown (default: one frame at the frame
+                        rate)
+    --gif-speed <n>     Speed of reducing GIF frames to 256 colors, from 1 (best) to 30 (fastest)
+                        (default 10)
+    --dzi <path>        In render, write a Deep Zoom Image tile pyramid for OpenSeadragon, e.g.
+                        set.dzi with its tiles under set_files/, instead of output_set.png
+    --dzi-size <WxH>    Full size of the pyramid in pixels (default 32768x18432), up to 16777216
+                        on a side
+    --roi <a,b,c,d>     In render, render exactly the rectangle from re = a to b and im = c to d,
+                        e.g. -0.8,-0.7,0.05,0.15, to output_set.png instead of the full view and r(θ)
+    --resolution <n>    Number of pixels along the longer side of the --roi rectangle (default 3960)
+    --max-memory <size> Render --roi in strips of rows, each written to output_set.png as it's done,
+                        to use at most about this much memory, e.g. 512M or 2G
+    --poster <dir>      Render --roi as overlapping tiles saved to the directory, skipping those
+                        already there, for stitch to join, instead of output_set.png
+    --tile <n>          Width and height of each --poster tile in pixels (default 2048)
+    --shard <i>/<n>     Only render every nth --poster tile starting from the ith, counting from 0, to
+                        split a poster over several runs or machines (default 0/1)
+    --iterations <n>    Stop iterating each pixel after n iterations instead of 1000
+    --width <n>         In from-image, the number of pixels across (default the recorded render's)
+    --escalate <n>      In render, start with a limit of 256 iterations and raise it fourfold each pass
+                        up to n, resuming only the pixels that reached the last limit
+    --escalate-tolerance <f>
+                        Stop escalating early once a pass lets at most this fraction of the pixels
+                        escape, or 0 to only stop once none do (default 0.0001)
+    --budget <time>     In render, stop starting rows once the time, e.g. 60s, 5m, or 1h, is up and copy
+                        each one left out from the nearest rendered row above, or with --escalate,
+                        stop raising the limit
+    --auto-iterations   In render, pick the iteration limit from the escape counts of a pass eight
+                        times coarser, up to 16384, and report it
+    --save-iterations <path>
+                        In render, save the iteration buffer for recolor to color again later
+    --palette <path>    In recolor, cycle escaped pixels through the colors of a Fractint MAP file, one
+                        `red green blue` line each, instead of the cosine palette
+    --theme <name>      Color escaped pixels by escape time through a built-in theme: classic, grayscale,
+                        fire, viridis, or the color-blind safe cividis or okabe-ito. Render and the
+                        other commands color the set black and white otherwise, and recolor uses the
+                        cosine palette
+    --simulate-cvd <deficiency>
+                        In render and analyze, also write the set and the plots as seen with
+                        protanopia, deuteranopia, or tritanopia, e.g. to output_plot_deuteranopia.png
+    --candidates <n>    In discover, the number of random points to try (default 1000)
+    --results <n>       In discover, the number of views to report (default 10)
+    --preperiod <n>     In misiurewicz, the number of steps before the orbit of 0 lands on its cycle, at
+                        least 2
+    --period <n>        In misiurewicz, the length of the cycle, and in nucleus, the period of the
+                        component
+    --near <c>          In misiurewicz and nucleus, where Newton's method starts, e.g. -0.1+0.95i
+    --close-up <width>  In misiurewicz and nucleus, also render a view this wide around the point, with
+                        the point marked, to output_misiurewicz.png or output_nucleus.png
+    --output <path>     Where recolor writes the image (default output_recolor.png), dive the keyframes
+                        (default output_dive.keyframes), stitch the poster (default
+                        output_poster.png, or a TIFF if it ends in .tif or .tiff), or diff the
+                        heatmap (default output_diff.png)
+    --cache <dir>       In render, reuse the rendered set from an earlier run with the same fractal,
+                        script, and size, kept in this directory, instead of rendering it again
+    --downscale <n,...> In render, also write output_set.png shrunk by each factor, e.g. 2,4,8 to
+                        output_set_2.png, output_set_4.png, and output_set_8.png
+    --port <n>          Port to serve tiles on (default 8080)
+    --bind <address>    Address to listen on (default 127.0.0.1, only this machine); 0.0.0.0 lets
+                        any host on the network submit jobs
+    --tile-cache <n>    Number of rendered tiles to keep in memory (default 1024); tiles also use
+                        --antialias and get --iteration-growth more iterations per zoom level
+    --levels <n>        In validate, the number of levels, each doubling the resolution and
+                        iteration limit from 128x128 at 256 iterations (default 5)
+    --at <θ,...>        In analyze, only print the refined radius and every membership interval at the
+                        given angles in radians, e.g. 0,0.5236,3.1416
+    --ray-step <r>      Distance between membership tests along each ray in analyze (default 0.001)
+    --refine <tol>      Refine each boundary radius by bisection to within the given tolerance
+    --crossings <path>  Write every membership interval along each ray to a tab-separated file
+    --polar-origin <c>  Cast rays from the given complex number, e.g. -0.25+0i (default 0)
+    --samples <n>       Number of evenly spaced angles to sample (default 1000)
+    --theta-range <a:b> Only sample angles from a to b radians, e.g. 2.8:3.5 around the antenna; the
+                        samples are spread over the sub-range, so it's sampled more densely
+    --adaptive <tol>    Bisect angular gaps across which r changes by more than the tolerance
+    --overlay <rays>    Draw the polar origin, the given number of rays, and every boundary point
+                        onto a copy of the rendered image in output_overlay.png
+    --annotate          Draw a coordinate grid with labeled real and imaginary ticks, a scale bar, and
+                        the center and polar origin onto a copy of the rendered image in
+                        output_annotated.png
+    --boundary          Trace the boundary of the rendered set with marching squares and plot it
+                        to output_boundary.png
+    --boundary-export <path>
+                        Write the traced boundary as SVG paths (.svg) or GeoJSON line strings
+                        (.geojson or .json) in complex-plane coordinates
+    --smooth-iterations <path>
+                        Write each pixel's continuous escape value, the iteration count interpolated
+                        between bands, to a grayscale PFM file
+    --mesh <path>       Write the continuous escape values as a closed heightfield mesh in OBJ (.obj)
+                        or binary STL (.stl), for Blender or 3D printing
+    --mesh-scale <f>    Height of the mesh per unit of ln(1 + escape value), with pixels a unit apart
+                        (default 50)
+    --mesh-step <n>     Take every nth pixel each way for the mesh's vertices (default 4)
+    --histogram <path>  Write how many pixels escaped after each number of iterations to a CSV file
+    --histogram-plot    Plot the number of pixels escaping after each number of iterations to
+                        output_histogram.png
+    --log-polar         Also render the set unrolled around the polar origin, with θ across and
+                        log r up, to output_log_polar.png
+    --polar-csv <path>  Write the (θ, r) samples to a CSV file
+    --csv-crossings     Include every membership interval in the CSV file, one row per interval
+    --precision <n>     Number of decimal places in exported values (default: shortest exact)
+    --precision fixed64 In render and analyze, iterate the Mandelbrot or --julia set in Q4.60 fixed
+                        point instead of single-precision floating point, the same on every target;
+                        --escape can be norm, either, or sum, up to 2
+    --polar-json <path> Write the parameters, samples, summary, and multiple crossings as JSON
+    --wav <path>        Write the plotted curve as a mono 16-bit WAV file, one sample per angle,
+                        centered on its mean and scaled to full volume, to hear r(θ)
+    --sample-rate <hz>  Samples per second of the --wav file, which sets the pitch of each turn
+                        (default 44100)
+    --loops <n>         Number of times the --wav file plays the turn over (default 1)
+    --fourier <order>   Report the dominant harmonics of r(θ) and plot its reconstruction up to
+                        the given order to output_fourier.png
+    --power-spectrum <path>
+                        Write the power in each harmonic of r(θ) to a CSV file
+    --autocorrelation <path>
+                        Write the autocorrelation of r(θ) at lags up to π to a CSV file
+    --spectrum-plot     Plot the power spectrum and autocorrelation to output_spectrum.png
+    --smooth <method>   Smooth the plotted curve with moving-average:<window> or
+                        savitzky-golay:<window>
+    --interpolate <n>   Resample the plotted curve at n angles with a periodic cubic spline
+    --plot <path>       Write the r(θ) plot here instead of output_plot.png; a .svg extension
+                        writes SVG
+    --inline <protocol> Also display the rendered set and r(θ) plot in the terminal with the kitty,
+                        iterm, or sixel graphics protocol, or auto to detect it
+    --plot-title <text> Title the r(θ) plot
+    --plot-x-label <text>
+    --plot-y-label <text>
+                        Label the axes of the r(θ) plot
+    --plot-size <WxH>   Size of the r(θ) plot in pixels (default 1280x960)
+    --plot-dpi <n>      Scale fonts, margins, and lines as if printed at this DPI (default 96)
+    --plot-color <c>    Color of the r(θ) curve, a name or a hex triplet like #ff8000 (default red)
+    --plot-line-width <n>
+                        Width of the plotted lines (default 1)
+    --plot-y-range <a:b>
+                        Range of the r axis (default 0:2)
+    --theta-units <u>   Label the θ axis in radians (default), degrees, or pi (multiples of π)
+    --plot-grid <n>     Number of labeled grid lines along each axis (default 10)
+    --plot-raw          Draw the unsmoothed samples behind the smoothed curve
+    --derivative        Plot dr/dθ of the (smoothed) curve below r(θ)
+    --curvature         Plot the signed curvature of the (smoothed) curve below r(θ), and add it to
+                        the CSV file
+    --equipotential <n> Plot n equipotential curves of the Mandelbrot set's exterior to
+                        output_equipotential.png
+    --external-ray <t>  Trace the external ray of angle t, a fraction of a turn like 1/3, and report
+                        where it lands; when rendering, draw it to output_external_rays.png. May be
+                        repeated
+    --orbit <c>         Print whether and when the orbit of c escapes and, when rendering, draw it
+                        over the image in output_orbit.png
+    --components <p>    Mark the centers of hyperbolic components up to period p (at most 7) on the
+                        polar plot and, when rendering, on output_components.png
+    --spokes <n>        Also draw n evenly spaced internal rays of each component
+    --nuclei <p>        List the centers, periods, and approximate sizes of the hyperbolic components up
+                        to period p inside the view, found by Newton's method from atom domains
+    --nuclei-plot       When rendering, also ring each of the --nuclei on output_nuclei.png
+    --cardioid          Plot the analytic boundary of the main cardioid and period-2 bulb against
+                        the measured curve
+    --symmetry          Compare r(θ) with r(−θ), which the set's symmetry about the real axis makes
+                        equal, and report the largest difference as a measure of the scan's accuracy
+    --fit <model>       Fit r(θ) by least squares to a moved, scaled, and rotated copy of the main
+                        cardioid (cardioid) or of it and the period-2 bulb (cardioid-circle), and
+                        report the parameters and RMS residual
+    --fit-residual      Plot the residual of the fit below r(θ)
+    --area              Estimate the enclosed area by integrating ½∫r² dθ, both from r(θ) and
+                        from every membership interval along each ray
+    --monte-carlo <n>   Estimate the area from n random points in the view, with a 95% confidence
+                        interval
+    --arc-length        Estimate the length of the boundary from r(θ) and, when rendering, from
+                        the traced pixel boundary
+    --nice              Run at the lowest scheduling priority, so that a long render leaves the machine
+                        usable for anything else
+    --max-cpu <percent> Only use that share of the cores, by running fewer worker threads, e.g. 50
+    --seed <n>          Seed the random number generator (default: the current time)
+    --deterministic     Guarantee identical output on every run, seeding the random number generator
+                        with 0 unless --seed is given
+    -h, --help          Print this message";
+
+pub const FRAMES: usize = 30;
+
+// The seed --deterministic uses when --seed isn't given.
+pub const DETERMINISTIC_SEED: u64 = 0;
+
+// --at always refines, to this tolerance unless --refine gives another.
+pub const AT_TOLERANCE: f32 = 1e-6;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Command {
+    #[default]
+    Render,
+    Analyze,
+    Plot,
+    Animate,
+    Serve,
+    Validate,
+    Verify,
+    Selftest,
+    Recolor,
+    Discover,
+    Misiurewicz,
+    Nucleus,
+    Dive,
+    Stitch,
+    Diff,
+    FromImage
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub command: Command,
+    pub compare: Vec<PathBuf>,
+    pub fractal: Option<String>,
+    pub script: Option<PathBuf>,
+    pub julia: Option<Complex<f32>>,
+    pub escape: Option<Escape>,
+    pub julia_sweep: Option<(Complex<f32>, Complex<f32>)>,
+    pub frames: usize,
+    pub target: Option<Complex<f32>>,
+    pub morph: Option<animation::Morph>,
+    pub keyframes: Option<PathBuf>,
+    pub inset: bool,
+    pub zoom: f32,
+    pub iteration_growth: u32,
+    pub antialias: u32,
+    pub frame_size: (u32, u32),
+    pub encoding: animation::Encoding,
+    pub animation: Option<PathBuf>,
+    pub dzi: Option<PathBuf>,
+    pub dzi_size: (u32, u32),
+    // The lower left and upper right corners.
+    pub roi: Option<(Complex<f32>, Complex<f32>)>,
+    pub resolution: u32,
+    pub max_memory: Option<u64>,
+    pub poster: Option<PathBuf>,
+    pub tile: u32,
+    pub shard: (u32, u32),
+    pub downscale: Vec<u32>,
+    pub cache: Option<PathBuf>,
+    pub iterations: Option<u32>,
+    pub escalate: Option<u32>,
+    pub save_iterations: Option<PathBuf>,
+    pub recolor: Option<PathBuf>,
+    pub stitch: Option<PathBuf>,
+    pub diff: Option<(PathBuf, PathBuf)>,
+    pub from_image: Option<PathBuf>,
+    pub width: Option<u32>,
+    // What follows the image in from-image, parsed after the recorded parameters.
+    pub overrides: Vec<String>,
+    pub palette: Option<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub theme: Option<String>,
+    pub simulate_cvd: Option<Deficiency>,
+    pub candidates: usize,
+    pub results: usize,
+    pub preperiod: Option<u32>,
+    pub period: Option<u32>,
+    pub near: Option<Complex<f64>>,
+    pub close_up: Option<f32>,
+    pub escalate_tolerance: f64,
+    pub budget: Option<Duration>,
+    pub auto_iterations: bool,
+    pub port: Option<u16>,
+    pub bind: Option<IpAddr>,
+    pub tile_cache: Option<usize>,
+    pub levels: usize,
+    pub at: Vec<f32>,
+    pub ray_step: f32,
+    pub refine: Option<f32>,
+    pub crossings: Option<PathBuf>,
+    pub polar_origin: Complex<f32>,
+    pub domain: Domain,
+    pub samples: usize,
+    pub adaptive: Option<f32>,
+    pub overlay: Option<usize>,
+    pub annotate: bool,
+    pub boundary: bool,
+    pub boundary_export: Option<PathBuf>,
+    pub smooth_iterations: Option<PathBuf>,
+    pub mesh: Option<PathBuf>,
+    pub mesh_scale: f32,
+    pub mesh_step: u32,
+    pub histogram: Option<PathBuf>,
+    pub histogram_plot: bool,
+    pub log_polar: bool,
+    pub polar_csv: Option<PathBuf>,
+    pub wav: Option<PathBuf>,
+    pub sample_rate: u32,
+    pub loops: u32,
+    pub csv_crossings: bool,
+    pub precision: Option<usize>,
+    pub fixed: bool,
+    pub polar_json: Option<PathBuf>,
+    pub fourier: Option<usize>,
+    pub power_spectrum: Option<PathBuf>,
+    pub autocorrelation: Option<PathBuf>,
+    pub spectrum_plot: bool,
+    pub smooth: Option<Smoothing>,
+    pub interpolate: Option<usize>,
+    pub plot: PathBuf,
+    pub inline: Option<Protocol>,
+    pub plot_style: PlotStyle,
+    pub plot_raw: bool,
+    pub derivative: bool,
+    pub curvature: bool,
+    pub equipotential: Option<usize>,
+    pub external_rays: Vec<Angle>,
+    pub orbit: Option<Complex<f32>>,
+    pub components: Option<u32>,
+    pub nuclei: Option<u32>,
+    pub nuclei_plot: bool,
+    pub spokes: usize,
+    pub cardioid: bool,
+    pub symmetry: bool,
+    pub fit: Option<Model>,
+    pub fit_residual: bool,
+    pub area: bool,
+    pub monte_carlo: Option<usize>,
+    pub seed: Option<u64>,
+    pub deterministic: bool,
+    pub nice: bool,
+    pub max_cpu: Option<u32>,
+    pub arc_length: bool,
+    pub help: bool
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            command: Command::default(),
+            compare: Vec::new(),
+            fractal: None,
+            script: None,
+            julia: None,
+            escape: None,
+            julia_sweep: None,
+            frames: FRAMES,
+            target: None,
+            morph: None,
+            keyframes: None,
+            inset: false,
+            zoom: animation::ZOOM,
+            iteration_growth: animation::ITERATION_GROWTH,
+            antialias: 1,
+            frame_size: animation::FRAME_SIZE,
+            encoding: animation::Encoding::default(),
+            animation: None,
+            dzi: None,
+            dzi_size: dzi::SIZE,
+            roi: None,
+            resolution: IMG_WIDTH,
+            max_memory: None,
+            poster: None,
+            tile: poster::TILE,
+            shard: (0, 1),
+            downscale: Vec::new(),
+            cache: None,
+            iterations: None,
+            escalate: None,
+            save_iterations: None,
+            recolor: None,
+            stitch: None,
+            diff: None,
+            from_image: None,
+            width: None,
+            overrides: Vec::new(),
+            palette: None,
+            output: None,
+            theme: None,
+            simulate_cvd: None,
+            candidates: discover::CANDIDATES,
+            results: discover::RESULTS,
+            preperiod: None,
+            period: None,
+            near: None,
+            close_up: None,
+            escalate_tolerance: render::ESCALATION_TOLERANCE,
+            budget: None,
+            auto_iterations: false,
+            port: None,
+            bind: None,
+            tile_cache: None,
+            levels: validate::LEVELS,
+            at: Vec::new(),
+            ray_step: polar_mandelbrot::polar::RAY_STEP,
+            refine: None,
+            crossings: None,
+            polar_origin: Complex::new(0.0, 0.0),
+            domain: Domain::Full,
+            samples: polar_mandelbrot::polar::DOMAIN_SIZE,
+            adaptive: None,
+            overlay: None,
+            annotate: false,
+            boundary: false,
+            boundary_export: None,
+            smooth_iterations: None,
+            mesh: None,
+            mesh_scale: mesh::SCALE,
+            mesh_step: mesh::STEP,
+            histogram: None,
+            histogram_plot: false,
+            log_polar: false,
+            polar_csv: None,
+            wav: None,
+            sample_rate: export::SAMPLE_RATE,
+            loops: 1,
+            csv_crossings: false,
+            precision: None,
+            fixed: false,
+            polar_json: None,
+            fourier: None,
+            power_spectrum: None,
+            autocorrelation: None,
+            spectrum_plot: false,
+            smooth: None,
+            interpolate: None,
+            plot: PathBuf::from("output_plot.png"),
+            inline: None,
+            plot_style: PlotStyle::default(),
+            plot_raw: false,
+            derivative: false,
+            curvature: false,
+            equipotential: None,
+            external_rays: Vec::new(),
+            orbit: None,
+            components: None,
+            nuclei: None,
+            nuclei_plot: false,
+            spokes: 0,
+            cardioid: false,
+            symmetry: false,
+            fit: None,
+            fit_residual: false,
+            area: false,
+            monte_carlo: None,
+            seed: None,
+            deterministic: false,
+            nice: false,
+            max_cpu: None,
+            arc_length: false,
+            help: false
+        }
+    }
+}
+
+fn value<T: FromStr>(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<T, String> {
+    let value = args.next().ok_or_else(|| format!("{} requires a value", flag))?;
+
+    value.parse().map_err(|_| format!("invalid value `{}` for {}", value, flag))
+}
+
+fn pair<T: FromStr>(value: &str, separator: char, flag: &str) -> Result<(T, T), String> {
+    let invalid = || format!("invalid value `{}` for {}", value, flag);
+
+    let (a, b) = value.split_once(separator).ok_or_else(invalid)?;
+
+    Ok((a.parse().map_err(|_| invalid())?, b.parse().map_err(|_| invalid())?))
+}
+
+// A length of time, optionally fractional, in seconds or with a suffix of s, m, or h.
+fn duration(value: &str, flag: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid time `{}` for {}", value, flag);
+
+    let (number, unit) = match value.chars().last() {
+        Some('s') => (&value[..value.len() - 1], 1.0),
+        Some('m') => (&value[..value.len() - 1], 60.0),
+        Some('h') => (&value[..value.len() - 1], 3600.0),
+        _ => (value, 1.0)
+    };
+
+    let seconds = number.parse::<f64>().ok().map(|number| number * unit);
+
+    seconds.and_then(|seconds| Duration::try_from_secs_f64(seconds).ok()).ok_or_else(invalid)
+}
+
+// A number of bytes, optionally with a binary suffix: K, M, G, or T.
+fn bytes(value: &str, flag: &str) -> Result<u64, String> {
+    let invalid = || format!("invalid size `{}` for {}", value, flag);
+
+    let (number, shift) = match value.chars().last().map(|suffix| suffix.to_ascii_uppercase()) {
+        Some('K') => (&value[..value.len() - 1], 10),
+        Some('M') => (&value[..value.len() - 1], 20),
+        Some('G') => (&value[..value.len() - 1], 30),
+        Some('T') => (&value[..value.len() - 1], 40),
+        _ => (value, 0)
+    };
+
+    number.parse::<u64>().ok().and_then(|number| number.checked_mul(1 << shift)).ok_or_else(invalid)
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+
+    let mut args = args.into_iter().peekable();
+
+    match args.peek().map(String::as_str) {
+        Some("render") => {
+            args.next();
+        }
+
+        Some("analyze") => {
+            options.command = Command::Analyze;
+
+            args.next();
+        }
+
+        Some("plot") => {
+            options.command = Command::Plot;
+
+            args.next();
+        }
+
+        Some("animate") => {
+            options.command = Command::Animate;
+
+            args.next();
+        }
+
+        Some("serve") => {
+            options.command = Command::Serve;
+
+            args.next();
+        }
+
+        // Only the area can be validated so far, but it's named so other checks can join it.
+        Some("validate") => {
+            options.command = Command::Validate;
+
+            args.next();
+
+            if args.next().as_deref() != Some("area") {
+                return Err("validate requires what to validate: area".to_string());
+            }
+        }
+
+        Some("verify") => {
+            options.command = Command::Verify;
+
+            args.next();
+        }
+
+        Some("selftest") => {
+            options.command = Command::Selftest;
+
+            args.next();
+        }
+
+        Some("recolor") => {
+            options.command = Command::Recolor;
+
+            args.next();
+
+            match args.next_if(|arg| !arg.starts_with("--")) {
+                Some(path) => options.recolor = Some(PathBuf::from(path)),
+                None => return Err("recolor requires the iteration buffer to color".to_string())
+            }
+        }
+
+        Some("discover") => {
+            options.command = Command::Discover;
+
+            args.next();
+        }
+
+        Some("misiurewicz") => {
+            options.command = Command::Misiurewicz;
+
+            args.next();
+        }
+
+        Some("nucleus") => {
+            options.command = Command::Nucleus;
+
+            args.next();
+        }
+
+        Some("dive") => {
+            options.command = Command::Dive;
+
+            args.next();
+        }
+
+        Some("stitch") => {
+            options.command = Command::Stitch;
+
+            args.next();
+
+            match args.next_if(|arg| !arg.starts_with("--")) {
+                Some(path) => options.stitch = Some(PathBuf::from(path)),
+                None => return Err("stitch requires the directory of tiles to join".to_string())
+            }
+        }
+
+        Some("diff") => {
+            options.command = Command::Diff;
+
+            args.next();
+
+            let a = args.next_if(|arg| !arg.starts_with("--"));
+            let b = args.next_if(|arg| !arg.starts_with("--"));
+
+            match a.zip(b) {
+                Some((a, b)) => options.diff = Some((PathBuf::from(a), PathBuf::from(b))),
+                None => return Err("diff requires the two renders to compare".to_string())
+            }
+        }
+
+        Some("from-image") => {
+            options.command = Command::FromImage;
+
+            args.next();
+
+            match args.next_if(|arg| !arg.starts_with("--")) {
+                Some(path) => options.from_image = Some(PathBuf::from(path)),
+                None => return Err("from-image requires the image to render again".to_string())
+            }
+
+            // Only --width is taken now; the rest can only be checked once the recorded render is
+            // known, which takes reading the image.
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--width" => options.width = Some(value(&mut args, &arg)?),
+                    _ => options.overrides.push(arg)
+                }
+            }
+
+            if options.width == Some(0) {
+                return Err("--width must be positive".to_string());
+            }
+
+            return Ok(options);
+        }
+
+        _ => {}
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--compare" => {
+                while let Some(path) = args.next_if(|arg| !arg.starts_with("--")) {
+                    options.compare.push(PathBuf::from(path));
+                }
+
+                if options.compare.is_empty() {
+                    return Err("--compare requires at least one path".to_string());
+                }
+            }
+
+            "--fractal" => options.fractal = Some(value(&mut args, &arg)?),
+            "--script" => options.script = Some(value(&mut args, &arg)?),
+            "--julia" => options.julia = Some(value(&mut args, &arg)?),
+            "--escape" => options.escape = Some(value::<String>(&mut args, &arg)?.parse()?),
+            "--julia-sweep" => options.julia_sweep = Some((value(&mut args, &arg)?, value(&mut args, &arg)?)),
+            "--frames" => options.frames = value(&mut args, &arg)?,
+            "--target" => options.target = Some(value(&mut args, &arg)?),
+            "--morph" => options.morph = Some(value::<String>(&mut args, &arg)?.parse()?),
+            "--keyframes" => options.keyframes = Some(value(&mut args, &arg)?),
+            "--inset" => options.inset = true,
+            "--zoom" => options.zoom = value(&mut args, &arg)?,
+            "--iteration-growth" => options.iteration_growth = value(&mut args, &arg)?,
+            "--antialias" => options.antialias = value(&mut args, &arg)?,
+            "--frame-size" => options.frame_size = pair(&value::<String>(&mut args, &arg)?, 'x', &arg)?,
+            "--frame-rate" => options.encoding.frame_rate = value(&mut args, &arg)?,
+            "--frame-delay" => options.encoding.frame_delay = Some(value(&mut args, &arg)?),
+            "--gif-speed" => options.encoding.gif_speed = value(&mut args, &arg)?,
+            "--y4m" => options.encoding.y4m = true,
+            "--animation" => options.animation = Some(value(&mut args, &arg)?),
+            "--dzi" => options.dzi = Some(value(&mut args, &arg)?),
+            "--dzi-size" => options.dzi_size = pair(&value::<String>(&mut args, &arg)?, 'x', &arg)?,
+
+            "--roi" => {
+                let roi = value::<String>(&mut args, &arg)?;
+
+                let bounds = roi
+                    .split(',')
+                    .map(|bound| bound.trim().parse::<f32>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|_| format!("invalid value `{}` for --roi", roi))?;
+
+                let [re_min, re_max, im_min, im_max] = bounds[..] else {
+                    return Err(format!("--roi takes four bounds, re_min,re_max,im_min,im_max, not `{}`", roi));
+                };
+
+                options.roi = Some((Complex::new(re_min, im_min), Complex::new(re_max, im_max)));
+            }
+
+            "--resolution" => options.resolution = value(&mut args, &arg)?,
+            "--max-memory" => options.max_memory = Some(bytes(&value::<String>(&mut args, &arg)?, &arg)?),
+            "--poster" => options.poster = Some(value(&mut args, &arg)?),
+            "--tile" => options.tile = value(&mut args, &arg)?,
+            "--shard" => options.shard = pair(&value::<String>(&mut args, &arg)?, '/', &arg)?,
+            "--cache" => options.cache = Some(value(&mut args, &arg)?),
+            "--iterations" => options.iterations = Some(value(&mut args, &arg)?),
+            "--escalate" => options.escalate = Some(value(&mut args, &arg)?),
+            "--save-iterations" => options.save_iterations = Some(value(&mut args, &arg)?),
+            "--palette" => options.palette = Some(value(&mut args, &arg)?),
+            "--output" => options.output = Some(value(&mut args, &arg)?),
+            "--theme" => options.theme = Some(value(&mut args, &arg)?),
+            "--simulate-cvd" => options.simulate_cvd = Some(value::<String>(&mut args, &arg)?.parse()?),
+            "--candidates" => options.candidates = value(&mut args, &arg)?,
+            "--results" => options.results = value(&mut args, &arg)?,
+            "--preperiod" => options.preperiod = Some(value(&mut args, &arg)?),
+            "--period" => options.period = Some(value(&mut args, &arg)?),
+            "--near" => options.near = Some(value(&mut args, &arg)?),
+            "--close-up" => options.close_up = Some(value(&mut args, &arg)?),
+            "--escalate-tolerance" => options.escalate_tolerance = value(&mut args, &arg)?,
+            "--budget" => options.budget = Some(duration(&value::<String>(&mut args, &arg)?, &arg)?),
+            "--auto-iterations" => options.auto_iterations = true,
+            "--downscale" => {
+                let factors = value::<String>(&mut args, &arg)?;
+
+                for factor in factors.split(',') {
+                    let parsed = factor.trim().parse();
+
+                    options.downscale.push(parsed.map_err(|_| format!("invalid factor `{}` for --downscale", factor))?);
+                }
+            }
+            "--port" => options.port = Some(value(&mut args, &arg)?),
+            "--bind" => options.bind = Some(value(&mut args, &arg)?),
+            "--tile-cache" => options.tile_cache = Some(value(&mut args, &arg)?),
+            "--levels" => options.levels = value(&mut args, &arg)?,
+            "--at" => {
+                let angles = value::<String>(&mut args, &arg)?;
+
+                for angle in angles.split(',') {
+                    options.at.push(angle.trim().parse().map_err(|_| format!("invalid angle `{}` for --at", angle))?);
+                }
+            }
+
+            "--ray-step" => options.ray_step = value(&mut args, &arg)?,
+            "--refine" => options.refine = Some(value(&mut args, &arg)?),
+            "--crossings" => options.crossings = Some(value(&mut args, &arg)?),
+            "--polar-origin" => options.polar_origin = value(&mut args, &arg)?,
+            "--theta-range" => {
+                let (start, end) = pair(&value::<String>(&mut args, &arg)?, ':', &arg)?;
+
+                options.domain = Domain::Range(start, end);
+            }
+
+            "--samples" => options.samples = value(&mut args, &arg)?,
+            "--adaptive" => options.adaptive = Some(value(&mut args, &arg)?),
+            "--overlay" => options.overlay = Some(value(&mut args, &arg)?),
+            "--annotate" => options.annotate = true,
+            "--boundary" => options.boundary = true,
+            "--boundary-export" => options.boundary_export = Some(value(&mut args, &arg)?),
+            "--smooth-iterations" => options.smooth_iterations = Some(value(&mut args, &arg)?),
+            "--mesh" => options.mesh = Some(value(&mut args, &arg)?),
+            "--mesh-scale" => options.mesh_scale = value(&mut args, &arg)?,
+            "--mesh-step" => options.mesh_step = value(&mut args, &arg)?,
+            "--histogram" => options.histogram = Some(value(&mut args, &arg)?),
+            "--histogram-plot" => options.histogram_plot = true,
+            "--log-polar" => options.log_polar = true,
+            "--polar-csv" => options.polar_csv = Some(value(&mut args, &arg)?),
+            "--wav" => options.wav = Some(value(&mut args, &arg)?),
+            "--sample-rate" => options.sample_rate = value(&mut args, &arg)?,
+            "--loops" => options.loops = value(&mut args, &arg)?,
+            "--csv-crossings" => options.csv_crossings = true,
+            "--precision" => match args.next_if(|value| value == "fixed64") {
+                Some(_) => options.fixed = true,
+                None => options.precision = Some(value(&mut args, &arg)?)
+            },
+            "--polar-json" => options.polar_json = Some(value(&mut args, &arg)?),
+            "--fourier" => options.fourier = Some(value(&mut args, &arg)?),
+            "--power-spectrum" => options.power_spectrum = Some(value(&mut args, &arg)?),
+            "--autocorrelation" => options.autocorrelation = Some(value(&mut args, &arg)?),
+            "--spectrum-plot" => options.spectrum_plot = true,
+            "--smooth" => {
+                let method = args.next().ok_or("--smooth requires a value")?;
+
+                options.smooth = Some(method.parse()?);
+            }
+
+            "--interpolate" => options.interpolate = Some(value(&mut args, &arg)?),
+            "--plot" => options.plot = value(&mut args, &arg)?,
+            "--plot-title" => options.plot_style.title = Some(value(&mut args, &arg)?),
+            "--plot-x-label" => options.plot_style.x_label = Some(value(&mut args, &arg)?),
+            "--plot-y-label" => options.plot_style.y_label = Some(value(&mut args, &arg)?),
+            "--plot-size" => options.plot_style.size = pair(&value::<String>(&mut args, &arg)?, 'x', &arg)?,
+            "--plot-dpi" => options.plot_style.dpi = value(&mut args, &arg)?,
+            "--plot-color" => options.plot_style.color = plot::parse_color(&value::<String>(&mut args, &arg)?)?,
+            "--plot-line-width" => options.plot_style.line_width = value(&mut args, &arg)?,
+            "--plot-y-range" => options.plot_style.y_range = Some(pair(&value::<String>(&mut args, &arg)?, ':', &arg)?),
+            "--theta-units" => options.plot_style.theta_units = value::<String>(&mut args, &arg)?.parse()?,
+            "--plot-grid" => options.plot_style.grid = value(&mut args, &arg)?,
+            "--inline" => options.inline = Some(value::<String>(&mut args, &arg)?.parse()?),
+            "--plot-raw" => options.plot_raw = true,
+            "--derivative" => options.derivative = true,
+            "--curvature" => options.curvature = true,
+            "--equipotential" => options.equipotential = Some(value(&mut args, &arg)?),
+            "--external-ray" => {
+                let angle = args.next().ok_or("--external-ray requires a value")?;
+
+                options.external_rays.push(angle.parse()?);
+            }
+
+            "--orbit" => options.orbit = Some(value(&mut args, &arg)?),
+            "--components" => options.components = Some(value(&mut args, &arg)?),
+            "--nuclei" => options.nuclei = Some(value(&mut args, &arg)?),
+            "--nuclei-plot" => options.nuclei_plot = true,
+            "--spokes" => options.spokes = value(&mut args, &arg)?,
+            "--cardioid" => options.cardioid = true,
+            "--symmetry" => options.symmetry = true,
+            "--fit" => options.fit = Some(value::<String>(&mut args, &arg)?.parse()?),
+            "--fit-residual" => options.fit_residual = true,
+            "--area" => options.area = true,
+            "--monte-carlo" => options.monte_carlo = Some(value(&mut args, &arg)?),
+            "--seed" => options.seed = Some(value(&mut args, &arg)?),
+            "--deterministic" => options.deterministic = true,
+            "--nice" => options.nice = true,
+            "--max-cpu" => options.max_cpu = Some(value(&mut args, &arg)?),
+            "--arc-length" => options.arc_length = true,
+            "-h" | "--help" => options.help = true,
+            _ => return Err(format!("unrecognized argument `{}`", arg))
+        }
+    }
+
+    if [options.fractal.is_some(), options.script.is_some(), options.julia.is_some()].iter().filter(|&&set| set).count() > 1 {
+        return Err("--fractal, --script, and --julia cannot be combined".to_string());
+    }
+
+    if let Some(theme) = &options.theme {
+        if Palette::theme(theme).is_none() {
+            let themes = palette::themes().collect::<Vec<_>>().join(", ");
+
+            return Err(format!("unknown theme `{}`, expected one of {}", theme, themes));
+        }
+
+        if options.script.is_some() || options.palette.is_some() {
+            return Err("--theme cannot be combined with --script or --palette, which color by themselves".to_string());
+        }
+    }
+
+    // Every command that renders a view averages this many samples per pixel.
+    if options.antialias == 0 {
+        return Err("--antialias must be positive".to_string());
+    }
+
+    if options.julia_sweep.is_some() {
+        if options.command != Command::Analyze {
+            return Err("--julia-sweep requires the analyze command".to_string());
+        }
+
+        if options.fractal.is_some() || options.script.is_some() || options.julia.is_some() {
+            return Err("--julia-sweep cannot be combined with --fractal, --script, or --julia".to_string());
+        }
+    }
+
+    if options.command == Command::Plot && options.compare.is_empty() {
+        return Err("the plot command requires --compare".to_string());
+    }
+
+    if !options.compare.is_empty() && options.command != Command::Plot {
+        return Err("--compare requires the plot command".to_string());
+    }
+
+    if options.command == Command::Animate {
+        let modes = [options.target.is_some(), options.morph.is_some(), options.keyframes.is_some()];
+
+        if modes.iter().filter(|&&set| set).count() != 1 {
+            return Err("the animate command requires exactly one of --target, --morph, and --keyframes".to_string());
+        }
+
+        let fractal = options.fractal.is_some() || options.script.is_some() || options.julia.is_some();
+
+        if options.morph.is_some() && fractal {
+            return Err("--morph cannot be combined with --fractal, --script, or --julia".to_string());
+        }
+
+        if options.inset && options.morph.is_none() {
+            return Err("--inset requires --morph".to_string());
+        }
+
+        if options.zoom.is_nan() || options.zoom < 1.0 {
+            return Err("--zoom must be at least 1".to_string());
+        }
+
+        if options.frame_size.0 == 0 || options.frame_size.1 == 0 {
+            return Err("--frame-size must be positive".to_string());
+        }
+
+        if render::buffer_len(options.frame_size, render::BYTES_PER_PIXEL).is_none() {
+            return Err("--frame-size is too large for a frame to be held in memory".to_string());
+        }
+
+        if options.encoding.frame_rate == 0 {
+            return Err("--frame-rate must be positive".to_string());
+        }
+
+        if !(1..=30).contains(&options.encoding.gif_speed) {
+            return Err("--gif-speed must be between 1 and 30".to_string());
+        }
+
+        if options.encoding.y4m && options.animation.as_deref() != Some(Path::new("-")) {
+            return Err("--y4m requires --animation -".to_string());
+        }
+    } else if options.command == Command::Dive {
+        if options.target.is_none() {
+            return Err("the dive command requires --target".to_string());
+        }
+
+        if options.morph.is_some() || options.keyframes.is_some() {
+            return Err("--morph and --keyframes require the animate command".to_string());
+        }
+
+        if options.zoom.is_nan() || options.zoom <= 1.0 {
+            return Err("--zoom must be greater than 1 for a dive".to_string());
+        }
+
+        if options.frame_size.0 == 0 || options.frame_size.1 == 0 {
+            return Err("--frame-size must be positive".to_string());
+        }
+    } else if options.target.is_some() || options.morph.is_some() || options.keyframes.is_some() {
+        return Err("--target, --morph, and --keyframes require the animate command".to_string());
+    } else if options.inset || options.animation.is_some() || options.encoding.y4m {
+        return Err("--inset, --animation, and --y4m require the animate command".to_string());
+    }
+
+    if options.dzi.is_some() && options.command != Command::Render {
+        return Err("--dzi requires the render command".to_string());
+    }
+
+    if options.dzi_size.0 == 0 || options.dzi_size.1 == 0 {
+        return Err("--dzi-size must be positive".to_string());
+    }
+
+    if options.dzi_size.0.max(options.dzi_size.1) > dzi::MAX_SIZE {
+        return Err(format!("--dzi-size must be at most {} pixels on a side", dzi::MAX_SIZE));
+    }
+
+    if let Some((low, high)) = options.roi {
+        if options.command != Command::Render || options.dzi.is_some() {
+            return Err("--roi requires the render command, without --dzi".to_string());
+        }
+
+        let finite = [low.re, low.im, high.re, high.im].iter().all(|bound| bound.is_finite());
+
+        if !finite || low.re >= high.re || low.im >= high.im {
+            return Err("--roi must be finite and increasing in both re and im".to_string());
+        }
+
+        if options.resolution == 0 {
+            return Err("--resolution must be positive".to_string());
+        }
+
+        let (width, height) = render::rect_size((low, high), options.resolution);
+
+        if options.poster.is_some() {
+            if options.max_memory.is_some() || !options.downscale.is_empty() {
+                return Err("--poster can't be combined with --max-memory or --downscale".to_string());
+            }
+
+            if width.max(height) > poster::MAX_SIZE {
+                return Err(format!("--resolution can be at most {} with --poster", poster::MAX_SIZE));
+            }
+
+            if options.tile == 0 {
+                return Err("--tile must be positive".to_string());
+            }
+
+            let tile = options.tile.saturating_add(poster::OVERLAP);
+
+            if render::buffer_len((tile, tile), render::BYTES_PER_PIXEL).is_none() {
+                return Err("--tile is too large for a tile to be held in memory".to_string());
+            }
+
+            if options.shard.1 == 0 || options.shard.0 >= options.shard.1 {
+                return Err("--shard must be i/n with n positive and i less than n".to_string());
+            }
+        }
+
+        // Under a budget, only a strip has to be held in memory at once, and a poster only a tile.
+        let rows = match options.max_memory {
+            Some(budget) => match strips::rows(width, budget) {
+                Some(rows) => rows.min(height),
+                None => return Err(format!("--max-memory is too small for even one row of {} pixels", width))
+            },
+
+            None if options.poster.is_some() => 1,
+            None => height
+        };
+
+        if render::buffer_len((width, rows), render::BYTES_PER_PIXEL).is_none() {
+            return Err("--resolution is too large for the image to be held in memory; try --max-memory".to_string());
+        }
+
+        if options.max_memory.is_some() && !options.downscale.is_empty() {
+            return Err("--downscale needs the whole image, so it can't be used with --max-memory".to_string());
+        }
+    } else if options.resolution != IMG_WIDTH || options.max_memory.is_some() || options.poster.is_some() {
+        return Err("--resolution, --max-memory, and --poster require --roi".to_string());
+    }
+
+    if options.poster.is_none() && (options.tile != poster::TILE || options.shard != (0, 1)) {
+        return Err("--tile and --shard require --poster".to_string());
+    }
+
+    let whole_render = options.command == Command::Render && options.dzi.is_none() && options.roi.is_none();
+
+    if options.cache.is_some() && !whole_render {
+        return Err("--cache requires the render command, without --dzi or --roi".to_string());
+    }
+
+    if options.iterations == Some(0) {
+        return Err("--iterations must be positive".to_string());
+    }
+
+    // The other commands pick their limits themselves, or read them from --keyframes.
+    let limited = matches!(
+        options.command,
+        Command::Render | Command::Analyze | Command::Animate | Command::Serve | Command::Verify | Command::FromImage
+    );
+
+    if options.iterations.is_some() && (!limited || options.keyframes.is_some()) {
+        return Err(
+            "--iterations only applies to render, analyze, animate without --keyframes, serve, verify, and from-image"
+                .to_string()
+        );
+    }
+
+    if options.iterations.is_some() && (options.escalate.is_some() || options.auto_iterations) {
+        return Err("--iterations can't be combined with --escalate or --auto-iterations".to_string());
+    }
+
+    // A script's `limit` is fixed when it's loaded, before either picks the limit.
+    if options.script.is_some() && (options.escalate.is_some() || options.auto_iterations) {
+        return Err("--script can't be combined with --escalate or --auto-iterations".to_string());
+    }
+
+    if let Some(target) = options.escalate {
+        if !whole_render {
+            return Err("--escalate requires the render command, without --dzi or --roi".to_string());
+        }
+
+        if target == 0 {
+            return Err("--escalate must be positive".to_string());
+        }
+
+        if !(0.0..1.0).contains(&options.escalate_tolerance) {
+            return Err("--escalate-tolerance must be at least 0 and less than 1".to_string());
+        }
+    } else if options.escalate_tolerance != render::ESCALATION_TOLERANCE {
+        return Err("--escalate-tolerance requires --escalate".to_string());
+    }
+
+    if options.budget.is_some() {
+        if !whole_render {
+            return Err("--budget requires the render command, without --dzi or --roi".to_string());
+        }
+
+        if options.cache.is_some() {
+            return Err("--budget can't be combined with --cache, which would keep the unfinished render".to_string());
+        }
+
+        // The saved buffer doesn't record which rows were copied, so they'd read back as rendered.
+        if options.save_iterations.is_some() {
+            return Err("--budget can't be combined with --save-iterations, which would keep the copies".to_string());
+        }
+    }
+
+    if options.auto_iterations {
+        if !whole_render {
+            return Err("--auto-iterations requires the render command, without --dzi or --roi".to_string());
+        }
+
+        if options.escalate.is_some() {
+            return Err("--auto-iterations and --escalate cannot be combined".to_string());
+        }
+    }
+
+    if !options.downscale.is_empty() {
+        if options.command != Command::Render || options.dzi.is_some() {
+            return Err("--downscale requires the render command, without --dzi".to_string());
+        }
+
+        if options.downscale.iter().any(|factor| !(2..=render::MAX_DOWNSCALE).contains(factor)) {
+            return Err(format!("--downscale factors must be from 2 to {}", render::MAX_DOWNSCALE));
+        }
+    }
+
+    let served = options.command == Command::Serve || options.dzi.is_some() || options.roi.is_some();
+
+    if !served && (options.port.is_some() || options.bind.is_some() || options.tile_cache.is_some()) {
+        return Err("--port, --bind, and --tile-cache require the serve command".to_string());
+    }
+
+    let other_fractal =
+        options.fractal.is_some() || options.script.is_some() || options.julia.is_some() || options.escape.is_some();
+
+    if options.fixed {
+        if ![Command::Render, Command::Analyze].contains(&options.command) {
+            return Err("--precision fixed64 requires the render or analyze command".to_string());
+        }
+
+        if options.fractal.is_some() || options.script.is_some() {
+            return Err("--precision fixed64 only iterates z² + c, without --fractal or --script".to_string());
+        }
+
+        Fixed::new(options.julia, options.escape.unwrap_or(Escape::Norm(fractal::BAILOUT_RADIUS)))?;
+    }
+
+    if options.command == Command::Validate {
+        if other_fractal {
+            return Err(
+                "validate area is for the Mandelbrot set, not --fractal, --script, --julia, or --escape".to_string()
+            );
+        }
+
+        if !(2..=8).contains(&options.levels) {
+            return Err("--levels must be between 2 and 8".to_string());
+        }
+    } else if options.levels != validate::LEVELS {
+        return Err("--levels requires the validate command".to_string());
+    }
+
+    if options.simulate_cvd.is_some() && !whole_render && options.command != Command::Analyze {
+        return Err("--simulate-cvd requires the render command, without --dzi or --roi, or analyze".to_string());
+    }
+
+    if options.save_iterations.is_some() && !whole_render {
+        return Err("--save-iterations requires the render command, without --dzi or --roi".to_string());
+    }
+
+    if options.command == Command::Recolor {
+        if other_fractal {
+            return Err(
+                "recolor colors the saved buffer as it is, without --fractal, --script, --julia, or --escape"
+                    .to_string()
+            );
+        }
+    } else if options.palette.is_some() {
+        return Err("--palette requires the recolor command".to_string());
+    }
+
+    if [Command::Stitch, Command::Diff].contains(&options.command) && other_fractal {
+        return Err(
+            "stitch and diff take the files as they are, without --fractal, --script, --julia, or --escape".to_string()
+        );
+    }
+
+    let output = [Command::Recolor, Command::Dive, Command::Stitch, Command::Diff];
+
+    if options.output.is_some() && !output.contains(&options.command) {
+        return Err("--output requires the recolor, dive, stitch, or diff command".to_string());
+    }
+
+    if options.command == Command::Discover {
+        if other_fractal {
+            return Err(
+                "discover estimates distances to the Mandelbrot set, not --fractal, --script, --julia, or --escape"
+                    .to_string()
+            );
+        }
+
+        if options.candidates == 0 || options.results == 0 {
+            return Err("--candidates and --results must be positive".to_string());
+        }
+    } else if options.candidates != discover::CANDIDATES || options.results != discover::RESULTS {
+        return Err("--candidates and --results require the discover command".to_string());
+    }
+
+    if options.command == Command::Misiurewicz {
+        if other_fractal {
+            return Err(
+                "misiurewicz finds points of the Mandelbrot set, not --fractal, --script, --julia, or --escape"
+                    .to_string()
+            );
+        }
+
+        let (Some(preperiod), Some(period), Some(_)) = (options.preperiod, options.period, options.near) else {
+            return Err("misiurewicz requires --preperiod, --period, and --near".to_string());
+        };
+
+        if preperiod < 2 {
+            return Err("--preperiod must be at least 2; with 1, 0 would be periodic itself".to_string());
+        }
+
+        if period == 0 {
+            return Err("--period must be positive".to_string());
+        }
+
+        if preperiod.checked_add(period).is_none() {
+            return Err("--preperiod and --period are too large".to_string());
+        }
+    } else if options.command == Command::Nucleus {
+        if other_fractal {
+            return Err(
+                "nucleus finds centers in the Mandelbrot set, not --fractal, --script, --julia, or --escape"
+                    .to_string()
+            );
+        }
+
+        let (Some(period), Some(_)) = (options.period, options.near) else {
+            return Err("nucleus requires --period and --near".to_string());
+        };
+
+        if period == 0 {
+            return Err("--period must be positive".to_string());
+        }
+
+        if options.preperiod.is_some() {
+            return Err("--preperiod requires the misiurewicz command".to_string());
+        }
+    } else if options.preperiod.is_some() || options.period.is_some() || options.near.is_some() {
+        return Err("--preperiod, --period, and --near require the misiurewicz or nucleus command".to_string());
+    } else if options.close_up.is_some() {
+        return Err("--close-up requires the misiurewicz or nucleus command".to_string());
+    }
+
+    if options.close_up.is_some_and(|width| !width.is_finite() || width <= 0.0) {
+        return Err("--close-up must be positive".to_string());
+    }
+
+    if options.command == Command::Selftest && other_fractal {
+        return Err("selftest checks the Mandelbrot set, not --fractal, --script, --julia, or --escape".to_string());
+    }
+
+    let reference = options.fractal.is_none() && options.script.is_none() && options.escape.is_none();
+
+    if options.command == Command::Verify && !reference {
+        return Err(
+            "verify only has references for the Mandelbrot and Julia sets, not --fractal, --script, or --escape"
+                .to_string()
+        );
+    }
+
+    let style = &options.plot_style;
+
+    if style.size.0 == 0 || style.size.1 == 0 {
+        return Err("--plot-size must be positive".to_string());
+    }
+
+    if render::buffer_len(style.size, 3).is_none() {
+        return Err("--plot-size is too large for the plot to be held in memory".to_string());
+    }
+
+    if style.dpi == 0 {
+        return Err("--plot-dpi must be positive".to_string());
+    }
+
+    if style.y_range.is_some_and(|(min, max)| min.is_nan() || max.is_nan() || min >= max) {
+        return Err("--plot-y-range must be increasing".to_string());
+    }
+
+    if !options.at.is_empty() && options.command != Command::Analyze {
+        return Err("--at requires the analyze command".to_string());
+    }
+
+    if options.frames == 0 {
+        return Err("--frames must be positive".to_string());
+    }
+
+    if options.ray_step.is_nan() || options.ray_step <= 0.0 {
+        return Err("--ray-step must be positive".to_string());
+    }
+
+    if options.refine.is_some_and(|tolerance| tolerance.is_nan() || tolerance <= 0.0) {
+        return Err("--refine must be positive".to_string());
+    }
+
+    if options.samples == 0 {
+        return Err("--samples must be positive".to_string());
+    }
+
+    let spectrum = options.power_spectrum.is_some() || options.autocorrelation.is_some() || options.spectrum_plot;
+
+    if let Domain::Range(start, end) = options.domain {
+        if !start.is_finite() || !end.is_finite() || start >= end {
+            return Err("--theta-range must be increasing".to_string());
+        }
+
+        if end - start > std::f32::consts::PI * 2.0 {
+            return Err("--theta-range can span at most a full turn".to_string());
+        }
+
+        if options.fourier.is_some()
+            || spectrum
+            || options.smooth.is_some()
+            || options.interpolate.is_some()
+            || options.derivative
+            || options.curvature
+            || options.arc_length
+            || options.area
+            || options.csv_crossings
+        {
+            return Err(
+                "--theta-range can't be combined with --fourier, the spectrum options, --smooth, --interpolate, \
+                 --derivative, --curvature, --arc-length, --area, or --csv-crossings, which need the whole turn"
+                    .to_string()
+            );
+        }
+    }
+
+    if options.monte_carlo == Some(0) {
+        return Err("--monte-carlo must be positive".to_string());
+    }
+
+    if options.adaptive.is_some_and(|tolerance| tolerance.is_nan() || tolerance <= 0.0) {
+        return Err("--adaptive must be positive".to_string());
+    }
+
+    if options.adaptive.is_some()
+        && (options.fourier.is_some()
+            || spectrum
+            || options.smooth.is_some()
+            || options.interpolate.is_some()
+            || options.derivative
+            || options.curvature
+            || options.arc_length
+            || options.csv_crossings
+            || options.wav.is_some())
+    {
+        return Err(
+            "--adaptive can't be combined with --fourier, the spectrum options, --smooth, --interpolate, --derivative, \
+             --curvature, --arc-length, --csv-crossings, or --wav, which need evenly spaced samples"
+                .to_string()
+        );
+    }
+
+    if options.annotate && !whole_render {
+        return Err("--annotate requires the render command, without --dzi or --roi".to_string());
+    }
+
+    if options.overlay.is_some() && options.command != Command::Render {
+        return Err("--overlay requires the render command".to_string());
+    }
+
+    if options.boundary && options.command != Command::Render {
+        return Err("--boundary requires the render command".to_string());
+    }
+
+    if let Some(path) = &options.boundary_export {
+        if options.command != Command::Render {
+            return Err("--boundary-export requires the render command".to_string());
+        }
+
+        if !matches!(path.extension().and_then(|extension| extension.to_str()), Some("svg" | "geojson" | "json")) {
+            return Err("--boundary-export must end in .svg, .geojson, or .json".to_string());
+        }
+    }
+
+    if options.smooth_iterations.is_some() && options.command != Command::Render {
+        return Err("--smooth-iterations requires the render command".to_string());
+    }
+
+    if let Some(path) = &options.mesh {
+        if !whole_render {
+            return Err("--mesh requires the render command, without --dzi or --roi".to_string());
+        }
+
+        if !matches!(path.extension().and_then(|extension| extension.to_str()), Some("obj" | "stl")) {
+            return Err("--mesh must end in .obj or .stl".to_string());
+        }
+
+        if !options.mesh_scale.is_finite() || options.mesh_scale <= 0.0 {
+            return Err("--mesh-scale must be positive".to_string());
+        }
+
+        if options.mesh_step == 0 {
+            return Err("--mesh-step must be positive".to_string());
+        }
+    } else if options.mesh_scale != mesh::SCALE || options.mesh_step != mesh::STEP {
+        return Err("--mesh-scale and --mesh-step require --mesh".to_string());
+    }
+
+    if (options.histogram.is_some() || options.histogram_plot) && options.command != Command::Render {
+        return Err("--histogram and --histogram-plot require the render command".to_string());
+    }
+
+    let mandelbrot = options.script.is_none()
+        && options.julia.is_none()
+        && options.fractal.as_deref().is_none_or(|name| name == "mandelbrot");
+
+    if options.equipotential.is_some() && !mandelbrot {
+        return Err("--equipotential only applies to the Mandelbrot set".to_string());
+    }
+
+    if options.orbit.is_some() && !matches!(options.command, Command::Render | Command::Analyze) {
+        return Err("--orbit requires the render or analyze command".to_string());
+    }
+
+    if !options.external_rays.is_empty() && !mandelbrot {
+        return Err("--external-ray only applies to the Mandelbrot set".to_string());
+    }
+
+    if options.components.is_some() && !mandelbrot {
+        return Err("--components only applies to the Mandelbrot set".to_string());
+    }
+
+    if options.components.is_some_and(|period| period == 0 || period > polar_mandelbrot::components::MAX_PERIOD) {
+        return Err(format!("--components must be between 1 and {}", polar_mandelbrot::components::MAX_PERIOD));
+    }
+
+    if options.spokes > 0 && options.components.is_none() {
+        return Err("--spokes requires --components".to_string());
+    }
+
+    if let Some(period) = options.nuclei {
+        if !mandelbrot {
+            return Err("--nuclei only applies to the Mandelbrot set".to_string());
+        }
+
+        if !matches!(options.command, Command::Render | Command::Analyze) {
+            return Err("--nuclei requires the render or analyze command".to_string());
+        }
+
+        if period == 0 || period > fractal::BAILOUT_ITERATIONS {
+            return Err(format!("--nuclei must be between 1 and {}", fractal::BAILOUT_ITERATIONS));
+        }
+    }
+
+    if options.nuclei_plot && (options.nuclei.is_none() || options.command != Command::Render) {
+        return Err("--nuclei-plot requires --nuclei and the render command".to_string());
+    }
+
+    if options.symmetry {
+        if options.polar_origin.im != 0.0 {
+            return Err("--symmetry requires a polar origin on the real axis".to_string());
+        }
+
+        if options.julia.is_some_and(|k| k.im != 0.0) {
+            return Err("--symmetry requires a real --julia parameter, or the set isn't symmetric".to_string());
+        }
+    }
+
+    if options.fit_residual && options.fit.is_none() {
+        return Err("--fit-residual requires --fit".to_string());
+    }
+
+    if options.wav.is_some() {
+        if options.sample_rate == 0 || options.loops == 0 {
+            return Err("--sample-rate and --loops must be positive".to_string());
+        }
+    } else if options.sample_rate != export::SAMPLE_RATE || options.loops != 1 {
+        return Err("--sample-rate and --loops require --wav".to_string());
+    }
+
+    if options.csv_crossings && options.polar_csv.is_none() {
+        return Err("--csv-crossings requires --polar-csv".to_string());
+    }
+
+    if options.max_cpu.is_some_and(|percent| !(1..=100).contains(&percent)) {
+        return Err("--max-cpu must be a percentage from 1 to 100".to_string());
+    }
+
+    // Rendering and marching are already reproducible, since every pixel and ray is computed on its
+    // own and reductions run in order; only the random samples depend on the seed.
+    if options.deterministic {
+        options.seed.get_or_insert(DETERMINISTIC_SEED);
+    }
+
+    Ok(options)
+}
@@ -0,0 +1,383 @@
+// Finds the centers of the Mandelbrot set's low-period hyperbolic components, where 0 is periodic,
+// and traces their internal rays, along which the multiplier of the attracting cycle has a fixed
+// argument. It also finds the nuclei, the centers, of components of higher period inside a view,
+// such as the minibrots deep in a zoom.
+
+use crate::fractal::{BAILOUT_ITERATIONS, BAILOUT_RADIUS};
+use crate::viewport::{self, coordinate_to_complex, IMG_HEIGHT, IMG_WIDTH};
+use num::complex::Complex;
+
+pub const MAX_PERIOD: u32 = 7;
+
+// Newton's method is started from a grid this many points across the region containing every
+// center.
+const SEEDS: (u32, u32) = (500, 250);
+const SEED_REGION: (Complex<f64>, Complex<f64>) = (Complex::new(-2.0, -1.25), Complex::new(0.5, 1.25));
+
+const NEWTON_STEPS: u32 = 64;
+const TOLERANCE: f64 = 1e-10;
+
+// The number of points along each internal ray, from the center to the boundary.
+const SPOKE_STEPS: u32 = 64;
+
+// The atom domains that seed the nuclei are found on a grid this many times coarser than the image.
+pub const NUCLEUS_SCALE: u32 = 4;
+
+#[derive(Clone, Debug)]
+pub struct Component {
+    pub period: u32,
+    pub center: Complex<f64>,
+    pub spokes: Vec<Vec<Complex<f64>>>
+}
+
+pub fn center_newton(period: u32, mut c: Complex<f64>) -> Option<Complex<f64>> {
+    for _ in 0..NEWTON_STEPS {
+        let mut z = Complex::new(0.0, 0.0);
+        let mut dc = Complex::new(0.0, 0.0);
+
+        for _ in 0..period {
+            dc = 2.0 * z * dc + 1.0;
+            z = z * z + c;
+        }
+
+        let step = z / dc;
+
+        if !step.re.is_finite() || !step.im.is_finite() {
+            return None;
+        }
+
+        c -= step;
+
+        if step.norm() < TOLERANCE {
+            return Some(c);
+        }
+    }
+
+    None
+}
+
+// Like center_newton, but to the limit of double precision, and with z_p divided by z_d for every
+// proper divisor d of the period, which vanishes at the centers of those periods, so that Newton's
+// method isn't drawn to them.
+fn deflated_newton(period: u32, mut c: Complex<f64>) -> Option<Complex<f64>> {
+    for _ in 0..NEWTON_STEPS {
+        let mut z = Complex::new(0.0, 0.0);
+        let mut dc = Complex::new(0.0, 0.0);
+
+        // The sum of the derivatives of log z_d for the proper divisors d of the period.
+        let mut divisors = Complex::new(0.0, 0.0);
+
+        for n in 1..=period {
+            dc = 2.0 * z * dc + 1.0;
+            z = z * z + c;
+
+            if n < period && period.is_multiple_of(n) {
+                divisors += dc / z;
+            }
+        }
+
+        // Exactly on the center, the step would divide by zero.
+        if z == Complex::new(0.0, 0.0) {
+            break;
+        }
+
+        let step = 1.0 / (dc / z - divisors);
+
+        if !step.re.is_finite() || !step.im.is_finite() {
+            return None;
+        }
+
+        c -= step;
+
+        if step.norm() < f64::EPSILON * 4.0 * c.norm().max(1.0) {
+            break;
+        }
+    }
+
+    has_period(period, c).then_some(c)
+}
+
+// |z_p| divided by |z_d| for the proper divisors d of the period, as deflated_newton solves for, or
+// None if the orbit escapes first, where Newton's method would overflow.
+fn deflated_modulus(period: u32, c: Complex<f64>) -> Option<f64> {
+    let mut z = Complex::new(0.0, 0.0);
+    let mut divisors = 1.0;
+
+    for n in 1..=period {
+        z = z * z + c;
+
+        if z.norm() >= BAILOUT_RADIUS as f64 {
+            return None;
+        }
+
+        if n < period && period.is_multiple_of(n) {
+            divisors *= z.norm();
+        }
+    }
+
+    Some(z.norm() / divisors).filter(|modulus| modulus.is_finite())
+}
+
+// The search for a nucleus starts in a square this far from the seed each way, with this many grid
+// steps across, and grows this many times wider until it finds one inside or covers the whole set.
+const SEARCH_START: f64 = 1e-3;
+const SEARCH_STEPS: u32 = 32;
+const SEARCH_GROWTH: f64 = 4.0;
+const SEARCH_END: f64 = 4.0;
+
+// The number of most promising grid points Newton's method is started from in each square.
+const SEARCH_SEEDS: usize = 8;
+
+// The center of the component of the period nearest the seed, to double precision. Newton's method
+// from the seed itself rarely works for high periods, since the orbit usually escapes and
+// overflows first, so it's started from the grid points of a square around the seed where the
+// orbit stays bounded and z_p, deflated, is nearest 0, and the square grows until a center turns up
+// inside it.
+pub fn nucleus(period: u32, seed: Complex<f64>) -> Option<Complex<f64>> {
+    let mut nearest: Option<Complex<f64>> = None;
+
+    let mut radius = SEARCH_START;
+
+    while radius <= SEARCH_END {
+        let mut candidates = (0..=SEARCH_STEPS)
+            .flat_map(|y| (0..=SEARCH_STEPS).map(move |x| (x, y)))
+            .filter_map(|(x, y)| {
+                let offset = Complex::new(x as f64, y as f64) * (2.0 * radius / SEARCH_STEPS as f64);
+
+                let c = seed - Complex::new(radius, radius) + offset;
+
+                deflated_modulus(period, c).map(|modulus| (modulus, c))
+            })
+            .collect::<Vec<_>>();
+
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        for &(_, c) in candidates.iter().take(SEARCH_SEEDS) {
+            let Some(center) = deflated_newton(period, c) else {
+                continue;
+            };
+
+            if nearest.is_none_or(|nearest| (center - seed).norm() < (nearest - seed).norm()) {
+                nearest = Some(center);
+            }
+        }
+
+        if nearest.is_some_and(|nearest| (nearest - seed).norm() <= radius) {
+            break;
+        }
+
+        radius *= SEARCH_GROWTH;
+    }
+
+    nearest
+}
+
+// Whether 0 first returns to itself after exactly `period` iterations, rather than a divisor of it.
+pub fn has_period(period: u32, c: Complex<f64>) -> bool {
+    let mut z = Complex::new(0.0, 0.0);
+
+    (1..=period).all(|n| {
+        z = z * z + c;
+
+        (n == period) == (z.norm() < 1e-6)
+    })
+}
+
+pub fn centers(period: u32) -> Vec<Complex<f64>> {
+    let (min, max) = SEED_REGION;
+
+    let mut centers: Vec<Complex<f64>> = Vec::new();
+
+    for y in 0..SEEDS.1 {
+        for x in 0..SEEDS.0 {
+            let seed = Complex::new(
+                min.re + (max.re - min.re) * (x as f64 + 0.5) / SEEDS.0 as f64,
+                min.im + (max.im - min.im) * (y as f64 + 0.5) / SEEDS.1 as f64
+            );
+
+            let center = match center_newton(period, seed) {
+                Some(center) if has_period(period, center) => center,
+                _ => continue
+            };
+
+            if centers.iter().all(|&other| (other - center).norm() > 1e-6) {
+                centers.push(center);
+            }
+        }
+    }
+
+    centers
+}
+
+// Follows the internal ray of the given angle, in turns, by solving f_cᵖ(z) = z and (f_cᵖ)'(z) = λ
+// for z and c as the multiplier λ grows from 0 at the center to modulus 1 on the boundary.
+pub fn spoke(period: u32, center: Complex<f64>, angle: f64) -> Vec<Complex<f64>> {
+    let mut z = Complex::new(0.0, 0.0);
+    let mut c = center;
+
+    let mut spoke = vec![c];
+
+    for step in 1..=SPOKE_STEPS {
+        let multiplier = Complex::from_polar(step as f64 / SPOKE_STEPS as f64, angle * std::f64::consts::TAU);
+
+        for _ in 0..NEWTON_STEPS {
+            // The orbit of z, with its first and second derivatives with respect to z and c.
+            let mut w = z;
+            let mut dz = Complex::new(1.0, 0.0);
+            let mut dc = Complex::new(0.0, 0.0);
+            let mut dzz = Complex::new(0.0, 0.0);
+            let mut dzc = Complex::new(0.0, 0.0);
+
+            for _ in 0..period {
+                dzz = 2.0 * (dz * dz + w * dzz);
+                dzc = 2.0 * (dz * dc + w * dzc);
+                dz *= 2.0 * w;
+                dc = 2.0 * w * dc + 1.0;
+                w = w * w + c;
+            }
+
+            let (f, g) = (w - z, dz - multiplier);
+
+            let (a, b, d, e) = (dz - 1.0, dc, dzz, dzc);
+
+            let determinant = a * e - b * d;
+
+            let step_z = (e * f - b * g) / determinant;
+            let step_c = (a * g - d * f) / determinant;
+
+            if !step_z.re.is_finite() || !step_c.re.is_finite() {
+                return spoke;
+            }
+
+            z -= step_z;
+            c -= step_c;
+
+            if step_z.norm() + step_c.norm() < TOLERANCE {
+                break;
+            }
+        }
+
+        spoke.push(c);
+    }
+
+    spoke
+}
+
+pub fn find(max_period: u32, spokes: usize) -> Vec<Component> {
+    (1..=max_period)
+        .flat_map(|period| {
+            centers(period).into_iter().map(move |center| Component {
+                period,
+                center,
+                spokes: (0..spokes).map(|i| spoke(period, center, i as f64 / spokes as f64)).collect()
+            })
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct Nucleus {
+    pub period: u32,
+    pub center: Complex<f64>,
+    // About how wide the component is, in the plane.
+    pub size: f64
+}
+
+// The size estimate 1 / |βλ²|, where λ is the product of 2z along the cycle up to the last step and
+// β sums the reciprocals of its partial products.
+pub fn size(period: u32, c: Complex<f64>) -> f64 {
+    let mut z = Complex::new(0.0, 0.0);
+    let mut product = Complex::new(1.0, 0.0);
+    let mut sum = Complex::new(1.0, 0.0);
+
+    for _ in 1..period {
+        z = z * z + c;
+        product *= 2.0 * z;
+        sum += 1.0 / product;
+    }
+
+    1.0 / (sum * product * product).norm()
+}
+
+// The atom domain of c: the step at most `max_period` at which |z| was smallest before the orbit
+// escaped or ran out of iterations, with that |z|. Near a nucleus of period p, z_p is near 0.
+fn atom_domain(max_period: u32, c: Complex<f64>) -> (u32, f64) {
+    let mut z = Complex::new(0.0, 0.0);
+
+    let mut domain = (1, f64::INFINITY);
+
+    for n in 1..=BAILOUT_ITERATIONS {
+        z = z * z + c;
+
+        if z.norm() >= BAILOUT_RADIUS as f64 {
+            break;
+        }
+
+        if n <= max_period && z.norm() < domain.1 {
+            domain = (n, z.norm());
+        }
+    }
+
+    domain
+}
+
+// Every nucleus up to `max_period` in the image's view, largest first. Each grid point where |z_p|
+// is smaller than at its neighbors in the same atom domain of period p seeds Newton's method.
+pub fn nuclei(max_period: u32) -> Vec<Nucleus> {
+    let (width, height) = (IMG_WIDTH / NUCLEUS_SCALE, IMG_HEIGHT / NUCLEUS_SCALE);
+
+    let point = |x: u32, y: u32| {
+        let c = coordinate_to_complex((x * NUCLEUS_SCALE + NUCLEUS_SCALE / 2, y * NUCLEUS_SCALE + NUCLEUS_SCALE / 2));
+
+        Complex::new(c.re as f64, c.im as f64)
+    };
+
+    let domains = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| atom_domain(max_period, point(x, y)))
+        .collect::<Vec<_>>();
+
+    let domain = |x: u32, y: u32| domains[(y * width + x) as usize];
+
+    let (low, high) = viewport::bounds();
+
+    let inside = |c: Complex<f64>| {
+        (low.re as f64..=high.re as f64).contains(&c.re) && (low.im as f64..=high.im as f64).contains(&c.im)
+    };
+
+    let mut nuclei: Vec<Nucleus> = Vec::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            let (period, modulus) = domain(x, y);
+
+            let mut neighbors = (y.saturating_sub(1)..(y + 2).min(height))
+                .flat_map(|ny| (x.saturating_sub(1)..(x + 2).min(width)).map(move |nx| (nx, ny)))
+                .filter(|&neighbor| neighbor != (x, y));
+
+            // Ties, such as between points mirrored across the real axis, go to the first in order.
+            let minimum = neighbors.all(|(nx, ny)| {
+                let (p, m) = domain(nx, ny);
+
+                p != period || m > modulus || (m == modulus && (ny, nx) > (y, x))
+            });
+
+            if !minimum {
+                continue;
+            }
+
+            let center = match center_newton(period, point(x, y)) {
+                Some(center) if inside(center) && has_period(period, center) => center,
+                _ => continue
+            };
+
+            if nuclei.iter().all(|other| (other.center - center).norm() > 1e-9) {
+                nuclei.push(Nucleus { period, center, size: size(period, center) });
+            }
+        }
+    }
+
+    nuclei.sort_by(|a, b| b.size.total_cmp(&a.size));
+
+    nuclei
+}
@@ -0,0 +1,104 @@
+// Simulates how images look with a color vision deficiency, so that figures can be checked for
+// color-blind viewers. Each color is converted to linear RGB, mapped through Machado, Oliveira, and
+// Fernandes's matrix for the full deficiency, and converted back.
+
+use image::{ImageResult, RgbImage};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Deficiency {
+    // Missing long-wavelength (red) cones.
+    Protanopia,
+    // Missing medium-wavelength (green) cones, the most common.
+    Deuteranopia,
+    // Missing short-wavelength (blue) cones.
+    Tritanopia
+}
+
+impl FromStr for Deficiency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "protanopia" => Ok(Self::Protanopia),
+            "deuteranopia" => Ok(Self::Deuteranopia),
+            "tritanopia" => Ok(Self::Tritanopia),
+            _ => Err(format!("unknown deficiency `{}`; expected protanopia, deuteranopia, or tritanopia", s))
+        }
+    }
+}
+
+impl Deficiency {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Protanopia => "protanopia",
+            Self::Deuteranopia => "deuteranopia",
+            Self::Tritanopia => "tritanopia"
+        }
+    }
+
+    fn matrix(self) -> [[f32; 3]; 3] {
+        match self {
+            Self::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998]
+            ],
+
+            Self::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881]
+            ],
+
+            Self::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900]
+            ]
+        }
+    }
+}
+
+fn to_linear(channel: u8) -> f32 {
+    let value = channel as f32 / 255.0;
+
+    if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+}
+
+fn to_srgb(value: f32) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+
+    let encoded = if value <= 0.0031308 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 };
+
+    (encoded * 255.0).round() as u8
+}
+
+pub fn simulate(image: &mut RgbImage, deficiency: Deficiency) {
+    let matrix = deficiency.matrix();
+
+    // Images take few enough distinct colors that converting each once saves most of the work.
+    let linear = (0..=255).map(to_linear).collect::<Vec<_>>();
+
+    for pixel in image.pixels_mut() {
+        let rgb = pixel.0.map(|channel| linear[channel as usize]);
+
+        pixel.0 = matrix.map(|row| to_srgb(row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]));
+    }
+}
+
+// Writes the image at the path as seen with the deficiency next to it, as name_deficiency.png.
+pub fn simulate_file(path: &Path, deficiency: Deficiency) -> ImageResult<PathBuf> {
+    let mut image = image::open(path)?.to_rgb8();
+
+    simulate(&mut image, deficiency);
+
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+    let output = path.with_file_name(format!("{}_{}.png", stem, deficiency.name()));
+
+    image.save(&output)?;
+
+    Ok(output)
+}
@@ -0,0 +1,94 @@
+// Compares two renders pixel by pixel, either as images or as saved iteration buffers, to check one
+// backend or precision against another. The result is summarized and drawn as a heatmap through
+// the fire theme, black where the two agree and brightest at the largest difference, on a log
+// scale so that small disagreements still show next to large ones.
+
+use crate::palette::Palette;
+use crate::render::IterationBuffer;
+use image::{ImageBuffer, RgbImage};
+
+pub struct Difference {
+    pub pixels: u64,
+    pub differing: u64,
+    // Over all pixels: in the largest channel for images, in escape counts for buffers.
+    pub max: f64,
+    pub mean: f64,
+    pub rms: f64,
+    // For buffers, the pixels that are members in one and escaped in the other.
+    pub membership: Option<u64>,
+    pub heatmap: RgbImage
+}
+
+impl Difference {
+    // The peak signal-to-noise ratio in decibels for images, or None if they're identical.
+    pub fn psnr(&self) -> Option<f64> {
+        (self.membership.is_none() && self.rms > 0.0).then(|| 20.0 * (255.0 / self.rms).log10())
+    }
+}
+
+fn compare(size: (u32, u32), difference: impl Fn(u32, u32) -> f64) -> Difference {
+    let differences = (0..size.1).flat_map(|y| (0..size.0).map(move |x| (x, y))).map(|(x, y)| difference(x, y));
+    let differences = differences.collect::<Vec<_>>();
+
+    let pixels = differences.len() as u64;
+
+    let max = differences.iter().copied().fold(0.0, f64::max);
+
+    let fire = Palette::theme("fire").expect("fire is a built-in theme");
+
+    // at() wraps around, so stopping a color short of a full turn ends on the brightest.
+    let end = (fire.colors.len() - 1) as f32 / fire.colors.len() as f32;
+
+    let heat = |difference: f64| {
+        let t = if max > 0.0 { difference.ln_1p() / max.ln_1p() } else { 0.0 };
+
+        fire.at(t as f32 * end)
+    };
+
+    let heatmap = ImageBuffer::from_fn(size.0, size.1, |x, y| heat(differences[(y * size.0 + x) as usize]));
+
+    Difference {
+        pixels,
+        differing: differences.iter().filter(|&&difference| difference > 0.0).count() as u64,
+        max,
+        mean: differences.iter().sum::<f64>() / pixels.max(1) as f64,
+        rms: (differences.iter().map(|difference| difference * difference).sum::<f64>() / pixels.max(1) as f64).sqrt(),
+        membership: None,
+        heatmap
+    }
+}
+
+fn same_size(a: (u32, u32), b: (u32, u32)) -> Result<(), String> {
+    if a == b {
+        Ok(())
+    } else {
+        Err(format!("the renders are {}x{} and {}x{}, so they can't be compared", a.0, a.1, b.0, b.1))
+    }
+}
+
+// Each pixel's difference is that of the channel that differs most.
+pub fn images(a: &RgbImage, b: &RgbImage) -> Result<Difference, String> {
+    same_size(a.dimensions(), b.dimensions())?;
+
+    Ok(compare(a.dimensions(), |x, y| {
+        let (p, q) = (a.get_pixel(x, y), b.get_pixel(x, y));
+
+        (0..3).map(|channel| p[channel].abs_diff(q[channel])).max().unwrap_or(0) as f64
+    }))
+}
+
+// Each pixel's difference is that of its escape counts; a member's count is the limit it reached.
+pub fn buffers(a: &IterationBuffer, b: &IterationBuffer) -> Result<Difference, String> {
+    let size = |buffer: &IterationBuffer| (buffer.width(), buffer.height());
+
+    same_size(size(a), size(b))?;
+
+    let mut difference = compare(size(a), |x, y| a.sample(x, y).iterations.abs_diff(b.sample(x, y).iterations) as f64);
+
+    let membership = (0..a.height()).flat_map(|y| (0..a.width()).map(move |x| (x, y)));
+    let membership = membership.filter(|&(x, y)| a.is_member(x, y) != b.is_member(x, y)).count() as u64;
+
+    difference.membership = Some(membership);
+
+    Ok(difference)
+}
@@ -0,0 +1,115 @@
+// Finds views of the Mandelbrot set worth a closer look, such as zoom targets for animate. Random
+// points outside the set are framed by their distance estimate, so each view holds a piece of the
+// boundary about as wide as the point is from it, and the views are ranked by how much the escape
+// counts vary across them: plain exterior and solid interior vary little, filaments and spirals a
+// lot.
+
+use crate::fractal::{Mandelbrot, BAILOUT_ITERATIONS};
+use crate::random::Random;
+use crate::render;
+use crate::viewport::{self, View};
+use num::complex::Complex;
+use std::sync::Arc;
+
+// Orbits carry on well past the escape radius so that the estimate converges.
+const DISTANCE_BAILOUT_RADIUS: f64 = 1e4;
+
+// How many distance estimates wide each view is.
+const FRAME: f32 = 16.0;
+
+// Views narrower than this zoom in past 10⁴, where single precision starts to show, and views
+// wider than this fraction of the default one hardly zoom in at all.
+const MIN_WIDTH: f32 = 4e-4;
+const MAX_WIDTH_FRACTION: f32 = 0.25;
+
+// The size of the grid each view is scored on, and of its thumbnail.
+const GRID: (u32, u32) = (32, 18);
+pub const THUMBNAIL: (u32, u32) = (320, 180);
+
+// The number of random points drawn, and of the best views kept, by default.
+pub const CANDIDATES: usize = 1000;
+pub const RESULTS: usize = 10;
+
+pub struct Discovery {
+    pub view: View,
+    pub score: f32
+}
+
+impl Discovery {
+    // The magnification relative to the default view, as animate's --zoom takes it.
+    pub fn zoom(&self) -> f32 {
+        View::default().width / self.view.width
+    }
+}
+
+// The distance from c to the Mandelbrot set estimated from the derivative of its orbit,
+// 2 |z| ln |z| / |dz/dc|, or None for members.
+pub fn distance(c: Complex<f32>) -> Option<f32> {
+    let c = Complex::new(c.re as f64, c.im as f64);
+
+    let mut z = Complex::new(0.0, 0.0);
+    let mut dz = Complex::new(0.0, 0.0);
+
+    for _ in 0..BAILOUT_ITERATIONS {
+        dz = 2.0 * z * dz + 1.0;
+        z = z * z + c;
+
+        if z.norm() >= DISTANCE_BAILOUT_RADIUS {
+            let distance = 2.0 * z.norm() * z.norm().ln() / dz.norm();
+
+            return distance.is_finite().then_some(distance as f32);
+        }
+    }
+
+    None
+}
+
+// The variance of the escape counts of the view's escaped pixels on the scoring grid.
+async fn score(view: View) -> f32 {
+    let histogram = render::render_view_iterations(Arc::new(Mandelbrot), GRID, view).await.histogram();
+
+    let pixels = histogram.iter().sum::<u64>() as f64;
+
+    if pixels == 0.0 {
+        return 0.0;
+    }
+
+    let moment = |power: i32| {
+        histogram.iter().enumerate().map(|(n, &count)| (n as f64).powi(power) * count as f64).sum::<f64>() / pixels
+    };
+
+    (moment(2) - moment(1).powi(2)).max(0.0) as f32
+}
+
+// Draws `candidates` random points from the default view and returns the best `results` of the
+// views around those near enough to the boundary, best first.
+pub async fn discover(candidates: usize, results: usize, random: &mut Random) -> Vec<Discovery> {
+    let (low, high) = viewport::bounds();
+
+    let max_width = View::default().width * MAX_WIDTH_FRACTION;
+
+    let mut discoveries = Vec::new();
+
+    for _ in 0..candidates {
+        let center = low + Complex::new((high.re - low.re) * random.next_f32(), (high.im - low.im) * random.next_f32());
+
+        let Some(distance) = distance(center) else {
+            continue;
+        };
+
+        let width = distance * FRAME;
+
+        if !(MIN_WIDTH..=max_width).contains(&width) {
+            continue;
+        }
+
+        let view = View { center, width };
+
+        discoveries.push(Discovery { view, score: score(view).await });
+    }
+
+    discoveries.sort_by(|a, b| b.score.total_cmp(&a.score));
+    discoveries.truncate(results);
+
+    discoveries
+}
@@ -0,0 +1,98 @@
+// Writes a Deep Zoom Image pyramid for OpenSeadragon: an XML descriptor at name.dzi and, under
+// name_files/, one directory of tiles per level, where level 0 is a single pixel and each level
+// doubles the size of the last up to the full image. Every tile is rendered directly at its level's
+// resolution rather than downsampled from the level below, so no level ever has to be held in
+// memory whole, and the full size can be far larger than any image that could be.
+
+use crate::fractal::Fractal;
+use crate::render::{self, Colorer};
+use crate::viewport::View;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub const TILE_SIZE: u32 = 256;
+pub const SIZE: (u32, u32) = (32768, 18432);
+
+// Tiles are placed by their pixel positions in single precision, which counts pixels exactly only
+// up to 2²⁴.
+pub const MAX_SIZE: u32 = 1 << 24;
+
+// The highest level, at which the image is at full size.
+pub fn levels((width, height): (u32, u32)) -> u32 {
+    width.max(height).next_power_of_two().trailing_zeros()
+}
+
+fn level_size((width, height): (u32, u32), level: u32) -> (u32, u32) {
+    let shift = levels((width, height)) - level;
+
+    (width.div_ceil(1 << shift).max(1), height.div_ceil(1 << shift).max(1))
+}
+
+fn descriptor((width, height): (u32, u32)) -> String {
+    format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<Image xmlns=\"http://schemas.microsoft.com/deepzoom/2008\"",
+            " Format=\"png\" Overlap=\"0\" TileSize=\"{}\">\n",
+            "  <Size Width=\"{}\" Height=\"{}\"/>\n",
+            "</Image>\n"
+        ),
+        TILE_SIZE,
+        width,
+        height
+    )
+}
+
+pub async fn write(
+    path: &Path,
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    view: View,
+    size: (u32, u32),
+    samples: u32
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut tiles_directory = PathBuf::from(path);
+
+    tiles_directory.set_extension("");
+
+    let tiles_directory = PathBuf::from(format!("{}_files", tiles_directory.display()));
+
+    for level in 0..=levels(size) {
+        let (width, height) = level_size(size, level);
+
+        let directory = tiles_directory.join(level.to_string());
+
+        std::fs::create_dir_all(&directory)?;
+
+        let (columns, rows) = (width.div_ceil(TILE_SIZE), height.div_ceil(TILE_SIZE));
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let (x, y) = (column * TILE_SIZE, row * TILE_SIZE);
+
+                let tile = ((width - x).min(TILE_SIZE), (height - y).min(TILE_SIZE));
+
+                // The level spans the same view as the full image, so each tile is the part of it
+                // under those pixels.
+                let middle = (x as f32 + tile.0 as f32 / 2.0, y as f32 + tile.1 as f32 / 2.0);
+
+                let tile_view = View {
+                    center: view.pixel_to_complex((width, height), middle),
+                    width: view.width * tile.0 as f32 / width as f32
+                };
+
+                let image = render::render_view(fractal.clone(), colorer.clone(), tile, tile_view, samples).await;
+
+                image.save(directory.join(format!("{}_{}.png", column, row)))?;
+            }
+        }
+
+        let tiles = columns as u64 * rows as u64;
+
+        eprintln!("dzi: level {} of {}, {}x{} in {} tiles", level, levels(size), width, height, tiles);
+    }
+
+    std::fs::write(path, descriptor(size))?;
+
+    Ok(())
+}
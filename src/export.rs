@@ -0,0 +1,463 @@
+use crate::analysis::{Area, ArcLength, Harmonic, MonteCarlo, Summary, Symmetry, PUBLISHED_AREA};
+use crate::json::Value;
+use crate::mesh::Mesh;
+use crate::polar::Crossing;
+use crate::render::{IterationBuffer, Statistics};
+use crate::viewport::bounds;
+use num::complex::Complex;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+fn format(value: f32, precision: Option<usize>) -> String {
+    match precision {
+        Some(precision) => format!("{:.*}", precision, value),
+        None => value.to_string()
+    }
+}
+
+pub fn write_crossings(
+    path: &Path,
+    crossings: &[Crossing],
+    precision: Option<usize>
+) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    writeln!(writer, "theta\tstart\tend")?;
+
+    for (theta, intervals) in crossings {
+        for (start, end) in intervals {
+            writeln!(
+                writer,
+                "{}\t{}\t{}",
+                format(*theta, precision),
+                format(*start, precision),
+                format(*end, precision)
+            )?;
+        }
+    }
+
+    writer.flush()
+}
+
+pub fn write_polar_csv(
+    path: &Path,
+    profile: &[(f32, f32)],
+    crossings: Option<&[Crossing]>,
+    curvature: Option<&[(f32, f32)]>,
+    precision: Option<usize>
+) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    let curvature_header = if curvature.is_some() { ",curvature" } else { "" };
+
+    let curvature_at = |i: usize| match curvature {
+        Some(curvature) => format!(",{}", format(curvature[i].1, precision)),
+        None => String::new()
+    };
+
+    match crossings {
+        Some(crossings) => {
+            writeln!(writer, "theta,r{},interval,start,end", curvature_header)?;
+
+            for (i, (&(theta, r), (_, intervals))) in profile.iter().zip(crossings).enumerate() {
+                for (j, (start, end)) in intervals.iter().enumerate() {
+                    writeln!(
+                        writer,
+                        "{},{}{},{},{},{}",
+                        format(theta, precision),
+                        format(r, precision),
+                        curvature_at(i),
+                        j,
+                        format(*start, precision),
+                        format(*end, precision)
+                    )?;
+                }
+            }
+        }
+
+        None => {
+            writeln!(writer, "theta,r{}", curvature_header)?;
+
+            for (i, &(theta, r)) in profile.iter().enumerate() {
+                writeln!(writer, "{},{}{}", format(theta, precision), format(r, precision), curvature_at(i))?;
+            }
+        }
+    }
+
+    writer.flush()
+}
+
+// Each pixel's continuous escape value as a grayscale PFM, the float counterpart of PGM that image
+// editors and numerical libraries read, whose rows run from the bottom up. Members keep their count.
+pub fn write_smooth_iterations(path: &Path, iterations: &IterationBuffer) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    // A negative scale marks the values as little-endian.
+    write!(writer, "Pf\n{} {}\n-1.0\n", iterations.width(), iterations.height())?;
+
+    for y in (0..iterations.height()).rev() {
+        for x in 0..iterations.width() {
+            let value = iterations.smooth(x, y).expect("the buffer keeps the continuous values");
+
+            writer.write_all(&value.to_le_bytes())?;
+        }
+    }
+
+    writer.flush()
+}
+
+// The mesh as Wavefront OBJ, whose vertex indices count from 1.
+pub fn write_obj(path: &Path, mesh: &Mesh) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    for [x, y, z] in &mesh.vertices {
+        writeln!(writer, "v {} {} {}", x, y, z)?;
+    }
+
+    for [a, b, c] in &mesh.triangles {
+        writeln!(writer, "f {} {} {}", a + 1, b + 1, c + 1)?;
+    }
+
+    writer.flush()
+}
+
+// The mesh as binary STL: an 80-byte header, the triangle count, and each triangle's normal and
+// corners followed by two unused bytes, all little-endian.
+pub fn write_stl(path: &Path, mesh: &Mesh) -> std::io::Result<()> {
+    let count = u32::try_from(mesh.triangles.len())
+        .map_err(|_| std::io::Error::other("the mesh has too many triangles for STL; raise --mesh-step"))?;
+
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    writer.write_all(&[0; 80])?;
+    writer.write_all(&count.to_le_bytes())?;
+
+    for &triangle in &mesh.triangles {
+        let corners = triangle.map(|vertex| mesh.vertices[vertex as usize]);
+
+        for value in std::iter::once(mesh.normal(triangle)).chain(corners).flatten() {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+
+        writer.write_all(&[0; 2])?;
+    }
+
+    writer.flush()
+}
+
+pub const SAMPLE_RATE: u32 = 44100;
+
+// The radii as a mono 16-bit PCM WAV, one audio sample each, played `loops` times over. They're
+// centered on their mean and scaled so the furthest from it is at full volume, since only the
+// shape of r(θ) is audible; a circle is silence.
+pub fn write_wav(path: &Path, profile: &[(f32, f32)], sample_rate: u32, loops: u32) -> std::io::Result<()> {
+    let data = (profile.len() as u64 * 2)
+        .checked_mul(loops as u64)
+        .filter(|&data| data <= (u32::MAX - 36) as u64)
+        .ok_or_else(|| std::io::Error::other("the WAV file would be over 4 GiB; lower --loops"))? as u32;
+
+    let mean = profile.iter().map(|&(_, r)| r as f64).sum::<f64>() / profile.len().max(1) as f64;
+
+    let peak = profile.iter().map(|&(_, r)| (r as f64 - mean).abs()).fold(0.0, f64::max);
+
+    let scale = if peak > 0.0 { i16::MAX as f64 / peak } else { 0.0 };
+
+    let samples = profile.iter().map(|&(_, r)| (((r as f64 - mean) * scale).round() as i16).to_le_bytes());
+    let samples = samples.flatten().collect::<Vec<_>>();
+
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(36 + data).to_le_bytes())?;
+    writer.write_all(b"WAVE")?;
+
+    // The format: PCM, one channel, the rate, bytes per second and per sample, and bits per sample.
+    writer.write_all(b"fmt ")?;
+    writer.write_all(&16u32.to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?;
+    writer.write_all(&1u16.to_le_bytes())?;
+    writer.write_all(&sample_rate.to_le_bytes())?;
+    writer.write_all(&sample_rate.saturating_mul(2).to_le_bytes())?;
+    writer.write_all(&2u16.to_le_bytes())?;
+    writer.write_all(&16u16.to_le_bytes())?;
+
+    writer.write_all(b"data")?;
+    writer.write_all(&data.to_le_bytes())?;
+
+    for _ in 0..loops {
+        writer.write_all(&samples)?;
+    }
+
+    writer.flush()
+}
+
+// One row per iteration count up to the highest, including those no pixel escaped at.
+pub fn write_histogram(path: &Path, histogram: &[u64]) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    writeln!(writer, "iterations,pixels")?;
+
+    for (iterations, pixels) in histogram.iter().enumerate() {
+        writeln!(writer, "{},{}", iterations, pixels)?;
+    }
+
+    writer.flush()
+}
+
+pub fn write_series<T: std::fmt::Display>(
+    path: &Path,
+    header: (&str, &str),
+    series: &[(T, f32)],
+    precision: Option<usize>
+) -> std::io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    writeln!(writer, "{},{}", header.0, header.1)?;
+
+    for (x, y) in series {
+        writeln!(writer, "{},{}", x, format(*y, precision))?;
+    }
+
+    writer.flush()
+}
+
+// Reads back the samples from a file written by write_polar_csv. A file with one row per membership
+// interval repeats each sample once per interval, so only the first row of each angle is kept.
+pub fn read_polar_csv(path: &Path) -> std::io::Result<Vec<(f32, f32)>> {
+    let invalid = |message: String| std::io::Error::new(std::io::ErrorKind::InvalidData, message);
+
+    let mut lines = BufReader::new(File::open(path)?).lines();
+
+    let header = lines.next().transpose()?.unwrap_or_default();
+
+    if !header.starts_with("theta,r") {
+        return Err(invalid("expected a `theta,r` header".to_string()));
+    }
+
+    let mut profile: Vec<(f32, f32)> = Vec::new();
+
+    for (i, line) in lines.enumerate() {
+        let line = line?;
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut fields = line.split(',').map(str::parse::<f32>);
+
+        let (Some(Ok(theta)), Some(Ok(r))) = (fields.next(), fields.next()) else {
+            return Err(invalid(format!("line {}: expected θ and r", i + 2)));
+        };
+
+        if profile.last().is_none_or(|&(previous, _)| previous != theta) {
+            profile.push((theta, r));
+        }
+    }
+
+    Ok(profile)
+}
+
+pub struct PolarReport<'a> {
+    pub parameters: Value,
+    pub profile: &'a [(f32, f32)],
+    pub summary: &'a Summary,
+    pub crossings: &'a [Crossing],
+    pub harmonics: Option<&'a [Harmonic]>,
+    pub area: Option<&'a Area>,
+    pub monte_carlo: Option<&'a MonteCarlo>,
+    pub arc_length: Option<&'a ArcLength>,
+    pub symmetry: Option<&'a Symmetry>,
+    pub render: Option<&'a Statistics>
+}
+
+pub fn polar_document(report: PolarReport) -> Value {
+    let PolarReport {
+        parameters,
+        profile,
+        summary,
+        crossings,
+        harmonics,
+        area,
+        monte_carlo,
+        arc_length,
+        symmetry,
+        render
+    } = report;
+
+    Value::object([
+        ("parameters", parameters),
+        (
+            "samples",
+            Value::array(profile.iter().map(|&(theta, r)| Value::object([("theta", theta.into()), ("r", r.into())])))
+        ),
+        (
+            "summary",
+            Value::object([
+                ("count", summary.count.into()),
+                ("min", summary.min.into()),
+                ("max", summary.max.into()),
+                ("theta_max", summary.theta_max.into()),
+                ("mean", summary.mean.into()),
+                ("variance", summary.variance.into()),
+                ("ratio", summary.ratio().into())
+            ])
+        ),
+        (
+            "crossings",
+            Value::array(crossings.iter().filter(|(_, intervals)| intervals.len() > 1).map(|(theta, intervals)| {
+                Value::object([
+                    ("theta", (*theta).into()),
+                    (
+                        "intervals",
+                        Value::array(intervals.iter().map(|&(start, end)| Value::array([start, end])))
+                    )
+                ])
+            }))
+        ),
+        (
+            "fourier",
+            harmonics.map_or(Value::Null, |harmonics| {
+                Value::array(harmonics.iter().map(|harmonic| {
+                    Value::object([
+                        ("order", harmonic.order.into()),
+                        ("cosine", harmonic.cosine.into()),
+                        ("sine", harmonic.sine.into()),
+                        ("amplitude", harmonic.amplitude().into()),
+                        ("phase", harmonic.phase().into())
+                    ])
+                }))
+            })
+        ),
+        (
+            "area",
+            area.map_or(Value::Null, |area| {
+                Value::object([
+                    ("star", area.star.into()),
+                    ("intervals", area.intervals.into()),
+                    ("pixels", area.pixels.into()),
+                    ("published", PUBLISHED_AREA.into())
+                ])
+            })
+        ),
+        (
+            "monte_carlo",
+            monte_carlo.map_or(Value::Null, |monte_carlo| {
+                let (low, high) = monte_carlo.confidence_interval();
+
+                Value::object([
+                    ("samples", monte_carlo.samples.into()),
+                    ("estimate", monte_carlo.estimate.into()),
+                    ("standard_error", monte_carlo.standard_error.into()),
+                    ("confidence_interval", Value::array([low, high]))
+                ])
+            })
+        ),
+        (
+            "arc_length",
+            arc_length.map_or(Value::Null, |arc_length| {
+                Value::object([("polar", arc_length.polar.into()), ("pixels", arc_length.pixels.into())])
+            })
+        ),
+        (
+            "symmetry",
+            symmetry.map_or(Value::Null, |symmetry| {
+                Value::object([
+                    ("compared", symmetry.compared.into()),
+                    ("max", symmetry.max.into()),
+                    ("theta_max", symmetry.theta_max.into()),
+                    ("rms", symmetry.rms.into()),
+                    ("exceeding", symmetry.exceeding.into()),
+                    ("resolution", symmetry.resolution.into())
+                ])
+            })
+        ),
+        (
+            "render",
+            render.map_or(Value::Null, |render| {
+                Value::object([
+                    ("pixels", render.pixels.into()),
+                    ("interior_fraction", render.interior_fraction().into()),
+                    ("escaped_fraction", render.escaped_fraction().into()),
+                    ("mean_iterations", render.mean_iterations.into()),
+                    ("max_iterations", render.max_iterations.into()),
+                    ("capped", render.capped.into()),
+                    ("rendered_rows", render.rendered_rows.into()),
+                    ("gap", render.gap.into())
+                ])
+            })
+        )
+    ])
+}
+
+pub fn write_json(path: &Path, value: &Value) -> std::io::Result<()> {
+    std::fs::write(path, format!("{}\n", value))
+}
+
+// The y axis is flipped so the path data stays in complex-plane coordinates.
+pub fn write_boundary_svg(
+    path: &Path,
+    polylines: &[Vec<Complex<f32>>],
+    precision: Option<usize>
+) -> std::io::Result<()> {
+    let (min, max) = bounds();
+
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    writeln!(
+        writer,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">",
+        format(min.re, precision),
+        format(-max.im, precision),
+        format(max.re - min.re, precision),
+        format(max.im - min.im, precision)
+    )?;
+
+    writeln!(
+        writer,
+        "<g transform=\"scale(1 -1)\" fill=\"none\" stroke=\"black\" stroke-width=\"1\">"
+    )?;
+
+    for polyline in polylines {
+        let points = polyline
+            .iter()
+            .map(|point| format!("{} {}", format(point.re, precision), format(point.im, precision)))
+            .collect::<Vec<_>>();
+
+        writeln!(writer, "<path vector-effect=\"non-scaling-stroke\" d=\"M {} Z\"/>", points.join(" L "))?;
+    }
+
+    writeln!(writer, "</g>")?;
+    writeln!(writer, "</svg>")?;
+
+    writer.flush()
+}
+
+pub fn boundary_document(polylines: &[Vec<Complex<f32>>]) -> Value {
+    Value::object([
+        ("type", "FeatureCollection".into()),
+        (
+            "features",
+            Value::array(polylines.iter().map(|polyline| {
+                Value::object([
+                    ("type", "Feature".into()),
+                    ("properties", Value::object::<&str>([])),
+                    (
+                        "geometry",
+                        Value::object([
+                            ("type", "LineString".into()),
+                            (
+                                "coordinates",
+                                Value::array(
+                                    polyline
+                                        .iter()
+                                        .map(|point| Value::array([point.re, point.im]))
+                                )
+                            )
+                        ])
+                    )
+                ])
+            }))
+        )
+    ])
+}
@@ -0,0 +1,118 @@
+// Traces external rays of the Mandelbrot set inward from far away by Newton's method. The ray of
+// external angle t is where the Böttcher coordinate Φ(c) ≈ (f_cⁿ(0))^(1/2ⁿ⁻¹) has argument 2πt,
+// so each point solves f_cⁿ(0) = ρ^(2ⁿ⁻¹) e^(2πi 2ⁿ⁻¹t) for a radius ρ that shrinks toward 1.
+
+use num::complex::Complex;
+use std::fmt;
+use std::str::FromStr;
+
+const ESCAPE_RADIUS: f64 = 64.0;
+
+// The number of times the iteration count goes up, and the number of points traced at each count.
+// Rays landing on parabolic points approach them slowly, so the depth is generous.
+const DEPTH: u32 = 1024;
+const SHARPNESS: u32 = 8;
+
+const NEWTON_STEPS: u32 = 16;
+
+// An external angle, in turns, kept exact so that doubling it never loses precision.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Angle {
+    numerator: u64,
+    denominator: u64
+}
+
+impl Angle {
+    fn doubled(self, times: u32) -> Self {
+        let mut numerator = self.numerator;
+
+        for _ in 0..times {
+            numerator = numerator * 2 % self.denominator;
+        }
+
+        Self { numerator, ..self }
+    }
+
+    fn turns(self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+}
+
+impl FromStr for Angle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid external angle `{}`; expected a fraction like 1/3", s);
+
+        let (numerator, denominator) = s.split_once('/').ok_or_else(invalid)?;
+
+        let numerator = numerator.trim().parse::<u64>().map_err(|_| invalid())?;
+        let denominator = denominator.trim().parse::<u64>().map_err(|_| invalid())?;
+
+        // Doubling the numerator mustn't overflow.
+        if denominator == 0 || denominator > u64::MAX / 2 {
+            return Err(invalid());
+        }
+
+        Ok(Self { numerator: numerator % denominator, denominator })
+    }
+}
+
+impl fmt::Display for Angle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.numerator, self.denominator)
+    }
+}
+
+// Returns the points along the ray from outside the view to (nearly) where it lands. Tracing stops
+// early once the points stop moving or Newton's method fails.
+pub fn trace(angle: Angle) -> Vec<Complex<f64>> {
+    let mut c = Complex::from_polar(ESCAPE_RADIUS, angle.turns() * std::f64::consts::TAU);
+
+    let mut ray = vec![c];
+
+    for k in 1..=DEPTH * SHARPNESS {
+        let n = k.div_ceil(SHARPNESS) + 1;
+
+        // ρ^(2ⁿ⁻¹) for ρ = R^(1/2^(k/S)), which stays between R and R² as n catches up with k.
+        let radius = ESCAPE_RADIUS.powf(2f64.powf((n - 1) as f64 - k as f64 / SHARPNESS as f64));
+
+        let target = Complex::from_polar(radius, angle.doubled(n - 1).turns() * std::f64::consts::TAU);
+
+        let mut next = c;
+
+        for _ in 0..NEWTON_STEPS {
+            let mut z = Complex::new(0.0, 0.0);
+            let mut dz = Complex::new(0.0, 0.0);
+
+            for _ in 0..n {
+                dz = 2.0 * z * dz + 1.0;
+                z = z * z + next;
+            }
+
+            let step = (z - target) / dz;
+
+            next -= step;
+
+            if step.norm() <= next.norm() * 1e-12 {
+                break;
+            }
+        }
+
+        if !next.re.is_finite() || !next.im.is_finite() {
+            break;
+        }
+
+        let moved = (next - c).norm();
+
+        c = next;
+
+        ray.push(c);
+
+        if moved < 1e-12 {
+            break;
+        }
+    }
+
+    ray
+}
@@ -0,0 +1,153 @@
+// A C API for embedding the renderer, declared in include/polar_mandelbrot.h. Build it as a shared
+// library with `cargo rustc --lib --release --features ffi --crate-type cdylib`. Functions that
+// can fail return 0 on success and -1 if an argument is invalid.
+
+use crate::fractal::{self, Fractal, Julia, Limited, BAILOUT_ITERATIONS};
+use crate::polar::{self, Domain, Ray};
+use crate::render::{self, MembershipColorer};
+use crate::viewport::View;
+use num::complex::Complex;
+use std::ffi::{c_char, c_int, CStr};
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+// Renders run on the renderer's own runtime, since C callers have none.
+pub struct Renderer {
+    fractal: Arc<dyn Fractal>,
+    runtime: Runtime
+}
+
+fn renderer(fractal: Arc<dyn Fractal>, iterations: u32) -> *mut Renderer {
+    let iterations = if iterations == 0 { BAILOUT_ITERATIONS } else { iterations };
+
+    match Runtime::new() {
+        Ok(runtime) => {
+            let fractal = Arc::new(Limited { fractal, iterations });
+
+            Box::into_raw(Box::new(Renderer { fractal, runtime }))
+        }
+
+        Err(_) => std::ptr::null_mut()
+    }
+}
+
+/// Creates a renderer for the named fractal (mandelbrot, magnet1, or magnet2, or mandelbrot if the
+/// name is null) with the given iteration limit, or the default if it's 0. Returns null if the
+/// name is unknown.
+///
+/// # Safety
+///
+/// `name` must be null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pm_renderer_new(name: *const c_char, iterations: u32) -> *mut Renderer {
+    let name = if name.is_null() { Ok("mandelbrot") } else { CStr::from_ptr(name).to_str() };
+
+    match name.ok().and_then(fractal::from_name) {
+        Some(fractal) => renderer(fractal, iterations),
+        None => std::ptr::null_mut()
+    }
+}
+
+/// Creates a renderer for the Julia set of k = re + im·i.
+#[no_mangle]
+pub extern "C" fn pm_renderer_new_julia(re: f32, im: f32, iterations: u32) -> *mut Renderer {
+    renderer(Arc::new(Julia { k: Complex::new(re, im) }), iterations)
+}
+
+/// Renders the view centered on (re, im) and `view_width` wide into `buffer` as 8-bit RGB rows,
+/// top to bottom, with members white and everything else black.
+///
+/// # Safety
+///
+/// `renderer` must come from one of the constructors and not have been freed, and `buffer` must
+/// hold `width * height * 3` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn pm_render(
+    renderer: *const Renderer,
+    re: f32,
+    im: f32,
+    view_width: f32,
+    width: u32,
+    height: u32,
+    samples: u32,
+    buffer: *mut u8
+) -> c_int {
+    let Some(renderer) = renderer.as_ref() else {
+        return -1;
+    };
+
+    if buffer.is_null() || width == 0 || height == 0 || samples == 0 || !view_width.is_finite() || view_width <= 0.0 {
+        return -1;
+    }
+
+    if render::buffer_len((width, height), render::BYTES_PER_PIXEL).is_none() {
+        return -1;
+    }
+
+    let view = View { center: Complex::new(re, im), width: view_width };
+
+    let image = renderer.runtime.block_on(render::render_view(
+        renderer.fractal.clone(),
+        Arc::new(MembershipColorer),
+        (width, height),
+        view,
+        samples
+    ));
+
+    std::slice::from_raw_parts_mut(buffer, image.as_raw().len()).copy_from_slice(image.as_raw());
+
+    0
+}
+
+/// Marches `angles` rays evenly spaced around the full circle from (re, im), writing each ray's
+/// angle to `thetas` and the radius at which it leaves the set to `radii`. `ray_step` must be at
+/// least 2⁻¹⁵.
+///
+/// # Safety
+///
+/// `renderer` must come from one of the constructors and not have been freed, and `thetas` and
+/// `radii` must each hold `angles` floats.
+#[no_mangle]
+pub unsafe extern "C" fn pm_polar_profile(
+    renderer: *const Renderer,
+    re: f32,
+    im: f32,
+    ray_step: f32,
+    angles: usize,
+    thetas: *mut f32,
+    radii: *mut f32
+) -> c_int {
+    let Some(renderer) = renderer.as_ref() else {
+        return -1;
+    };
+
+    if thetas.is_null() || radii.is_null() || angles == 0 || !ray_step.is_finite() || ray_step < polar::MIN_RAY_STEP {
+        return -1;
+    }
+
+    let thetas = std::slice::from_raw_parts_mut(thetas, angles);
+    let radii = std::slice::from_raw_parts_mut(radii, angles);
+
+    let origin = Complex::new(re, im);
+
+    let rays = thetas.iter_mut().zip(radii.iter_mut()).zip(polar::angles(Domain::Full, angles));
+
+    for ((theta, radius), angle) in rays {
+        *theta = angle;
+        *radius = polar::march_radius(renderer.fractal.as_ref(), Ray::new(origin, angle), ray_step).inside;
+    }
+
+    0
+}
+
+/// Frees a renderer. Null is ignored.
+///
+/// # Safety
+///
+/// `renderer` must be null or come from one of the constructors, and not have been freed already.
+#[no_mangle]
+pub unsafe extern "C" fn pm_renderer_free(renderer: *mut Renderer) {
+    if !renderer.is_null() {
+        drop(Box::from_raw(renderer));
+    }
+}
@@ -0,0 +1,235 @@
+// Fits r(θ) to the main cardioid of the Mandelbrot set, optionally together with its period-2 bulb,
+// moved, scaled, and rotated by c ↦ p + qc. Every minibrot's outline is close to such a copy, so
+// how well it fits measures how cardioid-like a region is, and p estimates its nucleus.
+
+use crate::polar::{Ray, MAX_RADIUS};
+use num::complex::Complex;
+use std::str::FromStr;
+
+// The model radius along each ray is found by marching this many steps out to where the ray leaves
+// the disk containing the model, or MAX_RADIUS if that's closer, and bisecting the last one.
+const MARCH_STEPS: u32 = 400;
+const BISECTIONS: u32 = 40;
+
+const MAX_ITERATIONS: u32 = 100;
+const DIFFERENCE_STEP: f64 = 1e-7;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Model {
+    Cardioid,
+    CardioidCircle
+}
+
+impl FromStr for Model {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cardioid" => Ok(Self::Cardioid),
+            "cardioid-circle" => Ok(Self::CardioidCircle),
+            _ => Err(format!("unknown model `{}`; expected cardioid or cardioid-circle", s))
+        }
+    }
+}
+
+impl Model {
+    fn contains(self, w: Complex<f64>) -> bool {
+        (1.0 - (1.0 - 4.0 * w).sqrt()).norm() <= 1.0 || (self == Self::CardioidCircle && (w + 1.0).norm() <= 0.25)
+    }
+
+    // Where the ray first leaves the model's image under c ↦ p + qc, or 0 if it starts outside it.
+    fn radius(self, ray: Ray, (p, q): (Complex<f64>, Complex<f64>)) -> f64 {
+        let origin = Complex::new(ray.origin.re as f64, ray.origin.im as f64);
+
+        let direction = Complex::from_polar(1.0, ray.theta as f64);
+
+        let inside = |r: f64| self.contains((origin + direction * r - p) / q);
+
+        if !inside(0.0) {
+            return 0.0;
+        }
+
+        // The cardioid reaches out to |c| = 3/4 and the bulb to 5/4.
+        let extent = ((origin - p).norm() + 1.25 * q.norm()).min(MAX_RADIUS as f64);
+
+        let step = extent / MARCH_STEPS as f64;
+
+        let Some(exit) = (1..=MARCH_STEPS).find(|&i| !inside(i as f64 * step)) else {
+            return extent;
+        };
+
+        let (mut low, mut high) = ((exit - 1) as f64 * step, exit as f64 * step);
+
+        for _ in 0..BISECTIONS {
+            let middle = (low + high) / 2.0;
+
+            if inside(middle) {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+
+        low
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Fit {
+    pub model: Model,
+    // The image of 0, the nucleus of the fitted copy.
+    pub nucleus: Complex<f32>,
+    // |q| is the copy's size relative to the main cardioid and arg q its rotation.
+    pub scale: Complex<f32>,
+    pub rms: f32,
+    pub residuals: Vec<(f32, f32)>,
+    pub iterations: u32
+}
+
+fn residuals(model: Model, profile: &[(f32, f32)], origin: Complex<f32>, parameters: [f64; 4]) -> Vec<f64> {
+    let transform = (Complex::new(parameters[0], parameters[1]), Complex::new(parameters[2], parameters[3]));
+
+    profile
+        .iter()
+        .map(|&(theta, r)| model.radius(Ray::new(origin, theta), transform) - r as f64)
+        .collect()
+}
+
+fn cost(residuals: &[f64]) -> f64 {
+    residuals.iter().map(|residual| residual * residual).sum()
+}
+
+// Gaussian elimination with partial pivoting; None if the system is singular.
+fn solve(mut matrix: [[f64; 4]; 4], mut rhs: [f64; 4]) -> Option<[f64; 4]> {
+    for column in 0..4 {
+        let pivot = (column..4).max_by(|&a, &b| matrix[a][column].abs().total_cmp(&matrix[b][column].abs()))?;
+
+        if matrix[pivot][column].abs() < f64::EPSILON {
+            return None;
+        }
+
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+
+        for row in column + 1..4 {
+            let factor = matrix[row][column] / matrix[column][column];
+
+            let pivot_row = matrix[column];
+
+            for (entry, pivot_entry) in matrix[row].iter_mut().zip(pivot_row).skip(column) {
+                *entry -= factor * pivot_entry;
+            }
+
+            rhs[row] -= factor * rhs[column];
+        }
+    }
+
+    let mut solution = [0.0; 4];
+
+    for row in (0..4).rev() {
+        let sum = (row + 1..4).map(|k| matrix[row][k] * solution[k]).sum::<f64>();
+
+        solution[row] = (rhs[row] - sum) / matrix[row][row];
+    }
+
+    Some(solution)
+}
+
+// Levenberg-Marquardt on the radial residuals, with a forward-difference Jacobian. It starts from a
+// copy centered on the polar origin whose mean radius matches the profile's.
+pub fn fit(model: Model, profile: &[(f32, f32)], origin: Complex<f32>) -> Fit {
+    let mean = profile.iter().map(|&(_, r)| r as f64).sum::<f64>() / profile.len().max(1) as f64;
+
+    let unit = profile
+        .iter()
+        .map(|&(theta, _)| model.radius(Ray::new(Complex::new(-0.25, 0.0), theta), (0.0.into(), 1.0.into())))
+        .sum::<f64>()
+        / profile.len().max(1) as f64;
+
+    let q = mean / unit;
+
+    let mut parameters = [origin.re as f64 + 0.25 * q, origin.im as f64, q, 0.0];
+
+    let mut current = residuals(model, profile, origin, parameters);
+
+    let mut damping = 1e-3;
+
+    let mut iterations = 0;
+
+    while iterations < MAX_ITERATIONS {
+        iterations += 1;
+
+        let jacobian = (0..4)
+            .map(|j| {
+                let mut shifted = parameters;
+
+                let h = DIFFERENCE_STEP * parameters[j].abs().max(1e-3);
+
+                shifted[j] += h;
+
+                residuals(model, profile, origin, shifted)
+                    .iter()
+                    .zip(&current)
+                    .map(|(shifted, current)| (shifted - current) / h)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut normal = [[0.0; 4]; 4];
+        let mut gradient = [0.0; 4];
+
+        for a in 0..4 {
+            for b in 0..4 {
+                normal[a][b] = jacobian[a].iter().zip(&jacobian[b]).map(|(x, y)| x * y).sum();
+            }
+
+            gradient[a] = -jacobian[a].iter().zip(&current).map(|(x, residual)| x * residual).sum::<f64>();
+        }
+
+        let mut improved = false;
+
+        while damping < 1e12 {
+            let mut damped = normal;
+
+            for (i, row) in damped.iter_mut().enumerate() {
+                row[i] += damping * normal[i][i].max(f64::EPSILON);
+            }
+
+            let Some(step) = solve(damped, gradient) else {
+                damping *= 10.0;
+
+                continue;
+            };
+
+            let candidate = std::array::from_fn(|i| parameters[i] + step[i]);
+
+            let trial = residuals(model, profile, origin, candidate);
+
+            if cost(&trial) < cost(&current) {
+                let relative = (cost(&current) - cost(&trial)) / cost(&current).max(f64::MIN_POSITIVE);
+
+                parameters = candidate;
+                current = trial;
+                damping = (damping / 10.0).max(1e-12);
+                improved = relative > 1e-9;
+
+                break;
+            }
+
+            damping *= 10.0;
+        }
+
+        if !improved {
+            break;
+        }
+    }
+
+    Fit {
+        model,
+        nucleus: Complex::new(parameters[0] as f32, parameters[1] as f32),
+        scale: Complex::new(parameters[2] as f32, parameters[3] as f32),
+        rms: (cost(&current) / current.len().max(1) as f64).sqrt() as f32,
+        residuals: profile.iter().zip(&current).map(|(&(theta, _), &residual)| (theta, -residual as f32)).collect(),
+        iterations
+    }
+}
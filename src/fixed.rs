@@ -0,0 +1,131 @@
+// An iteration kernel in Q4.60 fixed point: 4 integer bits and 60 fractional ones in an i64, with
+// products taken in i128. Integer arithmetic rounds the same way on every target, so the kernel is
+// a reference the floating-point one can be checked against, and it needs no FPU in the loop. It
+// iterates z² + c, for the Mandelbrot set or a Julia set, under escape conditions that bound both
+// parts of z by at most 2, which keeps every z that hasn't escaped well inside the range.
+
+use crate::fractal::{Escape, Fractal, Sample, Termination};
+use num::complex::Complex;
+
+pub const FRACTION: u32 = 60;
+
+// The largest escape threshold the kernel takes.
+pub const MAX_THRESHOLD: f32 = 2.0;
+
+// Points further out than this along either axis escape at once under any threshold the kernel
+// takes, so they're clamped to it, which leaves room to square what hasn't escaped.
+const CLAMP: f64 = 4.0;
+
+const ONE: f64 = (1u64 << FRACTION) as f64;
+
+fn to_fixed(value: f32) -> i64 {
+    ((value as f64).clamp(-CLAMP, CLAMP) * ONE) as i64
+}
+
+fn to_float(value: i128) -> f32 {
+    (value as f64 / ONE) as f32
+}
+
+fn mul(a: i128, b: i128) -> i128 {
+    (a * b) >> FRACTION
+}
+
+// Escaped parts may lie outside the range, so the norm is only squared once both are known to be
+// below the radius.
+fn escaped(escape: Escape, (re, im): (i128, i128)) -> bool {
+    let threshold = |threshold: f32| to_fixed(threshold) as i128;
+
+    match escape {
+        Escape::Norm(radius) => {
+            let radius = threshold(radius);
+
+            re.abs() >= radius || im.abs() >= radius || mul(re, re) + mul(im, im) >= mul(radius, radius)
+        }
+
+        Escape::Real(t) => re.abs() >= threshold(t),
+        Escape::Imaginary(t) => im.abs() >= threshold(t),
+        Escape::Either(t) => re.abs() >= threshold(t) || im.abs() >= threshold(t),
+        Escape::Sum(t) => re.abs() + im.abs() >= threshold(t)
+    }
+}
+
+pub struct Fixed {
+    k: Option<Complex<f32>>,
+    escape: Escape
+}
+
+impl Fixed {
+    // The Mandelbrot set, or the Julia set of k, under the escape condition. Conditions on one part
+    // alone would let the other grow out of range, so only norm, either, and sum are taken.
+    pub fn new(k: Option<Complex<f32>>, escape: Escape) -> Result<Self, String> {
+        let threshold = match escape {
+            Escape::Norm(threshold) | Escape::Either(threshold) | Escape::Sum(threshold) => threshold,
+            Escape::Real(_) | Escape::Imaginary(_) => {
+                return Err("fixed64 needs an escape condition bounding both parts: norm, either, or sum".to_string())
+            }
+        };
+
+        if threshold > MAX_THRESHOLD {
+            return Err(format!("fixed64 takes escape thresholds of at most {}", MAX_THRESHOLD));
+        }
+
+        if k.is_some_and(|k| k.re.abs() as f64 >= CLAMP || k.im.abs() as f64 >= CLAMP) {
+            return Err(format!("fixed64 takes Julia parameters of less than {} in each part", CLAMP));
+        }
+
+        Ok(Self { k, escape })
+    }
+}
+
+impl Fractal for Fixed {
+    fn start(&self, c: Complex<f32>) -> Complex<f32> {
+        match self.k {
+            Some(_) => c,
+            None => Complex::new(0.0, 0.0)
+        }
+    }
+
+    // For orbits and the like, which step one at a time in floating point.
+    fn step(&self, z: Complex<f32>, c: Complex<f32>) -> Complex<f32> {
+        z * z + self.k.unwrap_or(c)
+    }
+
+    fn escape(&self) -> Escape {
+        self.escape
+    }
+
+    fn terminated(&self, z: Complex<f32>, _c: Complex<f32>) -> Option<Termination> {
+        escaped(self.escape, (to_fixed(z.re) as i128, to_fixed(z.im) as i128)).then_some(Termination::Escaped)
+    }
+
+    fn resume(&self, sample: Sample, limit: u32) -> Sample {
+        let Sample { c, z, iterations: mut i, mut termination } = sample;
+
+        let add = self.k.unwrap_or(c);
+        let add = (to_fixed(add.re) as i128, to_fixed(add.im) as i128);
+
+        let mut z = (to_fixed(z.re), to_fixed(z.im));
+
+        // Where z escaped to, which may be out of an i64's range.
+        let mut escaped_to = None;
+
+        while termination.is_none() && i < limit {
+            let (re, im) = (z.0 as i128, z.1 as i128);
+
+            let next = (mul(re, re) - mul(im, im) + add.0, mul(2 * re, im) + add.1);
+
+            i += 1;
+
+            if escaped(self.escape, next) {
+                (termination, escaped_to) = (Some(Termination::Escaped), Some(next));
+            } else {
+                // Both parts are below the threshold, so they fit.
+                z = (next.0 as i64, next.1 as i64);
+            }
+        }
+
+        let z = escaped_to.unwrap_or((z.0 as i128, z.1 as i128));
+
+        Sample { c, z: Complex::new(to_float(z.0), to_float(z.1)), iterations: i, termination }
+    }
+}
@@ -0,0 +1,338 @@
+use num::complex::Complex;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+pub const BAILOUT_RADIUS: f32 = 2.0;
+pub const BAILOUT_ITERATIONS: u32 = 1000;
+
+const MAGNET_BAILOUT_RADIUS: f32 = 100.0;
+const MAGNET_CONVERGENCE_RADIUS: f32 = 1e-3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Termination {
+    Escaped,
+    Converged
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Sample {
+    pub c: Complex<f32>,
+    pub z: Complex<f32>,
+    pub iterations: u32,
+    pub termination: Option<Termination>
+}
+
+impl Sample {
+    pub fn is_member(&self) -> bool {
+        self.termination != Some(Termination::Escaped)
+    }
+
+    // The iteration count made continuous for an escaped point, from n − 1 when z only just reached
+    // the escape threshold to n when it overshot it by as much as squaring can, so that it varies
+    // smoothly across the bands of equal n. Other points keep their count.
+    pub fn smooth_iterations(&self, escape: Escape) -> f32 {
+        if self.is_member() {
+            return self.iterations as f32;
+        }
+
+        let threshold = match escape {
+            Escape::Norm(threshold)
+            | Escape::Real(threshold)
+            | Escape::Imaginary(threshold)
+            | Escape::Either(threshold)
+            | Escape::Sum(threshold) => threshold
+        };
+
+        // Squaring doesn't grow z past a threshold of 1 or less, so there's nothing to interpolate.
+        if threshold <= 1.0 {
+            return self.iterations as f32;
+        }
+
+        // ln |z| / ln threshold runs from 1 to 2 for a quadratic step from just below the threshold.
+        let overshoot = (self.z.norm().ln() / threshold.ln()).log2();
+
+        self.iterations as f32 - overshoot.clamp(0.0, 1.0)
+    }
+}
+
+// When an orbit counts as escaped: once |z| reaches the radius, or once the real part, the imaginary
+// part, either of them, or the sum of their magnitudes reaches the threshold. Some variants, like
+// those bounded in a strip, escape along one axis rather than in every direction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Escape {
+    Norm(f32),
+    Real(f32),
+    Imaginary(f32),
+    Either(f32),
+    Sum(f32)
+}
+
+impl Escape {
+    pub fn escaped(self, z: Complex<f32>) -> bool {
+        match self {
+            Self::Norm(radius) => z.norm() >= radius,
+            Self::Real(threshold) => z.re.abs() >= threshold,
+            Self::Imaginary(threshold) => z.im.abs() >= threshold,
+            Self::Either(threshold) => z.re.abs() >= threshold || z.im.abs() >= threshold,
+            Self::Sum(threshold) => z.re.abs() + z.im.abs() >= threshold
+        }
+    }
+}
+
+// In the form FromStr reads.
+impl fmt::Display for Escape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Norm(radius) => write!(f, "norm:{}", radius),
+            Self::Real(threshold) => write!(f, "real:{}", threshold),
+            Self::Imaginary(threshold) => write!(f, "imaginary:{}", threshold),
+            Self::Either(threshold) => write!(f, "either:{}", threshold),
+            Self::Sum(threshold) => write!(f, "sum:{}", threshold)
+        }
+    }
+}
+
+impl FromStr for Escape {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, threshold) = s.split_once(':').ok_or_else(|| format!("`{}` needs a threshold, e.g. norm:2", s))?;
+
+        let threshold = threshold
+            .parse::<f32>()
+            .ok()
+            .filter(|threshold| threshold.is_finite() && *threshold > 0.0)
+            .ok_or_else(|| format!("escape threshold `{}` must be a positive number", threshold))?;
+
+        match name {
+            "norm" => Ok(Self::Norm(threshold)),
+            "real" => Ok(Self::Real(threshold)),
+            "imaginary" => Ok(Self::Imaginary(threshold)),
+            "either" => Ok(Self::Either(threshold)),
+            "sum" => Ok(Self::Sum(threshold)),
+            _ => Err(format!("unknown escape condition `{}`", name))
+        }
+    }
+}
+
+pub trait Fractal: Send + Sync {
+    fn start(&self, _c: Complex<f32>) -> Complex<f32> {
+        Complex::new(0.0, 0.0)
+    }
+
+    fn step(&self, z: Complex<f32>, c: Complex<f32>) -> Complex<f32>;
+
+    fn escape(&self) -> Escape {
+        Escape::Norm(BAILOUT_RADIUS)
+    }
+
+    fn terminated(&self, z: Complex<f32>, _c: Complex<f32>) -> Option<Termination> {
+        self.escape().escaped(z).then_some(Termination::Escaped)
+    }
+
+    fn sample(&self, c: Complex<f32>) -> Sample {
+        self.sample_with_limit(c, BAILOUT_ITERATIONS)
+    }
+
+    fn sample_with_limit(&self, c: Complex<f32>, limit: u32) -> Sample {
+        let z = self.start(c);
+
+        self.resume(Sample { c, z, iterations: 0, termination: self.terminated(z, c) }, limit)
+    }
+
+    // Carries on iterating a sample that stopped at a lower limit from where it left off.
+    fn resume(&self, sample: Sample, limit: u32) -> Sample {
+        let Sample { c, mut z, iterations: mut i, mut termination } = sample;
+
+        while termination.is_none() && i < limit {
+            z = self.step(z, c);
+
+            i += 1;
+
+            termination = self.terminated(z, c);
+        }
+
+        Sample { c, z, iterations: i, termination }
+    }
+}
+
+pub struct Mandelbrot;
+
+impl Fractal for Mandelbrot {
+    fn step(&self, z: Complex<f32>, c: Complex<f32>) -> Complex<f32> {
+        z * z + c
+    }
+}
+
+fn magnet_terminated(escape: Escape, z: Complex<f32>) -> Option<Termination> {
+    if escape.escaped(z) {
+        Some(Termination::Escaped)
+    } else if (z - 1.0).norm() < MAGNET_CONVERGENCE_RADIUS {
+        Some(Termination::Converged)
+    } else {
+        None
+    }
+}
+
+pub struct MagnetI;
+
+impl Fractal for MagnetI {
+    fn step(&self, z: Complex<f32>, c: Complex<f32>) -> Complex<f32> {
+        ((z * z + c - 1.0) / (2.0 * z + c - 2.0)).powi(2)
+    }
+
+    fn escape(&self) -> Escape {
+        Escape::Norm(MAGNET_BAILOUT_RADIUS)
+    }
+
+    fn terminated(&self, z: Complex<f32>, _c: Complex<f32>) -> Option<Termination> {
+        magnet_terminated(self.escape(), z)
+    }
+}
+
+pub struct MagnetII;
+
+impl Fractal for MagnetII {
+    fn step(&self, z: Complex<f32>, c: Complex<f32>) -> Complex<f32> {
+        let numerator = z * z * z + 3.0 * (c - 1.0) * z + (c - 1.0) * (c - 2.0);
+
+        let denominator = 3.0 * z * z + 3.0 * (c - 2.0) * z + (c - 1.0) * (c - 2.0) + 1.0;
+
+        (numerator / denominator).powi(2)
+    }
+
+    fn escape(&self) -> Escape {
+        Escape::Norm(MAGNET_BAILOUT_RADIUS)
+    }
+
+    fn terminated(&self, z: Complex<f32>, _c: Complex<f32>) -> Option<Termination> {
+        magnet_terminated(self.escape(), z)
+    }
+}
+
+// The filled Julia set of z² + k, which is connected exactly when k is in the Mandelbrot set.
+pub struct Julia {
+    pub k: Complex<f32>
+}
+
+impl Fractal for Julia {
+    fn start(&self, c: Complex<f32>) -> Complex<f32> {
+        c
+    }
+
+    fn step(&self, z: Complex<f32>, _c: Complex<f32>) -> Complex<f32> {
+        z * z + self.k
+    }
+}
+
+impl Julia {
+    pub fn is_connected(&self) -> bool {
+        Mandelbrot.sample(self.k).is_member()
+    }
+}
+
+// Samples another fractal with a different iteration limit, e.g. one that grows as an animation
+// zooms in.
+pub struct Limited {
+    pub fractal: Arc<dyn Fractal>,
+    pub iterations: u32
+}
+
+impl Fractal for Limited {
+    fn start(&self, c: Complex<f32>) -> Complex<f32> {
+        self.fractal.start(c)
+    }
+
+    fn step(&self, z: Complex<f32>, c: Complex<f32>) -> Complex<f32> {
+        self.fractal.step(z, c)
+    }
+
+    fn escape(&self) -> Escape {
+        self.fractal.escape()
+    }
+
+    fn terminated(&self, z: Complex<f32>, c: Complex<f32>) -> Option<Termination> {
+        self.fractal.terminated(z, c)
+    }
+
+    fn sample(&self, c: Complex<f32>) -> Sample {
+        self.fractal.sample_with_limit(c, self.iterations)
+    }
+
+    fn resume(&self, sample: Sample, limit: u32) -> Sample {
+        self.fractal.resume(sample, limit)
+    }
+}
+
+// Samples another fractal with a different escape condition in place of its own, keeping any test
+// for convergence it has.
+pub struct Escaping {
+    pub fractal: Arc<dyn Fractal>,
+    pub escape: Escape
+}
+
+impl Fractal for Escaping {
+    fn start(&self, c: Complex<f32>) -> Complex<f32> {
+        self.fractal.start(c)
+    }
+
+    fn step(&self, z: Complex<f32>, c: Complex<f32>) -> Complex<f32> {
+        self.fractal.step(z, c)
+    }
+
+    fn escape(&self) -> Escape {
+        self.escape
+    }
+
+    fn terminated(&self, z: Complex<f32>, c: Complex<f32>) -> Option<Termination> {
+        if self.escape.escaped(z) {
+            Some(Termination::Escaped)
+        } else {
+            self.fractal.terminated(z, c).filter(|&termination| termination == Termination::Converged)
+        }
+    }
+
+    // A step at a time through the fractal's own resume, so it counts the iterations as it would alone.
+    fn resume(&self, mut sample: Sample, limit: u32) -> Sample {
+        while sample.termination.is_none() && sample.iterations < limit {
+            sample = self.fractal.resume(sample, sample.iterations + 1);
+
+            if self.escape.escaped(sample.z) {
+                sample.termination = Some(Termination::Escaped);
+            } else {
+                sample.termination = sample.termination.filter(|&termination| termination == Termination::Converged);
+            }
+        }
+
+        sample
+    }
+}
+
+// Every z the iteration visits from the start until it terminates or reaches the limit, the start
+// included, along with the sample it results in.
+pub fn orbit(fractal: &dyn Fractal, c: Complex<f32>, limit: u32) -> (Vec<Complex<f32>>, Sample) {
+    let z = fractal.start(c);
+
+    let mut orbit = vec![z];
+
+    let mut sample = Sample { c, z, iterations: 0, termination: fractal.terminated(z, c) };
+
+    // Resumed a step at a time, so fractals that count their iterations see the count.
+    while sample.termination.is_none() && sample.iterations < limit {
+        sample = fractal.resume(sample, sample.iterations + 1);
+
+        orbit.push(sample.z);
+    }
+
+    (orbit, sample)
+}
+
+pub fn from_name(name: &str) -> Option<Arc<dyn Fractal>> {
+    Some(match name {
+        "mandelbrot" => Arc::new(Mandelbrot),
+        "magnet1" => Arc::new(MagnetI),
+        "magnet2" => Arc::new(MagnetII),
+        _ => return None
+    })
+}
@@ -0,0 +1,469 @@
+// Render and analysis jobs submitted to the server as JSON, run in the background, and polled for
+// progress. Renders work in bands of rows and analyses ray by ray, checking between them whether
+// they've been cancelled, so cancelling one stops it within a band or a ray.
+
+use crate::analysis;
+use crate::export::{self, PolarReport};
+use crate::fractal::{self, Escape, Fractal, Julia, Limited, Sample, Termination, BAILOUT_ITERATIONS};
+use crate::json::Value;
+use crate::polar::{self, Domain, Ray};
+use crate::render::{self, Colorer, MembershipColorer};
+use crate::viewport::View;
+use image::{DynamicImage, ImageOutputFormat, RgbImage};
+use num::complex::Complex;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+const BAND_ROWS: u32 = 64;
+
+// Renders go through every 16th row first, then the rows halfway between those, and so on, so a
+// client watching one sees the whole image early, if coarsely, rather than the top of it in full.
+const INTERLACE: u32 = 16;
+const RAY_BATCH: usize = 64;
+
+// Larger jobs would take too long to be worth waiting on over HTTP.
+const MAX_PIXELS: u64 = 64 << 20;
+const MAX_RAYS: usize = 1 << 20;
+const MAX_ITERATIONS: u64 = 1 << 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Render,
+    Analyze
+}
+
+// Every field but kind is optional in the submitted JSON, and defaults to the same value as the
+// command line's.
+#[derive(Clone, Debug)]
+pub struct Parameters {
+    pub kind: Kind,
+    pub fractal: Option<String>,
+    pub julia: Option<Complex<f32>>,
+    pub view: View,
+    pub size: (u32, u32),
+    pub iterations: u32,
+    pub samples: u32,
+    pub polar_origin: Complex<f32>,
+    pub angles: usize,
+    pub ray_step: f32
+}
+
+fn complex(value: &Value, key: &str) -> Result<Complex<f32>, String> {
+    match (value.get("re").and_then(Value::as_f64), value.get("im").and_then(Value::as_f64)) {
+        (Some(re), Some(im)) => Ok(Complex::new(re as f32, im as f32)),
+        _ => Err(format!("`{}` must be an object with numbers `re` and `im`", key))
+    }
+}
+
+fn number(value: &Value, key: &str) -> Result<Option<f64>, String> {
+    match value.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => {
+            let number = value.as_f64().filter(|number| number.is_finite());
+
+            number.map(Some).ok_or_else(|| format!("`{}` must be a number", key))
+        }
+    }
+}
+
+// A positive whole number no larger than the limit.
+fn count(value: &Value, key: &str, limit: u64) -> Result<Option<u64>, String> {
+    match number(value, key)? {
+        Some(number) if number >= 1.0 && number.fract() == 0.0 && number <= limit as f64 => Ok(Some(number as u64)),
+        Some(_) => Err(format!("`{}` must be a whole number from 1 to {}", key, limit)),
+        None => Ok(None)
+    }
+}
+
+impl Parameters {
+    pub fn from_json(value: &Value) -> Result<Self, String> {
+        let kind = match value.get("kind").and_then(Value::as_str) {
+            Some("render") => Kind::Render,
+            Some("analyze") => Kind::Analyze,
+            _ => return Err("`kind` must be \"render\" or \"analyze\"".to_string())
+        };
+
+        let fractal = match value.get("fractal") {
+            None | Some(Value::Null) => None,
+            Some(Value::String(name)) if fractal::from_name(name).is_some() => Some(name.clone()),
+            Some(_) => return Err("`fractal` must be mandelbrot, magnet1, or magnet2".to_string())
+        };
+
+        let julia = value
+            .get("julia")
+            .filter(|julia| **julia != Value::Null)
+            .map(|julia| complex(julia, "julia"))
+            .transpose()?;
+
+        if fractal.is_some() && julia.is_some() {
+            return Err("`fractal` and `julia` cannot be combined".to_string());
+        }
+
+        let size = match value.get("size").and_then(Value::as_array) {
+            Some([width, height]) => {
+                let dimension = |value: &Value| {
+                    value.as_f64().filter(|&n| n >= 1.0 && n.fract() == 0.0 && n <= u32::MAX as f64)
+                };
+
+                match (dimension(width), dimension(height)) {
+                    (Some(width), Some(height)) if width * height <= MAX_PIXELS as f64 => {
+                        (width as u32, height as u32)
+                    }
+
+                    _ => return Err(format!("`size` must be two whole numbers with at most {} pixels", MAX_PIXELS))
+                }
+            }
+
+            Some(_) => return Err("`size` must be [width, height]".to_string()),
+            None => (1280, 720)
+        };
+
+        let center = value.get("center").map(|center| complex(center, "center")).transpose()?;
+
+        let bounds = match value.get("bounds").and_then(Value::as_array) {
+            Some([a, b]) => Some((complex(a, "bounds")?, complex(b, "bounds")?)),
+            Some(_) => return Err("`bounds` must be two corners".to_string()),
+            None => None
+        };
+
+        let view = match (number(value, "width")?, number(value, "height")?, bounds) {
+            (Some(span), None, None) | (None, Some(span), None) if span <= 0.0 => {
+                return Err("`width` and `height` must be positive".to_string());
+            }
+
+            (Some(width), None, None) => View::fit_width(center.unwrap_or_default(), width as f32),
+            (None, Some(height), None) => View::fit_height(center.unwrap_or_default(), height as f32, size),
+            (None, None, Some((a, b))) if center.is_none() && a != b => View::fit_bounds(a, b, size),
+            (None, None, Some(_)) => return Err("`bounds` must be distinct corners, without `center`".to_string()),
+            (None, None, None) => View { center: center.unwrap_or_default(), ..View::default() },
+            _ => return Err("`width`, `height`, and `bounds` cannot be combined".to_string())
+        };
+
+        let ray_step = number(value, "ray_step")?.unwrap_or(0.001) as f32;
+
+        if ray_step < polar::MIN_RAY_STEP {
+            return Err(format!("`ray_step` must be at least {}", polar::MIN_RAY_STEP));
+        }
+
+        let polar_origin = value.get("polar_origin").map(|origin| complex(origin, "polar_origin")).transpose()?;
+
+        Ok(Self {
+            kind,
+            fractal,
+            julia,
+            view,
+            size,
+            iterations: count(value, "iterations", MAX_ITERATIONS)?.map_or(BAILOUT_ITERATIONS, |n| n as u32),
+            samples: count(value, "samples", 64)?.map_or(1, |n| n as u32),
+            polar_origin: polar_origin.unwrap_or_default(),
+            angles: count(value, "angles", MAX_RAYS as u64)?.map_or(1000, |n| n as usize),
+            ray_step
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum State {
+    Running,
+    Done,
+    Cancelled
+}
+
+impl State {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Done => "done",
+            Self::Cancelled => "cancelled"
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Output {
+    Image(Arc<Vec<u8>>),
+    Polar(Arc<Value>)
+}
+
+// A finished band of a render, kept so it can be streamed to clients watching the job: the rows top,
+// top + stride, and so on, stacked into one image.
+#[derive(Clone, Debug)]
+pub struct Band {
+    pub top: u32,
+    pub stride: u32,
+    pub rows: u32,
+    pub png: Arc<Vec<u8>>
+}
+
+struct Status {
+    state: State,
+    // The fraction of the work done, from 0 to 1.
+    progress: f32,
+    bands: Vec<Band>,
+    output: Option<Output>
+}
+
+fn encode_png(image: RgbImage) -> Vec<u8> {
+    let mut png = Vec::new();
+
+    DynamicImage::ImageRgb8(image)
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .expect("encoding into memory can't fail");
+
+    png
+}
+
+// Samples another fractal until the job is cancelled, then stops every orbit where it starts, so the
+// rest of a band or ray, which is thrown away, takes no time.
+struct Cancellable {
+    fractal: Arc<dyn Fractal>,
+    cancelled: Arc<AtomicBool>
+}
+
+impl Fractal for Cancellable {
+    fn start(&self, c: Complex<f32>) -> Complex<f32> {
+        self.fractal.start(c)
+    }
+
+    fn step(&self, z: Complex<f32>, c: Complex<f32>) -> Complex<f32> {
+        self.fractal.step(z, c)
+    }
+
+    fn escape(&self) -> Escape {
+        self.fractal.escape()
+    }
+
+    fn terminated(&self, z: Complex<f32>, c: Complex<f32>) -> Option<Termination> {
+        self.fractal.terminated(z, c)
+    }
+
+    fn sample(&self, c: Complex<f32>) -> Sample {
+        if self.cancelled.load(Ordering::Relaxed) {
+            self.fractal.sample_with_limit(c, 0)
+        } else {
+            self.fractal.sample(c)
+        }
+    }
+}
+
+pub struct Job {
+    pub id: u64,
+    pub parameters: Parameters,
+    status: Mutex<Status>,
+    // Shared with the blocking tasks marching rays, which check it between them.
+    cancelled: Arc<AtomicBool>
+}
+
+impl Job {
+    pub fn new(id: u64, parameters: Parameters) -> Self {
+        Self {
+            id,
+            parameters,
+            status: Mutex::new(Status { state: State::Running, progress: 0.0, bands: Vec::new(), output: None }),
+            cancelled: Arc::new(AtomicBool::new(false))
+        }
+    }
+
+    pub fn state(&self) -> State {
+        self.status.lock().unwrap().state
+    }
+
+    pub fn progress(&self) -> f32 {
+        self.status.lock().unwrap().progress
+    }
+
+    pub fn output(&self) -> Option<Output> {
+        self.status.lock().unwrap().output.clone()
+    }
+
+    // The bands of a render finished so far, skipping the first `from`.
+    pub fn bands(&self, from: usize) -> Vec<Band> {
+        self.status.lock().unwrap().bands.iter().skip(from).cloned().collect()
+    }
+
+    // Marks a running job to stop at the next band or ray; finished jobs are left alone.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn document(&self) -> Value {
+        let status = self.status.lock().unwrap();
+
+        Value::object([
+            ("id", (self.id as f64).into()),
+            (
+                "kind",
+                match self.parameters.kind {
+                    Kind::Render => "render",
+                    Kind::Analyze => "analyze"
+                }
+                .into()
+            ),
+            ("state", status.state.name().into()),
+            ("progress", status.progress.into())
+        ])
+    }
+
+    fn set_progress(&self, progress: f32) -> bool {
+        self.status.lock().unwrap().progress = progress;
+
+        !self.cancelled.load(Ordering::Relaxed)
+    }
+
+    // Bands are only kept to stream while the job runs; a finished render's image holds them all.
+    fn finish(&self, output: Option<Output>) {
+        let mut status = self.status.lock().unwrap();
+
+        status.bands = Vec::new();
+
+        match output {
+            Some(output) => {
+                status.state = State::Done;
+                status.progress = 1.0;
+                status.output = Some(output);
+            }
+
+            None => status.state = State::Cancelled
+        }
+    }
+
+    // Uses the server's own fractal and colorer unless the parameters pick another fractal.
+    pub async fn run(self: Arc<Self>, fractal: Arc<dyn Fractal>, colorer: Arc<dyn Colorer>) {
+        let parameters = &self.parameters;
+
+        let (fractal, colorer): (Arc<dyn Fractal>, Arc<dyn Colorer>) = match (&parameters.fractal, parameters.julia) {
+            (Some(name), _) => (fractal::from_name(name).expect("the name was validated"), Arc::new(MembershipColorer)),
+            (None, Some(k)) => (Arc::new(Julia { k }), Arc::new(MembershipColorer)),
+            (None, None) => (fractal, colorer)
+        };
+
+        let fractal = Arc::new(Limited { fractal, iterations: parameters.iterations });
+
+        let fractal = Arc::new(Cancellable { fractal, cancelled: self.cancelled.clone() });
+
+        let output = match parameters.kind {
+            Kind::Render => self.render(fractal, colorer).await,
+            Kind::Analyze => self.analyze(fractal).await
+        };
+
+        self.finish(output);
+    }
+
+    async fn render(&self, fractal: Arc<dyn Fractal>, colorer: Arc<dyn Colorer>) -> Option<Output> {
+        let Parameters { view, size: (width, height), samples, .. } = self.parameters;
+
+        let mut image = RgbImage::new(width, height);
+
+        let mut done = 0;
+
+        for offset in render::interlace_offsets(INTERLACE) {
+            let rows = (offset..height).step_by(INTERLACE as usize).collect::<Vec<_>>();
+
+            for band_rows in rows.chunks(BAND_ROWS as usize) {
+                let band_rows = Arc::new(band_rows.to_vec());
+
+                let band = render::render_view_rows(
+                    fractal.clone(),
+                    colorer.clone(),
+                    (width, height),
+                    view,
+                    samples,
+                    band_rows.clone()
+                )
+                .await;
+
+                let row_bytes = width as usize * 3;
+
+                let pixels: &mut [u8] = &mut image;
+
+                for (row, &y) in band.chunks(row_bytes).zip(band_rows.iter()) {
+                    pixels[y as usize * row_bytes..][..row_bytes].copy_from_slice(row);
+                }
+
+                let rows = band_rows.len() as u32;
+
+                let band = Band { top: band_rows[0], stride: INTERLACE, rows, png: Arc::new(encode_png(band)) };
+
+                self.status.lock().unwrap().bands.push(band);
+
+                done += band_rows.len();
+
+                if !self.set_progress(done as f32 / height as f32) {
+                    return None;
+                }
+            }
+        }
+
+        Some(Output::Image(Arc::new(encode_png(image))))
+    }
+
+    // Marching is synchronous, so each batch of rays runs on the blocking pool to keep the server
+    // responsive meanwhile.
+    async fn analyze(&self, fractal: Arc<dyn Fractal>) -> Option<Output> {
+        let Parameters { polar_origin, angles, ray_step, .. } = self.parameters;
+
+        let thetas = polar::angles(Domain::Full, angles).collect::<Vec<_>>();
+
+        let mut profile = Vec::with_capacity(angles);
+        let mut crossings = Vec::with_capacity(angles);
+
+        for batch in thetas.chunks(RAY_BATCH) {
+            let (batch, fractal, cancelled) = (batch.to_vec(), fractal.clone(), self.cancelled.clone());
+
+            let results = tokio::task::spawn_blocking(move || {
+                batch
+                    .into_iter()
+                    .take_while(|_| !cancelled.load(Ordering::Relaxed))
+                    .map(|theta| {
+                        let ray = Ray::new(polar_origin, theta);
+
+                        let radius = polar::march_radius(fractal.as_ref(), ray, ray_step).inside;
+
+                        let intervals = polar::march_intervals(fractal.as_ref(), ray, ray_step)
+                            .into_iter()
+                            .map(|interval| polar::refine_interval(fractal.as_ref(), ray, interval, None))
+                            .collect();
+
+                        ((theta, radius), (theta, intervals))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await
+            .expect("marching rays doesn't panic");
+
+            for (sample, crossing) in results {
+                profile.push(sample);
+                crossings.push(crossing);
+            }
+
+            if !self.set_progress(profile.len() as f32 / angles as f32) {
+                return None;
+            }
+        }
+
+        let summary = analysis::summarize(&profile, Domain::Full);
+
+        let parameters = Value::object([
+            ("polar_origin", Value::object([("re", polar_origin.re.into()), ("im", polar_origin.im.into())])),
+            ("samples", angles.into()),
+            ("ray_step", ray_step.into()),
+            ("iterations", self.parameters.iterations.into())
+        ]);
+
+        let document = export::polar_document(PolarReport {
+            parameters,
+            profile: &profile,
+            summary: &summary,
+            crossings: &crossings,
+            harmonics: None,
+            area: None,
+            monte_carlo: None,
+            arc_length: None,
+            symmetry: None,
+            render: None
+        });
+
+        Some(Output::Polar(Arc::new(document)))
+    }
+}
@@ -0,0 +1,384 @@
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>)
+}
+
+impl Value {
+    pub fn object<K: Into<String>>(entries: impl IntoIterator<Item = (K, Value)>) -> Self {
+        Self::Object(entries.into_iter().map(|(key, value)| (key.into(), value)).collect())
+    }
+
+    pub fn array<T: Into<Value>>(values: impl IntoIterator<Item = T>) -> Self {
+        Self::Array(values.into_iter().map(Into::into).collect())
+    }
+
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut parser = Parser { source: source.as_bytes(), position: 0, depth: 0 };
+
+        let value = parser.value()?;
+
+        parser.whitespace();
+
+        if parser.position < source.len() {
+            return Err(parser.error("trailing characters"));
+        }
+
+        Ok(value)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Self::Object(entries) => entries.iter().find(|(name, _)| name == key).map(|(_, value)| value),
+            _ => None
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None
+        }
+    }
+}
+
+// Arrays and objects nested deeper than this are rejected rather than overflowing the stack, since
+// the parser recurses into them and request bodies come from anyone.
+const MAX_DEPTH: usize = 128;
+
+// A recursive descent parser over the bytes of the source, which only ever splits it at ASCII
+// characters, so every string it slices out is still valid UTF-8.
+struct Parser<'a> {
+    source: &'a [u8],
+    position: usize,
+    depth: usize
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> String {
+        format!("{} at byte {}", message, self.position)
+    }
+
+    fn whitespace(&mut self) {
+        while self.source.get(self.position).is_some_and(u8::is_ascii_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.whitespace();
+
+        self.source.get(self.position).copied()
+    }
+
+    fn expect(&mut self, literal: &str) -> Result<(), String> {
+        if self.source[self.position..].starts_with(literal.as_bytes()) {
+            self.position += literal.len();
+
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected `{}`", literal)))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some(b'n') => self.expect("null").map(|()| Value::Null),
+            Some(b't') => self.expect("true").map(|()| Value::Bool(true)),
+            Some(b'f') => self.expect("false").map(|()| Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'[') => self.nested(Self::array),
+            Some(b'{') => self.nested(Self::object),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("expected a value")),
+            None => Err(self.error("unexpected end of input"))
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Value, String>) -> Result<Value, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+
+        self.depth += 1;
+
+        let value = parse(self);
+
+        self.depth -= 1;
+
+        value
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let start = self.position;
+
+        let numeric = |byte: &u8| matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9');
+
+        while self.source.get(self.position).is_some_and(numeric) {
+            self.position += 1;
+        }
+
+        let text = std::str::from_utf8(&self.source[start..self.position]).expect("the number is ASCII");
+
+        // Numbers too large for an f64 would parse as infinities, which JSON can't hold.
+        match text.parse::<f64>() {
+            Ok(number) if number.is_finite() => Ok(Value::Number(number)),
+            Ok(_) => Err(format!("number `{}` out of range at byte {}", text, start)),
+            Err(_) => Err(format!("invalid number `{}` at byte {}", text, start))
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect("\"")?;
+
+        let mut string = String::new();
+
+        loop {
+            let start = self.position;
+
+            while self.source.get(self.position).is_some_and(|&byte| byte != b'"' && byte != b'\\') {
+                self.position += 1;
+            }
+
+            string.push_str(std::str::from_utf8(&self.source[start..self.position]).expect("the source is UTF-8"));
+
+            match self.source.get(self.position) {
+                Some(b'"') => {
+                    self.position += 1;
+
+                    return Ok(string);
+                }
+
+                Some(_) => {
+                    let escape = self.source.get(self.position + 1).copied();
+
+                    self.position += 2;
+
+                    match escape {
+                        Some(b'"') => string.push('"'),
+                        Some(b'\\') => string.push('\\'),
+                        Some(b'/') => string.push('/'),
+                        Some(b'b') => string.push('\u{8}'),
+                        Some(b'f') => string.push('\u{c}'),
+                        Some(b'n') => string.push('\n'),
+                        Some(b'r') => string.push('\r'),
+                        Some(b't') => string.push('\t'),
+
+                        // Surrogate pairs aren't combined, so characters outside the basic
+                        // multilingual plane become replacement characters.
+                        Some(b'u') => {
+                            let code = self
+                                .source
+                                .get(self.position..self.position + 4)
+                                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                                .and_then(|hex| u32::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok())
+                                .ok_or_else(|| self.error("invalid unicode escape"))?;
+
+                            self.position += 4;
+
+                            string.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                        }
+
+                        _ => return Err(self.error("invalid escape"))
+                    }
+                }
+
+                None => return Err(self.error("unterminated string"))
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect("[")?;
+
+        let mut values = Vec::new();
+
+        if self.peek() == Some(b']') {
+            self.position += 1;
+
+            return Ok(Value::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+
+                    return Ok(Value::Array(values));
+                }
+
+                _ => return Err(self.error("expected `,` or `]`"))
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.expect("{")?;
+
+        let mut entries = Vec::new();
+
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+
+            return Ok(Value::Object(entries));
+        }
+
+        loop {
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected a key"));
+            }
+
+            let key = self.string()?;
+
+            if self.peek() != Some(b':') {
+                return Err(self.error("expected `:`"));
+            }
+
+            self.position += 1;
+
+            entries.push((key, self.value()?));
+
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+
+                    return Ok(Value::Object(entries));
+                }
+
+                _ => return Err(self.error("expected `,` or `}`"))
+            }
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        // Going through the shortest decimal representation keeps 0.1f32 from becoming
+        // 0.10000000149011612.
+        value.to_string().parse().map_or(Self::Null, Self::Number)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::Null, Into::into)
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, string: &str) -> fmt::Result {
+    write!(f, "\"")?;
+
+    for ch in string.chars() {
+        match ch {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            ch if (ch as u32) < 0x20 => write!(f, "\\u{:04x}", ch as u32)?,
+            ch => write!(f, "{}", ch)?
+        }
+    }
+
+    write!(f, "\"")
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Null => write!(f, "null"),
+            Self::Bool(value) => write!(f, "{}", value),
+            Self::Number(value) if value.is_finite() => write!(f, "{}", value),
+            Self::Number(_) => write!(f, "null"),
+            Self::String(value) => write_string(f, value),
+            Self::Array(values) => {
+                write!(f, "[")?;
+
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write!(f, "{}", value)?;
+                }
+
+                write!(f, "]")
+            }
+
+            Self::Object(entries) => {
+                write!(f, "{{")?;
+
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+
+                write!(f, "}}")
+            }
+        }
+    }
+}
@@ -0,0 +1,308 @@
+// Keyframed camera moves for animate. A keyframe file consists of `[frame]` headers, each followed by
+// `key = value` lines for the center, zoom, iteration limit, palette phase, and Julia parameter at
+// that frame; every value not given carries over from the previous keyframe. Frames in between are
+// interpolated, with the easing given at each keyframe shaping the approach to it:
+//
+//     [0]
+//     center = -0.75+0i
+//
+//     [150]
+//     center = -0.7436+0.1318i
+//     zoom = 1000
+//     iterations = 3000
+//     easing = ease-in-out
+
+use crate::fractal::BAILOUT_ITERATIONS;
+use crate::viewport::View;
+use num::complex::Complex;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Clone, Debug)]
+pub struct KeyframeError {
+    line: usize,
+    message: String
+}
+
+impl KeyframeError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self { line, message: message.into() }
+    }
+}
+
+impl fmt::Display for KeyframeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for KeyframeError {}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut
+}
+
+impl FromStr for Easing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "linear" => Ok(Self::Linear),
+            "ease-in" => Ok(Self::EaseIn),
+            "ease-out" => Ok(Self::EaseOut),
+            "ease-in-out" => Ok(Self::EaseInOut),
+            _ => Err(format!("unknown easing `{}`; expected linear, ease-in, ease-out, or ease-in-out", s))
+        }
+    }
+}
+
+impl fmt::Display for Easing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Linear => "linear",
+            Self::EaseIn => "ease-in",
+            Self::EaseOut => "ease-out",
+            Self::EaseInOut => "ease-in-out"
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+impl Easing {
+    // Cubic easing, which starts or ends (or both) with zero velocity.
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t * t,
+            Self::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Self::EaseInOut => t * t * (3.0 - 2.0 * t)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe {
+    pub frame: usize,
+    pub center: Complex<f32>,
+    // Magnification relative to the default view.
+    pub zoom: f32,
+    pub iterations: u32,
+    // Offset of the escape-time palette in turns.
+    pub phase: f32,
+    pub k: Option<Complex<f32>>,
+    pub easing: Easing
+}
+
+impl Default for Keyframe {
+    fn default() -> Self {
+        Self {
+            frame: 0,
+            center: Complex::new(0.0, 0.0),
+            zoom: 1.0,
+            iterations: BAILOUT_ITERATIONS,
+            phase: 0.0,
+            k: None,
+            easing: Easing::Linear
+        }
+    }
+}
+
+// What a single frame shows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct State {
+    pub view: View,
+    pub iterations: u32,
+    pub phase: f32,
+    pub k: Option<Complex<f32>>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyframes {
+    keys: Vec<Keyframe>,
+    // Whether any keyframe sets the phase, in which case frames are colored with the palette.
+    pub palette: bool
+}
+
+fn value<T: FromStr>(value: &str, key: &str, line: usize) -> Result<T, KeyframeError> {
+    value.parse().map_err(|_| KeyframeError::new(line, format!("invalid value `{}` for `{}`", value, key)))
+}
+
+impl Keyframes {
+    // Keyframes made some other way than parsing, which have to start at frame 0 and increase.
+    pub fn new(keys: Vec<Keyframe>) -> Self {
+        assert!(keys.first().is_some_and(|key| key.frame == 0), "the first keyframe must be at frame 0");
+        assert!(keys.windows(2).all(|pair| pair[0].frame < pair[1].frame), "keyframes must be in increasing order");
+
+        Self { keys, palette: false }
+    }
+
+    pub fn parse(source: &str) -> Result<Self, KeyframeError> {
+        let mut keys = Vec::<Keyframe>::new();
+
+        let mut palette = false;
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+
+            let line = line.split('#').next().unwrap().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                let frame: usize = value(header.trim(), "frame", line_number)?;
+
+                // The frame count is one past the last frame, so that has to fit too.
+                if frame == usize::MAX {
+                    return Err(KeyframeError::new(line_number, "the frame number is too large"));
+                }
+
+                let key = match keys.last() {
+                    None if frame != 0 => return Err(KeyframeError::new(line_number, "the first keyframe must be [0]")),
+                    None => Keyframe::default(),
+
+                    Some(previous) if frame <= previous.frame => {
+                        return Err(KeyframeError::new(line_number, "keyframes must be in increasing order"));
+                    }
+
+                    Some(previous) => Keyframe { frame, easing: Easing::Linear, ..*previous }
+                };
+
+                keys.push(key);
+
+                continue;
+            }
+
+            let (key, value_text) =
+                line.split_once('=').ok_or_else(|| KeyframeError::new(line_number, "expected `key = value`"))?;
+
+            let (key, value_text) = (key.trim(), value_text.trim());
+
+            let first = keys.len() == 1;
+
+            let keyframe = keys
+                .last_mut()
+                .ok_or_else(|| KeyframeError::new(line_number, "expected a `[frame]` header first"))?;
+
+            match key {
+                "center" => keyframe.center = value(value_text, key, line_number)?,
+
+                "zoom" => {
+                    keyframe.zoom = value(value_text, key, line_number)?;
+
+                    if !keyframe.zoom.is_finite() || keyframe.zoom <= 0.0 {
+                        return Err(KeyframeError::new(line_number, "`zoom` must be positive"));
+                    }
+                }
+
+                "iterations" => keyframe.iterations = value(value_text, key, line_number)?,
+
+                "phase" => {
+                    keyframe.phase = value(value_text, key, line_number)?;
+
+                    palette = true;
+                }
+
+                // Interpolating k needs a value at both ends, so it's all or nothing.
+                "k" if keyframe.k.is_none() && !first => {
+                    return Err(KeyframeError::new(line_number, "`k` must be set in the first keyframe if at all"));
+                }
+
+                "k" => keyframe.k = Some(value(value_text, key, line_number)?),
+
+                "easing" => {
+                    keyframe.easing = value_text.parse().map_err(|error| KeyframeError::new(line_number, error))?;
+                }
+
+                key => return Err(KeyframeError::new(line_number, format!("unknown key `{}`", key)))
+            }
+        }
+
+        if keys.is_empty() {
+            return Err(KeyframeError::new(source.lines().count(), "no keyframes"));
+        }
+
+        Ok(Self { keys, palette })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn frames(&self) -> usize {
+        self.keys.last().map_or(0, |key| key.frame + 1)
+    }
+
+    pub fn is_julia(&self) -> bool {
+        self.keys[0].k.is_some()
+    }
+
+    // Frames past the last keyframe hold it.
+    pub fn state(&self, frame: usize) -> State {
+        let frame = frame.min(self.keys[self.keys.len() - 1].frame);
+
+        let next = self.keys.iter().position(|key| key.frame >= frame).unwrap_or(self.keys.len() - 1);
+
+        let (from, to) = (self.keys[next.saturating_sub(1)], self.keys[next]);
+
+        let t = if to.frame > from.frame {
+            to.easing.apply((frame - from.frame) as f32 / (to.frame - from.frame) as f32)
+        } else {
+            1.0
+        };
+
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+
+        // Zooming geometrically keeps the rate of zooming in constant, and moving the center by how
+        // far the width has shrunk keeps a single point of the plane fixed on screen throughout,
+        // like a camera zooming into it, instead of the destination rushing past at the end.
+        let zoom = from.zoom * (to.zoom / from.zoom).powf(t);
+
+        let shrunk = 1.0 - from.zoom / to.zoom;
+
+        let u = if shrunk.abs() > 1e-6 { (1.0 - from.zoom / zoom) / shrunk } else { t };
+
+        State {
+            view: View { center: from.center + (to.center - from.center) * u, width: View::default().width / zoom },
+            iterations: lerp(from.iterations as f32, to.iterations as f32).round() as u32,
+            phase: lerp(from.phase, to.phase),
+            k: from.k.zip(to.k).map(|(a, b)| a + (b - a) * t)
+        }
+    }
+}
+
+// Writes the keyframes out in the format parse reads, with every value given.
+impl fmt::Display for Keyframes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, key) in self.keys.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+
+            writeln!(f, "[{}]", key.frame)?;
+            writeln!(f, "center = {}", key.center)?;
+            writeln!(f, "zoom = {}", key.zoom)?;
+            writeln!(f, "iterations = {}", key.iterations)?;
+
+            if self.palette {
+                writeln!(f, "phase = {}", key.phase)?;
+            }
+
+            if let Some(k) = key.k {
+                writeln!(f, "k = {}", k)?;
+            }
+
+            writeln!(f, "easing = {}", key.easing)?;
+        }
+
+        Ok(())
+    }
+}
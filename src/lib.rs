@@ -0,0 +1,40 @@
+pub mod analysis;
+pub mod animation;
+pub mod boundary;
+pub mod cache;
+pub mod components;
+pub mod cvd;
+pub mod diff;
+pub mod discover;
+pub mod export;
+pub mod dzi;
+pub mod external;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fit;
+pub mod fixed;
+pub mod fractal;
+pub mod jobs;
+pub mod json;
+pub mod keyframes;
+pub mod mesh;
+pub mod metadata;
+pub mod metrics;
+pub mod misiurewicz;
+#[cfg(feature = "evcxr")]
+pub mod notebook;
+pub mod palette;
+pub mod plot;
+pub mod polar;
+pub mod poster;
+pub mod potential;
+pub mod random;
+pub mod render;
+pub mod script;
+pub mod server;
+pub mod smoothing;
+pub mod strips;
+pub mod terminal;
+pub mod validate;
+pub mod viewport;
+pub mod websocket;
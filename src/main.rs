@@ -1,137 +1,1721 @@
-use bresenham::Bresenham;
-use image::{ImageBuffer, Pixel, Rgb, RgbImage};
+mod cli;
+
+use cli::{Command, Options};
+use polar_mandelbrot::fixed::Fixed;
+use polar_mandelbrot::fractal::{self, Escape, Escaping, Fractal, Julia, Limited, Mandelbrot};
+use polar_mandelbrot::keyframes::Keyframes;
+use polar_mandelbrot::palette::Palette;
+use polar_mandelbrot::render::{self, Colorer, CycleColorer, MembershipColorer, PaletteColorer};
+use polar_mandelbrot::script::Script;
+use polar_mandelbrot::{
+    analysis, animation, boundary, cache, components, cvd, diff, discover, dzi, export, external, fit, json, mesh,
+    metadata, misiurewicz, plot, polar, poster, potential, random, server, smoothing, strips, terminal, validate,
+    viewport
+};
+use image::{ImageResult, RgbImage};
 use num::complex::Complex;
-use plotters::prelude::*;
-use std::sync::{Arc, Mutex};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Instant;
 
-const IMG_WIDTH: u32 = 3960;
-const IMG_HEIGHT: u32 = 2160;
+// The parameters of the run, with the iteration limit it ended up with if it picked or escalated one,
+// and under a budget, how much of the render was done in time.
+fn parameters(options: &Options, limit: Option<u32>, render: Option<&render::Statistics>) -> json::Value {
+    let budgeted = options.budget.and(render);
 
-const BAILOUT_RADIUS: f32 = 2.0;
-const BAILOUT_ITERATIONS: u32 = 1000;
+    let (low, high) = options.roi.unwrap_or_else(viewport::bounds);
 
-fn complex_to_coordinate(c: Complex<f32>) -> (u32, u32) {
-    (
-        ((c.re / 2.0 + 1.0) / 2.0 * IMG_WIDTH as f32) as u32,
-        ((1.0 - (c.im / (2.0 * IMG_HEIGHT as f32 / IMG_WIDTH as f32) + 1.0) / 2.0) * IMG_HEIGHT as f32) as u32
-    )
+    let size = match options.roi {
+        Some(roi) => render::rect_size(roi, options.resolution),
+        None => (viewport::IMG_WIDTH, viewport::IMG_HEIGHT)
+    };
+
+    json::Value::object([
+        ("version", env!("CARGO_PKG_VERSION").into()),
+        (
+            "command",
+            match options.command {
+                Command::Render => "render",
+                Command::Analyze => "analyze",
+                Command::Plot => "plot",
+                Command::Animate => "animate",
+                Command::Serve => "serve",
+                Command::Validate => "validate",
+                Command::Verify => "verify",
+                Command::Selftest => "selftest",
+                Command::Recolor => "recolor",
+                Command::Discover => "discover",
+                Command::Misiurewicz => "misiurewicz",
+                Command::Nucleus => "nucleus",
+                Command::Dive => "dive",
+                Command::Stitch => "stitch",
+                Command::Diff => "diff",
+                Command::FromImage => "from-image"
+            }
+            .into()
+        ),
+        ("fractal", options.fractal.as_deref().unwrap_or("mandelbrot").into()),
+        ("script", options.script.as_ref().map(|path| path.display().to_string()).into()),
+        (
+            "julia",
+            options.julia.map(|k| json::Value::object([("re", k.re.into()), ("im", k.im.into())])).into()
+        ),
+        ("escape", options.escape.map(|escape| escape.to_string()).into()),
+        ("arithmetic", if options.fixed { "fixed64" } else { "f32" }.into()),
+        ("iterations", limit.or(options.iterations).or(options.escalate).unwrap_or(fractal::BAILOUT_ITERATIONS).into()),
+        ("auto_iterations", options.auto_iterations.into()),
+        ("budget", options.budget.map(|budget| budget.as_secs_f64()).into()),
+        ("rendered_rows", budgeted.map(|render| render.rendered_rows).into()),
+        ("gap", budgeted.map(|render| render.gap).into()),
+        (
+            "viewport",
+            json::Value::object([
+                ("re_min", low.re.into()),
+                ("re_max", high.re.into()),
+                ("im_min", low.im.into()),
+                ("im_max", high.im.into())
+            ])
+        ),
+        ("size", json::Value::array([size.0, size.1])),
+        ("antialias", options.antialias.into()),
+        ("theme", options.theme.as_deref().into()),
+        (
+            "polar_origin",
+            json::Value::object([("re", options.polar_origin.re.into()), ("im", options.polar_origin.im.into())])
+        ),
+        ("max_radius", polar::MAX_RADIUS.into()),
+        (
+            "theta_range",
+            match options.domain {
+                polar::Domain::Full => json::Value::Null,
+                polar::Domain::Range(start, end) => json::Value::array([start, end])
+            }
+        ),
+        ("samples", options.samples.into()),
+        ("ray_step", (options.command == Command::Analyze).then_some(options.ray_step).into()),
+        ("refine", options.refine.into())
+    ])
 }
 
-fn coordinate_to_complex((x, y): (u32, u32)) -> Complex<f32> {
-    Complex::new(
-        2.0 * (x as f32 / IMG_WIDTH as f32 * 2.0 - 1.0),
-        2.0 * IMG_HEIGHT as f32 / IMG_WIDTH as f32 * ((1.0 - y as f32 / IMG_HEIGHT as f32) * 2.0 - 1.0)
-    )
+// Each frame marches its own Julia set, so they're computed in parallel like the rows of a render.
+async fn sweep(options: &Options, (from, to): (Complex<f32>, Complex<f32>)) {
+    let frames = options.frames;
+
+    let tasks = (0..frames).map(|i| {
+        let t = if frames > 1 { i as f32 / (frames - 1) as f32 } else { 0.0 };
+
+        let k = from + (to - from) * t;
+
+        let (origin, domain, samples, step, refine) =
+            (options.polar_origin, options.domain, options.samples, options.ray_step, options.refine);
+
+        let style = options.plot_style.clone();
+
+        tokio::spawn(async move {
+            let julia = Julia { k };
+
+            let profile = polar::sample_profile(domain, samples, |theta| {
+                let ray = polar::Ray::new(origin, theta);
+
+                let bracket = polar::march_radius(&julia, ray, step);
+
+                match refine {
+                    Some(tolerance) => polar::refine_radius(&julia, ray, bracket, tolerance),
+                    None => bracket.inside
+                }
+            });
+
+            let caption = format!("k = {}", k);
+
+            let path = format!("output_sweep_{:04}.png", i);
+
+            plot::plot_polar(
+                &plot::PolarPlot {
+                    profile: &profile,
+                    caption: Some(&caption),
+                    style: Some(&style),
+                    domain,
+                    ..Default::default()
+                },
+                Path::new(&path)
+            )
+            .unwrap();
+        })
+    });
+
+    futures::future::join_all(tasks).await;
+
+    eprintln!("sweep: wrote {} frames", frames);
+}
+
+fn compare(options: &Options) {
+    let profiles = options
+        .compare
+        .iter()
+        .map(|path| {
+            let profile = export::read_polar_csv(path).unwrap_or_else(|error| {
+                eprintln!("{}: {}", path.display(), error);
+
+                std::process::exit(1);
+            });
+
+            let label = path
+                .file_stem()
+                .map_or_else(|| path.display().to_string(), |stem| stem.to_string_lossy().into_owned());
+
+            (label, profile)
+        })
+        .collect::<Vec<_>>();
+
+    plot::plot_comparison(&profiles, &options.plot_style, &options.plot).unwrap();
+
+    if let Some(protocol) = options.inline {
+        display_plot(&options.plot, protocol);
+    }
+}
+
+// Writes the rendered set, and a copy shrunk by each --downscale factor beside it.
+fn save_set(image: &RgbImage, downscale: &[u32]) -> ImageResult<()> {
+    image.save("output_set.png")?;
+
+    for &factor in downscale {
+        render::downscale(image, factor).save(format!("output_set_{}.png", factor))?;
+    }
+
+    Ok(())
 }
 
-fn compute_radius(img_buf: &RgbImage, theta: f32) -> f32 {
-    let origin = complex_to_coordinate(Complex::new(0.0, 0.0));
+// Records the version and the parameters of the run in each of the images that's a PNG.
+fn embed_parameters<'a>(
+    options: &Options,
+    limit: Option<u32>,
+    render: Option<&render::Statistics>,
+    paths: impl IntoIterator<Item = &'a Path>
+) {
+    let entries = [
+        ("Software", format!("polar_mandelbrot {}", env!("CARGO_PKG_VERSION"))),
+        (metadata::PARAMETERS, parameters(options, limit, render).to_string())
+    ];
 
-    let end =
-        complex_to_coordinate(Complex::new(2.0 * theta.cos(), 2.0 * theta.sin()));
+    let png = |path: &&Path| path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
 
-    let mut last_member = origin;
+    for path in paths.into_iter().filter(png) {
+        if let Err(error) = metadata::embed(path, &entries) {
+            eprintln!("metadata: {}: {}", path.display(), error);
 
-    for (x, y) in Bresenham::new(
-        (origin.0 as isize, origin.1 as isize),
-        (end.0 as isize, end.1 as isize)
-    ) {
-        if img_buf.get_pixel(x as u32, y as u32).channels() != [255, 255, 255] {
-            break;
+            std::process::exit(1);
         }
+    }
+}
+
+// Renders the set, or as much of it as there's time for before the deadline.
+async fn render_within(
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    smooth: bool,
+    deadline: Option<Instant>
+) -> (render::IterationBuffer, RgbImage) {
+    let Some(deadline) = deadline else {
+        return render::render(fractal, colorer, smooth).await;
+    };
 
-        last_member = (x as u32, y as u32);
+    let (iterations, image) = render::render_within(fractal, colorer, smooth, deadline).await;
+
+    if iterations.rendered_rows() < viewport::IMG_HEIGHT {
+        eprintln!(
+            "budget: out of time after {} of {} rows; the others are copies of the nearest above, up to {} rows away, \
+             which the statistics and r(θ) leave out",
+            iterations.rendered_rows(),
+            viewport::IMG_HEIGHT,
+            iterations.gap() - 1
+        );
+    } else {
+        eprintln!("budget: every row rendered in time");
     }
 
-    coordinate_to_complex(last_member).norm()
+    (iterations, image)
 }
 
-fn compute_row(y: u32) -> Vec<Rgb<u8>> {
-    let mut result = Vec::with_capacity(IMG_WIDTH as usize);
+// Renders the set, escalating the iteration limit or picking it first if asked to, along with the
+// limit it ran to.
+async fn render_set(
+    options: &Options,
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>
+) -> (render::IterationBuffer, RgbImage, u32) {
+    let smooth = options.smooth_iterations.is_some() || options.mesh.is_some();
+
+    let deadline = options.budget.map(|budget| Instant::now() + budget);
+
+    match options.escalate {
+        Some(target) => {
+            let tolerance = options.escalate_tolerance;
 
-    for x in 0..IMG_WIDTH {
-        let c = coordinate_to_complex((x, y));
+            render::render_escalating(fractal, colorer, smooth, target, tolerance, deadline).await
+        }
+
+        None if options.auto_iterations => {
+            let (limit, (width, height)) = render::auto_iterations(fractal.clone()).await;
 
-        let mut z = Complex::new(0.0, 0.0);
+            eprintln!("auto-iterations: {} iterations, from a {}x{} pass", limit, width, height);
 
-        let mut i = 0;
+            let fractal = Arc::new(Limited { fractal, iterations: limit });
 
-        while z.norm() < BAILOUT_RADIUS && i < BAILOUT_ITERATIONS {
-            z = z * z + c;
+            let (iterations, image) = render_within(fractal, colorer, smooth, deadline).await;
 
-            i += 1;
+            (iterations, image, limit)
         }
 
-        result.push(
-            Rgb::<u8>(
-                if i < BAILOUT_ITERATIONS {
-                    [0, 0, 0]
-                } else {
-                    [255, 255, 255]
-                }
-            )
+        None => {
+            let (iterations, image) = render_within(fractal, colorer, smooth, deadline).await;
+
+            (iterations, image, options.iterations.unwrap_or(fractal::BAILOUT_ITERATIONS))
+        }
+    }
+}
+
+// Renders the set, or reads it back from the cache if it was rendered with the same parameters before.
+async fn cached_render(
+    options: &Options,
+    dir: &Path,
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>
+) -> (render::IterationBuffer, RgbImage, u32) {
+    let script = options.script.as_ref().map_or(Ok(Vec::new()), std::fs::read).unwrap_or_default();
+
+    let julia = options.julia.map(|k| k.to_string()).unwrap_or_default();
+
+    let escape = fractal.escape().to_string();
+
+    let smooth = options.smooth_iterations.is_some() || options.mesh.is_some();
+
+    let key = cache::key(&[
+        env!("CARGO_PKG_VERSION").as_bytes(),
+        options.fractal.as_deref().unwrap_or("mandelbrot").as_bytes(),
+        julia.as_bytes(),
+        escape.as_bytes(),
+        &[smooth as u8],
+        &[options.fixed as u8],
+        &script,
+        options.theme.as_deref().unwrap_or_default().as_bytes(),
+        &options.iterations.or(options.escalate).unwrap_or(fractal::BAILOUT_ITERATIONS).to_le_bytes(),
+        &[options.escalate.is_some() as u8],
+        &[options.auto_iterations as u8],
+        &options.escalate_tolerance.to_le_bytes(),
+        &viewport::IMG_WIDTH.to_le_bytes(),
+        &viewport::IMG_HEIGHT.to_le_bytes()
+    ]);
+
+    if let Some(cached) = cache::load(dir, &key) {
+        eprintln!("cache: reusing render {} from {}", key, dir.display());
+
+        return cached;
+    }
+
+    let (iterations, image, limit) = render_set(options, fractal, colorer).await;
+
+    match cache::store(dir, &key, (&iterations, &image, limit)) {
+        Ok(()) => eprintln!("cache: stored render {} in {}", key, dir.display()),
+        Err(error) => eprintln!("cache: couldn't store the render in {}: {}", dir.display(), error)
+    }
+
+    (iterations, image, limit)
+}
+
+// Prints the best views discover finds, each ready to pass to animate, with a thumbnail of each in
+// the theme or the cosine palette.
+async fn discover(options: &Options) {
+    let mut random = options.seed.map_or_else(random::Random::from_time, random::Random::new);
+
+    let discoveries = discover::discover(options.candidates, options.results, &mut random).await;
+
+    eprintln!(
+        "discover: the best {} views near the boundary from {} random points",
+        discoveries.len(),
+        options.candidates
+    );
+
+    let colorer = Arc::new(PaletteColorer { phase: 0.0, theme: theme(options) });
+
+    for (rank, discovery) in discoveries.iter().enumerate() {
+        let path = format!("output_discover_{}.png", rank + 1);
+
+        let image = render::render_view(
+            Arc::new(Mandelbrot),
+            colorer.clone(),
+            discover::THUMBNAIL,
+            discovery.view,
+            options.antialias
+        )
+        .await;
+
+        if let Err(error) = image.save(&path) {
+            eprintln!("discover: {}", error);
+
+            std::process::exit(1);
+        }
+
+        eprintln!(
+            "discover {}: --target {} --zoom {}, escape count variance {}, in {}",
+            rank + 1,
+            discovery.view.center,
+            discovery.zoom(),
+            discovery.score,
+            path
         );
     }
+}
+
+// Prints the Misiurewicz point --near converges to and, with --close-up, renders it marked.
+async fn misiurewicz(options: &Options) {
+    let (Some(preperiod), Some(period), Some(near)) = (options.preperiod, options.period, options.near) else {
+        unreachable!("the options were checked");
+    };
+
+    let Some(point) = misiurewicz::locate(preperiod, period, near) else {
+        eprintln!(
+            "misiurewicz: Newton's method from {} didn't converge to a point of preperiod {} and period {}",
+            near,
+            preperiod,
+            period
+        );
+
+        std::process::exit(1);
+    };
+
+    eprintln!(
+        "misiurewicz: preperiod {}, period {} at {}, cycle multiplier {} (modulus {})",
+        point.preperiod,
+        point.period,
+        point.c,
+        point.multiplier,
+        point.multiplier.norm()
+    );
+
+    close_up(options, "misiurewicz", point.c).await;
+}
 
-    result
+// Prints the center of the component of --period that --near converges to and, with --close-up,
+// renders it marked.
+async fn nucleus(options: &Options) {
+    let (Some(period), Some(near)) = (options.period, options.near) else {
+        unreachable!("the options were checked");
+    };
+
+    let Some(center) = components::nucleus(period, near) else {
+        eprintln!("nucleus: Newton's method from {} didn't converge to a center of period {}", near, period);
+
+        std::process::exit(1);
+    };
+
+    eprintln!("nucleus: period {} at {}, size {}", period, center, components::size(period, center));
+
+    close_up(options, "nucleus", center).await;
 }
 
-async fn compute_and_set_row(img_buf_mutex: Arc<Mutex<RgbImage>>, y: u32) {
-    let row = compute_row(y);
+// With --close-up, renders a view that wide around the point found by the command, with the point
+// ringed, to output_<command>.png.
+async fn close_up(options: &Options, command: &str, point: Complex<f64>) {
+    let Some(width) = options.close_up else {
+        return;
+    };
+
+    let c = Complex::new(point.re as f32, point.im as f32);
+
+    let view = viewport::View { center: c, width };
+
+    let colorer = Arc::new(PaletteColorer { phase: 0.0, theme: theme(options) });
+
+    let image =
+        render::render_view(Arc::new(Mandelbrot), colorer, viewport::CLOSE_UP_SIZE, view, options.antialias).await;
 
-    let mut img_buf = img_buf_mutex.lock().unwrap();
+    let path = format!("output_{}.png", command);
 
-    for x in 0..IMG_WIDTH {
-        img_buf.put_pixel(x, y, row[x as usize]);
+    if let Err(error) = plot::plot_marks(&image, view, &[c], Path::new(&path)) {
+        eprintln!("{}: {}", command, error);
+
+        std::process::exit(1);
     }
+
+    eprintln!("{}: close-up {} wide in {}", command, width, path);
 }
 
-fn plot_polar(img_buf: &RgbImage) -> Result<(), Box<dyn std::error::Error>> {
-    let domain = 0.0..std::f32::consts::PI * 2.0;
-    let domain_size = 1000;
+// The built-in theme --theme names, already checked to exist.
+fn theme(options: &Options) -> Option<Palette> {
+    options.theme.as_deref().and_then(Palette::theme)
+}
 
-    let root = BitMapBackend::new("output_plot.png", (1280, 960)).into_drawing_area();
+fn recolor(path: &Path, options: &Options) {
+    let iterations = render::IterationBuffer::load(path).unwrap_or_else(|error| {
+        eprintln!("{}: {}", path.display(), error);
 
-    root.fill(&WHITE)?;
+        std::process::exit(1);
+    });
 
-    let mut chart = ChartBuilder::on(&root)
-        .margin(5)
-        .x_label_area_size(30)
-        .y_label_area_size(30)
-        .build_cartesian_2d(domain.start..domain.end, 0.0f32..2.0f32)?;
+    let colorer: Box<dyn Colorer> = match &options.palette {
+        Some(palette) => match Palette::load(palette) {
+            Ok(palette) => Box::new(CycleColorer { palette }),
+            Err(error) => {
+                eprintln!("{}: {}", palette.display(), error);
 
-    chart.configure_mesh().draw()?;
+                std::process::exit(1);
+            }
+        },
 
-    chart.draw_series(
-        LineSeries::new(
-            (0..domain_size)
-                .map(|i| domain.start + (domain.end - domain.start) / domain_size as f32 * i as f32)
-                .map(|theta| (theta, compute_radius(&img_buf, theta))),
+        None => Box::new(PaletteColorer { phase: 0.0, theme: theme(options) })
+    };
 
-            &RED
-        )
-    )?;
+    let output = options.output.as_deref().unwrap_or(Path::new("output_recolor.png"));
 
-    root.present()?;
+    if let Err(error) = render::recolor(&iterations, colorer.as_ref()).save(output) {
+        eprintln!("recolor: {}", error);
 
-    Ok(())
+        std::process::exit(1);
+    }
+
+    eprintln!("recolor: {}x{} to {}", iterations.width(), iterations.height(), output.display());
+}
+
+// Compares two images, or two iteration buffers for any path that isn't named like an image.
+fn diff(a: &Path, b: &Path, options: &Options) {
+    let fail = |path: &Path, error: &dyn std::fmt::Display| -> ! {
+        eprintln!("{}: {}", path.display(), error);
+
+        std::process::exit(1);
+    };
+
+    let is_image = |path: &Path| image::ImageFormat::from_path(path).is_ok();
+
+    let difference = match (is_image(a), is_image(b)) {
+        (true, true) => {
+            let load = |path: &Path| image::open(path).unwrap_or_else(|error| fail(path, &error)).to_rgb8();
+
+            diff::images(&load(a), &load(b))
+        }
+
+        (false, false) => {
+            let load = |path: &Path| render::IterationBuffer::load(path).unwrap_or_else(|error| fail(path, &error));
+
+            diff::buffers(&load(a), &load(b))
+        }
+
+        _ => Err("diff compares two images or two iteration buffers, not one of each".to_string())
+    };
+
+    let difference = difference.unwrap_or_else(|error| {
+        eprintln!("diff: {}", error);
+
+        std::process::exit(1);
+    });
+
+    let unit = if difference.membership.is_some() { "iterations" } else { "levels" };
+
+    eprintln!(
+        "diff: {} of {} pixels differ ({:.4}%), by at most {} {}, mean {:.4}, rms {:.4}",
+        difference.differing,
+        difference.pixels,
+        100.0 * difference.differing as f64 / difference.pixels.max(1) as f64,
+        difference.max,
+        unit,
+        difference.mean,
+        difference.rms
+    );
+
+    if let Some(membership) = difference.membership {
+        eprintln!("diff: {} pixels are members in one and escaped in the other", membership);
+    }
+
+    if let Some(psnr) = difference.psnr() {
+        eprintln!("diff: PSNR {:.2} dB", psnr);
+    }
+
+    let output = options.output.as_deref().unwrap_or(Path::new("output_diff.png"));
+
+    if let Err(error) = difference.heatmap.save(output) {
+        fail(output, &error);
+    }
+
+    eprintln!("diff: heatmap to {}", output.display());
+}
+
+// Terminals only display bitmaps, so SVG plots are left out.
+fn display_plot(path: &Path, protocol: terminal::Protocol) {
+    if path.extension().is_some_and(|extension| extension == "svg") {
+        eprintln!("inline: {} is SVG, so it isn't displayed", path.display());
+    } else {
+        terminal::display(path, protocol).unwrap();
+    }
+}
+
+fn parse(args: impl IntoIterator<Item = String>) -> Options {
+    match cli::parse(args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, cli::USAGE);
+
+            std::process::exit(2);
+        }
+    }
+}
+
+// The arguments of a render of the rectangle the parameters record, the whole view for renders
+// that weren't of a --roi, at the width if given and the recorded size otherwise.
+fn recorded_arguments(parameters: &json::Value, width: Option<u32>) -> Option<Vec<String>> {
+    let mut arguments = vec!["render".to_string()];
+
+    let text = |key: &str| parameters.get(key).and_then(json::Value::as_str);
+
+    let number = |value: Option<&json::Value>| value.and_then(json::Value::as_f64);
+
+    if let Some(script) = text("script") {
+        arguments.extend(["--script".to_string(), script.to_string()]);
+    } else if let Some(fractal) = text("fractal").filter(|&fractal| fractal != "mandelbrot") {
+        arguments.extend(["--fractal".to_string(), fractal.to_string()]);
+    }
+
+    if let Some(k) = parameters.get("julia").filter(|&k| *k != json::Value::Null) {
+        let k = Complex::new(number(k.get("re"))?, number(k.get("im"))?);
+
+        arguments.extend(["--julia".to_string(), k.to_string()]);
+    }
+
+    if let Some(escape) = text("escape") {
+        arguments.extend(["--escape".to_string(), escape.to_string()]);
+    }
+
+    if let Some(theme) = text("theme") {
+        arguments.extend(["--theme".to_string(), theme.to_string()]);
+    }
+
+    if text("arithmetic") == Some("fixed64") {
+        arguments.extend(["--precision".to_string(), "fixed64".to_string()]);
+    }
+
+    let iterations = number(parameters.get("iterations"))?;
+    let antialias = number(parameters.get("antialias"))?;
+
+    let viewport = parameters.get("viewport")?;
+
+    let bound = |key: &str| number(viewport.get(key));
+
+    let low = Complex::new(bound("re_min")?, bound("im_min")?);
+    let high = Complex::new(bound("re_max")?, bound("im_max")?);
+
+    // --resolution is of the longer side, so a narrow view needs more than its width.
+    let resolution = match width {
+        Some(width) if high.re - low.re < high.im - low.im => {
+            (width as f64 * (high.im - low.im) / (high.re - low.re)).round()
+        }
+
+        Some(width) => width as f64,
+        None => parameters.get("size")?.as_array()?.iter().filter_map(json::Value::as_f64).fold(0.0, f64::max)
+    };
+
+    arguments.extend([
+        "--iterations".to_string(),
+        iterations.to_string(),
+        "--antialias".to_string(),
+        antialias.to_string(),
+        "--roi".to_string(),
+        format!("{},{},{},{}", low.re, high.re, low.im, high.im),
+        "--resolution".to_string(),
+        resolution.to_string()
+    ]);
+
+    Some(arguments)
+}
+
+// Reads the parameters the image records and parses them as a render, followed by the overrides.
+fn from_image(path: &Path, width: Option<u32>, overrides: &[String]) -> Options {
+    let fail = |message: &str| -> ! {
+        eprintln!("from-image: {}: {}", path.display(), message);
+
+        std::process::exit(1);
+    };
+
+    let entries = metadata::read(path).unwrap_or_else(|error| fail(&error.to_string()));
+
+    let Some((_, recorded)) = entries.iter().find(|(keyword, _)| keyword == metadata::PARAMETERS) else {
+        fail("no parameters are recorded in it; was it written by polar_mandelbrot?");
+    };
+
+    let parameters = json::Value::parse(recorded).unwrap_or_else(|error| fail(&error));
+
+    let version = parameters.get("version").and_then(json::Value::as_str).unwrap_or("an unknown version");
+
+    if version != env!("CARGO_PKG_VERSION") {
+        eprintln!("from-image: rendered by {}, not {}, so it may not match", version, env!("CARGO_PKG_VERSION"));
+    }
+
+    let arguments = recorded_arguments(&parameters, width);
+    let arguments = arguments.unwrap_or_else(|| fail("the recorded parameters are incomplete"));
+
+    eprintln!("from-image: {}", arguments.join(" "));
+
+    parse(arguments.into_iter().chain(overrides.iter().cloned()))
+}
+
+#[cfg(unix)]
+extern "C" {
+    fn setpriority(which: i32, who: u32, priority: i32) -> i32;
+}
+
+// Drops the process to the lowest priority before any worker thread starts, since threads take
+// theirs from the thread that creates them.
+#[cfg(unix)]
+fn lower_priority() {
+    const PRIO_PROCESS: i32 = 0;
+
+    // 19 is the lowest niceness; 0 for `who` is this process.
+    if unsafe { setpriority(PRIO_PROCESS, 0, 19) } != 0 {
+        eprintln!("nice: couldn't lower the priority: {}", std::io::Error::last_os_error());
+    }
+}
+
+#[cfg(not(unix))]
+fn lower_priority() {
+    eprintln!("nice: lowering the priority is only supported on Unix");
+}
+
+fn main() {
+    let options = parse(std::env::args().skip(1));
+
+    let options = match &options.from_image {
+        Some(path) => from_image(path, options.width, &options.overrides),
+        None => options
+    };
+
+    if options.nice {
+        lower_priority();
+    }
+
+    let mut runtime = tokio::runtime::Builder::new_multi_thread();
+
+    runtime.enable_all();
+
+    if let Some(percent) = options.max_cpu {
+        let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+
+        let threads = (cores * percent as usize / 100).max(1);
+
+        eprintln!("max-cpu: {} of {} cores", threads, cores);
+
+        // Blocking tasks, like server jobs, count against the share too.
+        runtime.worker_threads(threads).max_blocking_threads(threads);
+    }
+
+    runtime.build().expect("the runtime starts").block_on(run(options));
 }
 
-#[tokio::main]
-async fn main() {
-    let img_buf_mutex = Arc::new(Mutex::new(ImageBuffer::new(IMG_WIDTH, IMG_HEIGHT)));
+async fn run(options: Options) {
+    if options.help {
+        println!("{}", cli::USAGE);
+
+        return;
+    }
+
+    if options.command == Command::Plot {
+        compare(&options);
+
+        return;
+    }
+
+    if let Some(path) = &options.recolor {
+        recolor(path, &options);
+
+        return;
+    }
+
+    if let Some((a, b)) = &options.diff {
+        diff(a, b, &options);
 
-    let mut futures = Vec::new();
+        return;
+    }
+
+    if let Some(directory) = &options.stitch {
+        let output = options.output.as_deref().unwrap_or(Path::new("output_poster.png"));
+
+        match poster::stitch(directory, output) {
+            Ok(poster) => {
+                let (columns, rows) = poster.grid();
+
+                let (width, height) = poster.size;
+
+                let output = output.display();
+
+                eprintln!("stitch: {}x{} from {}x{} tiles, seams agree, to {}", width, height, columns, rows, output);
+            }
+
+            Err(error) => {
+                eprintln!("stitch: {}", error);
+
+                std::process::exit(1);
+            }
+        }
+
+        return;
+    }
+
+    if let Some(sweep_range) = options.julia_sweep {
+        sweep(&options, sweep_range).await;
+
+        return;
+    }
+
+    let (fractal, colorer): (Arc<dyn Fractal>, Arc<dyn Colorer>) = match &options.script {
+        Some(path) => {
+            let script = Script::load(path).unwrap_or_else(|error| {
+                eprintln!("{}: {}", path.display(), error);
+
+                std::process::exit(1);
+            });
+
+            let script = Arc::new(script.with_limit(options.iterations.unwrap_or(fractal::BAILOUT_ITERATIONS)));
+
+            (script.clone(), script)
+        }
+
+        None => {
+            let fractal = match &options.fractal {
+                Some(name) => fractal::from_name(name).unwrap_or_else(|| {
+                    eprintln!("unknown fractal `{}`\n\n{}", name, cli::USAGE);
+
+                    std::process::exit(2);
+                }),
+
+                None if options.fixed => {
+                    let escape = options.escape.unwrap_or(Escape::Norm(fractal::BAILOUT_RADIUS));
+
+                    Arc::new(Fixed::new(options.julia, escape).expect("the escape condition was checked"))
+                }
+
+                None => match options.julia {
+                    Some(k) => Arc::new(Julia { k }) as Arc<dyn Fractal>,
+                    None => Arc::new(Mandelbrot)
+                }
+            };
+
+            let colorer: Arc<dyn Colorer> = match theme(&options) {
+                Some(theme) => Arc::new(PaletteColorer { phase: 0.0, theme: Some(theme) }),
+                None => Arc::new(MembershipColorer)
+            };
+
+            (fractal, colorer)
+        }
+    };
+
+    // The fixed-point kernel tests the escape condition itself.
+    let fractal = match options.escape.filter(|_| !options.fixed) {
+        Some(escape) => Arc::new(Escaping { fractal, escape }),
+        None => fractal
+    };
+
+    let fractal = match options.iterations {
+        Some(iterations) => Arc::new(Limited { fractal, iterations }),
+        None => fractal
+    };
+
+    if options.command == Command::Animate {
+        let keyframes = options.keyframes.as_ref().map(|path| {
+            let keyframes = Keyframes::load(path).unwrap_or_else(|error| {
+                eprintln!("{}: {}", path.display(), error);
 
-    for y in 0..IMG_HEIGHT {
-        futures.push(tokio::spawn(compute_and_set_row(img_buf_mutex.clone(), y)));
+                std::process::exit(1);
+            });
+
+            let fractal = options.fractal.is_some() || options.script.is_some() || options.julia.is_some();
+
+            if keyframes.is_julia() && fractal {
+                eprintln!("{}: setting k can't be combined with --fractal, --script, or --julia", path.display());
+
+                std::process::exit(2);
+            }
+
+            keyframes
+        });
+
+        let (size, frames) = (options.frame_size, keyframes.as_ref().map_or(options.frames, Keyframes::frames));
+
+        let sink = animation::Sink::create(options.animation.as_deref(), size, frames, options.encoding);
+
+        let result = match sink {
+            Ok(mut sink) => {
+                let rendered = match (&keyframes, &options.morph, options.target) {
+                    (Some(keyframes), _, _) => {
+                        let theme = theme(&options);
+
+                        animation::keyframes(fractal, colorer, theme, keyframes, options.antialias, size, &mut sink)
+                            .await
+                    }
+
+                    (None, Some(morph), _) => {
+                        let morph = animation::JuliaMorph {
+                            morph: morph.clone(),
+                            frames: options.frames,
+                            iterations: options.iterations.unwrap_or(fractal::BAILOUT_ITERATIONS),
+                            samples: options.antialias,
+                            size: options.frame_size,
+                            inset: options.inset.then_some(animation::Inset {
+                                origin: options.polar_origin,
+                                samples: options.samples,
+                                ray_step: options.ray_step
+                            })
+                        };
+
+                        animation::morph(colorer, &morph, &mut sink).await
+                    }
+
+                    (None, None, target) => {
+                        let zoom = animation::Zoom {
+                            target: target.expect("animate requires --target, --morph, or --keyframes"),
+                            magnification: options.zoom,
+                            frames: options.frames,
+                            iterations: options.iterations.unwrap_or(fractal::BAILOUT_ITERATIONS),
+                            iteration_growth: options.iteration_growth,
+                            samples: options.antialias,
+                            size: options.frame_size
+                        };
+
+                        animation::zoom(fractal, colorer, &zoom, &mut sink).await
+                    }
+                };
+
+                rendered.and_then(|()| sink.finish())
+            }
+
+            Err(error) => Err(error)
+        };
+
+        if let Err(error) = result {
+            eprintln!("animate: {}", error);
+
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    if let Some(path) = &options.dzi {
+        let view = viewport::View::default();
+
+        if let Err(error) = dzi::write(path, fractal, colorer, view, options.dzi_size, options.antialias).await {
+            eprintln!("dzi: {}", error);
+
+            std::process::exit(1);
+        }
+
+        return;
     }
 
-    futures::future::join_all(futures).await;
+    if let Some(roi) = options.roi {
+        let size = render::rect_size(roi, options.resolution);
+
+        if let Some(directory) = &options.poster {
+            let poster = poster::Poster { size, rect: roi, tile: options.tile, samples: options.antialias };
+
+            if let Err(error) = poster::render(directory, fractal, colorer, &poster, options.shard).await {
+                eprintln!("poster: {}", error);
+
+                std::process::exit(1);
+            }
+
+            let (columns, rows) = poster.grid();
+
+            eprintln!("poster: {}x{} in {}x{} tiles under {}", size.0, size.1, columns, rows, directory.display());
+
+            return;
+        }
+
+        if let Some(budget) = options.max_memory {
+            let rows = strips::rows(size.0, budget).expect("the budget was checked to fit a row");
 
-    let img_buf = img_buf_mutex.lock().unwrap();
+            let path = Path::new("output_set.png");
 
-    img_buf.save("output_set.png").unwrap();
+            if let Err(error) = strips::write(path, fractal, colorer, size, roi, options.antialias, rows).await {
+                eprintln!("roi: {}", error);
 
-    plot_polar(&img_buf).unwrap();
+                std::process::exit(1);
+            }
+
+            eprintln!("roi: {}x{} from {} to {}, in strips of {} rows", size.0, size.1, roi.0, roi.1, rows.min(size.1));
+
+            embed_parameters(&options, None, None, [path]);
+
+            return;
+        }
+
+        let image = render::render_rect(fractal, colorer, size, roi, options.antialias).await;
+
+        if let Err(error) = save_set(&image, &options.downscale) {
+            eprintln!("roi: {}", error);
+
+            std::process::exit(1);
+        }
+
+        eprintln!("roi: {}x{} from {} to {}", size.0, size.1, roi.0, roi.1);
+
+        embed_parameters(&options, None, None, [Path::new("output_set.png")]);
+
+        return;
+    }
+
+    if options.command == Command::Validate {
+        let validation = validate::area(fractal, options.levels).await;
+
+        match validation.order {
+            Some(order) => eprintln!("validate: converging with order {:.2} per level", order),
+            None => eprintln!("validate: the levels don't converge steadily, so the last is taken as is")
+        }
+
+        eprintln!(
+            "validate: extrapolated area {} against the published {}, {:.3}% off",
+            validation.extrapolated,
+            analysis::PUBLISHED_AREA,
+            validation.relative_error() * 100.0
+        );
+
+        if validation.passed() {
+            eprintln!("validate: passed");
+        } else {
+            eprintln!(
+                "validate: failed; every level should be closer to the published area than the last, and the \
+                 extrapolation within {}%",
+                validate::TOLERANCE * 100.0
+            );
+
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    if options.command == Command::Selftest {
+        let checks = validate::selftest().await;
+
+        for check in &checks {
+            let result = if check.passed { "passed" } else { "FAILED" };
+
+            eprintln!("selftest: {}: {} ({})", check.name, result, check.detail);
+        }
+
+        let failed = checks.iter().filter(|check| !check.passed).count();
+
+        if failed > 0 {
+            eprintln!("selftest: {} of {} checks failed", failed, checks.len());
+
+            std::process::exit(1);
+        }
+
+        eprintln!("selftest: all {} checks passed", checks.len());
+
+        return;
+    }
+
+    if options.command == Command::Verify {
+        let mut random = options.seed.map_or_else(random::Random::from_time, random::Random::new);
+
+        let limit = options.iterations.unwrap_or(fractal::BAILOUT_ITERATIONS);
+
+        let check = validate::spot_check(options.julia, options.samples, limit, &mut random);
+
+        let percent = |count: usize| count as f32 / check.samples as f32 * 100.0;
+
+        eprintln!(
+            "verify: the render disagrees with double precision at {} iterations on {} of {} pixels ({:.2}%)",
+            limit.saturating_mul(validate::REFERENCE_ITERATION_FACTOR),
+            check.disagreements(),
+            check.samples,
+            percent(check.disagreements())
+        );
+
+        eprintln!(
+            "verify: {} counted as members escape later, and {} counted as escaping don't",
+            check.false_members,
+            check.false_escapes
+        );
+
+        eprintln!(
+            "verify: {} ({:.2}%) disagree even at the render's {} iterations, so come from single precision",
+            check.precision,
+            percent(check.precision),
+            limit
+        );
+
+        return;
+    }
+
+    if options.command == Command::Discover {
+        discover(&options).await;
+
+        return;
+    }
+
+    if options.command == Command::Misiurewicz {
+        misiurewicz(&options).await;
+
+        return;
+    }
+
+    if options.command == Command::Nucleus {
+        nucleus(&options).await;
+
+        return;
+    }
+
+    if options.command == Command::Dive {
+        let dive = animation::Dive {
+            target: options.target.expect("dive requires --target"),
+            magnification: options.zoom,
+            frames: options.frames,
+            size: options.frame_size
+        };
+
+        let keyframes = animation::dive(fractal, &dive).await;
+
+        let path = options.output.as_deref().unwrap_or(Path::new("output_dive.keyframes"));
+
+        if let Err(error) = std::fs::write(path, keyframes.to_string()) {
+            eprintln!("dive: {}: {}", path.display(), error);
+
+            std::process::exit(1);
+        }
+
+        eprintln!("dive: wrote {}; play it with animate --keyframes {}", path.display(), path.display());
+
+        return;
+    }
+
+    if options.command == Command::Serve {
+        let settings = server::Settings {
+            port: options.port.unwrap_or(server::PORT),
+            address: options.bind.unwrap_or(server::ADDRESS),
+            cache_tiles: options.tile_cache.unwrap_or(server::CACHE_TILES),
+            iterations: options.iterations.unwrap_or(fractal::BAILOUT_ITERATIONS),
+            iteration_growth: options.iteration_growth,
+            samples: options.antialias
+        };
+
+        if let Err(error) = server::serve(fractal, colorer, settings).await {
+            eprintln!("serve: {}", error);
+
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
+    let ray = |theta: f32| polar::Ray::new(options.polar_origin, theta);
+
+    let sample = |radius: &dyn Fn(f32) -> f32| match options.adaptive {
+        Some(tolerance) => polar::sample_adaptive(options.domain, options.samples, tolerance, radius),
+        None => polar::sample_profile(options.domain, options.samples, radius)
+    };
+
+    let radius = |theta: f32, bracket: polar::Bracket| match options.refine {
+        Some(tolerance) => polar::refine_radius(fractal.as_ref(), ray(theta), bracket, tolerance),
+        None => bracket.inside
+    };
+
+    if !options.at.is_empty() {
+        let tolerance = options.refine.unwrap_or(cli::AT_TOLERANCE);
+
+        println!("theta\tr\tintervals");
+
+        for &theta in &options.at {
+            let bracket = polar::march_radius(fractal.as_ref(), ray(theta), options.ray_step);
+
+            let intervals = polar::march_intervals(fractal.as_ref(), ray(theta), options.ray_step)
+                .into_iter()
+                .map(|interval| {
+                    let (start, end) = polar::refine_interval(fractal.as_ref(), ray(theta), interval, Some(tolerance));
+
+                    format!("{}:{}", start, end)
+                })
+                .collect::<Vec<_>>();
+
+            println!(
+                "{}\t{}\t{}",
+                theta,
+                polar::refine_radius(fractal.as_ref(), ray(theta), bracket, tolerance),
+                intervals.join(" ")
+            );
+        }
+
+        return;
+    }
+
+    let needs_crossings = options.crossings.is_some()
+        || (options.polar_csv.is_some() && options.csv_crossings)
+        || options.polar_json.is_some()
+        || options.area
+        || options.julia.is_some();
+
+    let crossings = |intervals: &dyn Fn(f32) -> Vec<polar::Interval>| {
+        needs_crossings.then(|| {
+            polar::angles(options.domain, options.samples)
+                .map(|theta| {
+                    let refined = intervals(theta)
+                        .into_iter()
+                        .map(|interval| polar::refine_interval(fractal.as_ref(), ray(theta), interval, options.refine))
+                        .collect();
+
+                    (theta, refined)
+                })
+                .collect::<polar::Crossings>()
+        })
+    };
+
+    if options.log_polar {
+        render::render_log_polar(fractal.clone(), colorer.clone(), options.polar_origin)
+            .await
+            .save("output_log_polar.png")
+            .unwrap();
+    }
+
+    if let Some(count) = options.equipotential {
+        plot::plot_equipotentials(&potential::equipotentials(&potential::levels(count))).unwrap();
+    }
+
+    let external_rays = options
+        .external_rays
+        .iter()
+        .map(|&angle| {
+            let ray = external::trace(angle);
+
+            if let Some(landing) = ray.last() {
+                eprintln!("external ray {} lands near {}", angle, landing);
+            }
+
+            (angle, ray)
+        })
+        .collect::<Vec<_>>();
+
+    let orbit = options.orbit.map(|c| {
+        let limit = options.iterations.unwrap_or(fractal::BAILOUT_ITERATIONS);
+
+        let (orbit, sample) = fractal::orbit(fractal.as_ref(), c, limit);
+
+        match sample.termination {
+            Some(fractal::Termination::Escaped) => {
+                eprintln!("orbit of {}: escapes after {} iterations, to {}", c, sample.iterations, sample.z)
+            }
+
+            Some(fractal::Termination::Converged) => {
+                eprintln!("orbit of {}: converges after {} iterations, to {}", c, sample.iterations, sample.z)
+            }
+
+            None => eprintln!("orbit of {}: still bounded after {} iterations, at {}", c, sample.iterations, sample.z)
+        }
+
+        (c, orbit)
+    });
+
+    let components = options.components.map(|period| {
+        let components = components::find(period, options.spokes);
+
+        eprintln!("hyperbolic components: {} centers up to period {}", components.len(), period);
+
+        components
+    });
+
+    let nuclei = options.nuclei.map(|period| {
+        let nuclei = components::nuclei(period);
+
+        eprintln!("nuclei: {} up to period {} in the view", nuclei.len(), period);
+
+        for nucleus in &nuclei {
+            eprintln!("nucleus: period {} at {}, size {}", nucleus.period, nucleus.center, nucleus.size);
+        }
+
+        nuclei
+    });
+
+    let (profile, crossings, pixel_area, pixel_arc_length, statistics, limit) = match options.command {
+        Command::Render => {
+            let (iterations, img_buf, limit) = match &options.cache {
+                Some(dir) => cached_render(&options, dir, fractal.clone(), colorer).await,
+                None => render_set(&options, fractal.clone(), colorer).await
+            };
+
+            save_set(&img_buf, &options.downscale).unwrap();
+
+            if let Some(path) = &options.save_iterations {
+                iterations.save(path).unwrap();
+            }
+
+            let profile = sample(&|theta| radius(theta, polar::compute_radius(&iterations, ray(theta))));
+
+            if let Some(components) = &components {
+                plot::plot_components(&img_buf, components).unwrap();
+            }
+
+            if let Some(nuclei) = nuclei.as_ref().filter(|_| options.nuclei_plot) {
+                plot::plot_nuclei(&img_buf, nuclei).unwrap();
+            }
+
+            if !external_rays.is_empty() {
+                plot::plot_external_rays(&img_buf, &external_rays).unwrap();
+            }
+
+            if let Some((c, orbit)) = &orbit {
+                plot::plot_orbit(&img_buf, *c, orbit).unwrap();
+            }
+
+            if let Some(rays) = options.overlay {
+                plot::plot_overlay(&img_buf, &profile, options.polar_origin, rays).unwrap();
+            }
+
+            if options.annotate {
+                plot::plot_annotations(&img_buf, options.polar_origin).unwrap();
+            }
+
+            if options.boundary || options.boundary_export.is_some() {
+                let polylines = boundary::extract(&iterations);
+
+                eprintln!("boundary: {} polylines", polylines.len());
+
+                if options.boundary {
+                    plot::plot_boundary(&polylines, options.polar_origin).unwrap();
+                }
+
+                if let Some(path) = &options.boundary_export {
+                    match path.extension().and_then(|extension| extension.to_str()) {
+                        Some("svg") => export::write_boundary_svg(path, &polylines, options.precision).unwrap(),
+                        _ => export::write_json(path, &export::boundary_document(&polylines)).unwrap()
+                    }
+                }
+            }
+
+            if let Some(path) = &options.smooth_iterations {
+                export::write_smooth_iterations(path, &iterations).unwrap();
+            }
+
+            if let Some(path) = &options.mesh {
+                let mesh = mesh::heightfield(&iterations, options.mesh_scale, options.mesh_step);
+
+                let written = match path.extension().and_then(|extension| extension.to_str()) {
+                    Some("stl") => export::write_stl(path, &mesh),
+                    _ => export::write_obj(path, &mesh)
+                };
+
+                if let Err(error) = written {
+                    eprintln!("mesh: {}: {}", path.display(), error);
+
+                    std::process::exit(1);
+                }
+
+                let (vertices, triangles) = (mesh.vertices.len(), mesh.triangles.len());
+
+                eprintln!("mesh: {} vertices and {} triangles to {}", vertices, triangles, path.display());
+            }
+
+            if options.histogram.is_some() || options.histogram_plot {
+                let histogram = iterations.histogram();
+
+                let escaped = histogram.iter().sum::<u64>();
+
+                // Pixels escaping right up to the limit suggest others that would have escaped are
+                // being counted as members.
+                let late = histogram.iter().skip(limit as usize * 9 / 10).sum::<u64>();
+
+                eprintln!(
+                    "histogram: {} pixels escaped, {} in the last tenth of the {} iteration limit",
+                    escaped,
+                    late,
+                    limit
+                );
+
+                if let Some(path) = &options.histogram {
+                    export::write_histogram(path, &histogram).unwrap();
+                }
+
+                if options.histogram_plot {
+                    plot::plot_histogram(&histogram, limit).unwrap();
+                }
+            }
+
+            let statistics = iterations.statistics(limit);
+
+            eprintln!(
+                "render: {:.2}% of pixels interior and {:.2}% escaped, after {:.1} iterations on average and {} at \
+                 most",
+                statistics.interior_fraction() * 100.0,
+                statistics.escaped_fraction() * 100.0,
+                statistics.mean_iterations,
+                statistics.max_iterations
+            );
+
+            eprintln!(
+                "render: {} pixels reached the {} iteration limit without escaping",
+                statistics.capped,
+                limit
+            );
+
+            // The members counted in the rows rendered stand for the whole image.
+            let rendered = iterations.rendered_rows() as f32 / iterations.height() as f32;
+
+            let pixel_area = iterations.member_count() as f32 * viewport::pixel_area() / rendered;
+
+            // Copied rows double the boundary across them and hide it between them.
+            let complete = iterations.rendered_rows() == iterations.height();
+
+            if options.arc_length && !complete {
+                eprintln!("arc length: no pixel estimate, since some rows are copies");
+            }
+
+            let pixel_arc_length = (options.arc_length && complete)
+                .then(|| analysis::pixel_arc_length(iterations.boundary_edges(), viewport::pixel_size()));
+
+            (
+                profile,
+                crossings(&|theta| polar::compute_intervals(&iterations, ray(theta))),
+                Some(pixel_area),
+                pixel_arc_length,
+                Some(statistics),
+                Some(limit)
+            )
+        }
+
+        Command::Analyze => {
+            let profile = sample(&|theta| {
+                radius(theta, polar::march_radius(fractal.as_ref(), ray(theta), options.ray_step))
+            });
+
+            for (theta, r) in &profile {
+                println!("{}\t{}", theta, r);
+            }
+
+            (
+                profile,
+                crossings(&|theta| polar::march_intervals(fractal.as_ref(), ray(theta), options.ray_step)),
+                None,
+                None,
+                None,
+                None
+            )
+        }
+
+        Command::Plot
+        | Command::Animate
+        | Command::Serve
+        | Command::Validate
+        | Command::Verify
+        | Command::Selftest
+        | Command::Recolor
+        | Command::Discover
+        | Command::Misiurewicz
+        | Command::Nucleus
+        | Command::Dive
+        | Command::Stitch
+        | Command::Diff
+        | Command::FromImage => {
+            unreachable!("only render and analyze sample r(θ)")
+        }
+    };
+
+    if let (Some(k), Some(crossings)) = (options.julia, &crossings) {
+        let misses = crossings.iter().filter(|(_, intervals)| intervals.is_empty()).count();
+
+        eprintln!("julia set: {} of {} rays never hit a member", misses, crossings.len());
+
+        if misses == crossings.len() {
+            eprintln!("julia set: no ray hit it, so it looks disconnected around the polar origin");
+        }
+
+        let connected = Julia { k }.is_connected();
+
+        eprintln!(
+            "julia set: k = {} is {} the Mandelbrot set, so the Julia set is {}",
+            k,
+            if connected { "in" } else { "not in" },
+            if connected { "connected" } else { "disconnected" }
+        );
+    }
+
+    if let (Some(path), Some(crossings)) = (&options.crossings, &crossings) {
+        export::write_crossings(path, crossings, options.precision).unwrap();
+    }
+
+    if let Some(path) = &options.polar_csv {
+        let crossings = crossings.as_deref().filter(|_| options.csv_crossings);
+
+        let curvature = options.curvature.then(|| analysis::curvature(&profile));
+
+        export::write_polar_csv(path, &profile, crossings, curvature.as_deref(), options.precision).unwrap();
+    }
+
+    if options.adaptive.is_some() {
+        eprintln!("adaptive sampling: {} angles", profile.len());
+    }
+
+    let summary = analysis::summarize(&profile, options.domain);
+
+    eprintln!(
+        "radius: min {}, max {} at θ = {}, mean {}, variance {}, max/min {}",
+        summary.min,
+        summary.max,
+        summary.theta_max,
+        summary.mean,
+        summary.variance,
+        summary.ratio().map_or("undefined, as some ray starts outside the set".to_string(), |ratio| ratio.to_string())
+    );
+
+    let harmonics = options.fourier.map(|order| {
+        let harmonics = analysis::fourier(&profile, profile.len() / 2);
+
+        eprintln!("mean radius: {}", harmonics[0].cosine);
+
+        for harmonic in analysis::dominant_harmonics(&harmonics, 10) {
+            eprintln!(
+                "harmonic {}: amplitude {}, phase {}",
+                harmonic.order,
+                harmonic.amplitude(),
+                harmonic.phase()
+            );
+        }
+
+        plot::plot_fourier(&profile, &harmonics[..=order.min(harmonics.len() - 1)]).unwrap();
+
+        harmonics
+    });
+
+    if options.power_spectrum.is_some() || options.autocorrelation.is_some() || options.spectrum_plot {
+        let power = analysis::power_spectrum(&analysis::fourier(&profile, profile.len() / 2));
+
+        let autocorrelation = analysis::autocorrelation(&profile);
+
+        if let Some((lag, correlation)) = analysis::autocorrelation_peak(&autocorrelation) {
+            eprintln!("autocorrelation: strongest peak {} at lag {}", correlation, lag);
+        }
+
+        if let Some(path) = &options.power_spectrum {
+            export::write_series(path, ("order", "power"), &power, options.precision).unwrap();
+        }
+
+        if let Some(path) = &options.autocorrelation {
+            export::write_series(path, ("lag", "autocorrelation"), &autocorrelation, options.precision).unwrap();
+        }
+
+        if options.spectrum_plot {
+            plot::plot_spectrum(&power, &autocorrelation).unwrap();
+        }
+    }
+
+    let area = options.area.then(|| {
+        let mut area = analysis::polar_area(&profile, crossings.as_deref().unwrap_or_default());
+
+        area.pixels = pixel_area;
+
+        eprintln!("area: {} from r(θ), {} from every membership interval", area.star, area.intervals);
+
+        if let Some(pixels) = area.pixels {
+            eprintln!("area: {} from counting interior pixels", pixels);
+        }
+
+        eprintln!(
+            "published Mandelbrot area: {}; r(θ) misses {:.2}% of the interval estimate by assuming the set is star-shaped",
+            analysis::PUBLISHED_AREA,
+            (1.0 - area.star / area.intervals) * 100.0
+        );
+
+        area
+    });
+
+    let monte_carlo = options.monte_carlo.map(|samples| {
+        let mut random = options.seed.map_or_else(random::Random::from_time, random::Random::new);
+
+        let monte_carlo = analysis::monte_carlo_area(fractal.as_ref(), viewport::bounds(), samples, &mut random);
+
+        let (low, high) = monte_carlo.confidence_interval();
+
+        eprintln!(
+            "area: {} ± {} from {} random samples (95% confidence interval {} to {})",
+            monte_carlo.estimate,
+            monte_carlo.standard_error,
+            samples,
+            low,
+            high
+        );
+
+        monte_carlo
+    });
+
+    let arc_length = options.arc_length.then(|| {
+        let arc_length = analysis::ArcLength {
+            polar: analysis::polar_arc_length(&profile),
+            pixels: pixel_arc_length
+        };
+
+        eprintln!("arc length: {} from r(θ)", arc_length.polar);
+
+        if let Some(pixels) = arc_length.pixels {
+            eprintln!("arc length: {} along the traced pixel boundary", pixels);
+        }
+
+        arc_length
+    });
+
+    let fit = options.fit.map(|model| {
+        let fit = fit::fit(model, &profile, options.polar_origin);
+
+        eprintln!(
+            "{} fit: nucleus {}, scale {} and rotation {} relative to the main cardioid, rms residual {} after {} iterations",
+            match model {
+                fit::Model::Cardioid => "cardioid",
+                fit::Model::CardioidCircle => "cardioid and circle"
+            },
+            fit.nucleus,
+            fit.scale.norm(),
+            fit.scale.arg(),
+            fit.rms,
+            fit.iterations
+        );
+
+        fit
+    });
+
+    let symmetry = options.symmetry.then(|| {
+        let resolution = match (options.refine, options.command) {
+            (Some(tolerance), _) => tolerance,
+            (None, Command::Render) => viewport::pixel_size().0.max(viewport::pixel_size().1),
+            (None, _) => options.ray_step
+        };
+
+        let symmetry = analysis::symmetry(&profile, options.domain, resolution);
+
+        if symmetry.compared == 0 {
+            eprintln!("symmetry: no sample has its mirror image across the real axis in the sampled range");
+
+            return symmetry;
+        }
+
+        eprintln!(
+            "symmetry: max |r(θ) − r(−θ)| {} at θ = {}, rms {} over {} samples",
+            symmetry.max,
+            symmetry.theta_max,
+            symmetry.rms,
+            symmetry.compared
+        );
+
+        eprintln!(
+            "symmetry: {} samples differ from their mirror image by more than the resolution of {}",
+            symmetry.exceeding,
+            symmetry.resolution
+        );
+
+        symmetry
+    });
+
+    if let Some(path) = &options.polar_json {
+        let document = export::polar_document(export::PolarReport {
+            parameters: parameters(&options, limit, statistics.as_ref()),
+            profile: &profile,
+            summary: &summary,
+            crossings: crossings.as_deref().unwrap_or_default(),
+            harmonics: harmonics.as_deref(),
+            area: area.as_ref(),
+            monte_carlo: monte_carlo.as_ref(),
+            arc_length: arc_length.as_ref(),
+            symmetry: symmetry.as_ref(),
+            render: statistics.as_ref()
+        });
+
+        export::write_json(path, &document).unwrap();
+    }
+
+    let mut smoothed = match options.smooth {
+        Some(smoothing) => smoothing.apply(&profile),
+        None => profile.clone()
+    };
+
+    if let Some(size) = options.interpolate {
+        smoothed = smoothing::interpolate(&smoothed, size);
+    }
+
+    if let Some(path) = &options.wav {
+        if let Err(error) = export::write_wav(path, &smoothed, options.sample_rate, options.loops) {
+            eprintln!("wav: {}: {}", path.display(), error);
+
+            std::process::exit(1);
+        }
+
+        let seconds = (smoothed.len() as u64 * options.loops as u64) as f64 / options.sample_rate as f64;
+
+        let pitch = options.sample_rate as f64 / smoothed.len() as f64;
+
+        eprintln!("wav: {:.2} s at {:.1} Hz to {}", seconds, pitch, path.display());
+    }
+
+    let caption = area.map(|area| {
+        let mut caption = format!("area ≈ {:.4} (r(θ)), {:.4} (all intervals)", area.star, area.intervals);
+
+        if let Some(pixels) = area.pixels {
+            caption += &format!(", {:.4} (pixels)", pixels);
+        }
+
+        caption + &format!("; published {:.4}", analysis::PUBLISHED_AREA)
+    });
+
+    let derivative = options.derivative.then(|| analysis::derivative(&smoothed));
+
+    let curvature = options.curvature.then(|| analysis::curvature(&smoothed));
+
+    let analytic = options.cardioid.then(|| {
+        smoothed
+            .iter()
+            .map(|&(theta, _)| (theta, analysis::analytic_radius(ray(theta))))
+            .collect::<Vec<_>>()
+    });
+
+    plot::plot_polar(
+        &plot::PolarPlot {
+            profile: &smoothed,
+            raw: options.plot_raw.then_some(profile.as_slice()),
+            derivative: derivative.as_deref(),
+            curvature: curvature.as_deref(),
+            residual: fit.as_ref().filter(|_| options.fit_residual).map(|fit| fit.residuals.as_slice()),
+            analytic: analytic.as_deref(),
+            caption: caption.as_deref(),
+            style: Some(&options.plot_style),
+            domain: options.domain
+        },
+        &options.plot
+    )
+    .unwrap();
+
+    if let Some(protocol) = options.inline {
+        if options.command == Command::Render {
+            terminal::display(Path::new("output_set.png"), protocol).unwrap();
+        }
+
+        display_plot(&options.plot, protocol);
+    }
+
+    plot::plot_polar_curve(&plot::PolarCurve {
+        profile: &smoothed,
+        analytic: analytic.as_deref(),
+        components: components.as_deref(),
+        origin: options.polar_origin,
+        domain: options.domain
+    })
+    .unwrap();
+
+    let set = (options.command == Command::Render).then_some(Path::new("output_set.png"));
+
+    let images = set.into_iter().chain([options.plot.as_path(), Path::new("output_polar.png")]);
+
+    embed_parameters(&options, limit, statistics.as_ref(), images.clone());
+
+    if let Some(deficiency) = options.simulate_cvd {
+        // SVG plots aren't bitmaps to simulate.
+        for path in images.filter(|path| path.extension().is_none_or(|extension| extension != "svg")) {
+            let output = cvd::simulate_file(path, deficiency).unwrap_or_else(|error| {
+                eprintln!("simulate-cvd: {}: {}", path.display(), error);
+
+                std::process::exit(1);
+            });
+
+            eprintln!("simulate-cvd: {} as seen with {} in {}", path.display(), deficiency.name(), output.display());
+        }
+    }
 }
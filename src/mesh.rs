@@ -0,0 +1,155 @@
+// Triangulates the continuous escape values of a render into a heightfield mesh for Blender or a 3D
+// printer. The surface is closed into a solid, with walls around the edge and a flat base, so that
+// slicers take it as is. Pixels are a unit apart, rows running up from the bottom of the image, and
+// the height of each is the log of one plus its escape value, so that the slow climb towards the
+// boundary doesn't dwarf everything else; members stand at the limit's.
+
+use crate::render::IterationBuffer;
+
+pub const SCALE: f32 = 50.0;
+pub const STEP: u32 = 4;
+
+pub struct Mesh {
+    pub vertices: Vec<[f32; 3]>,
+    pub triangles: Vec<[u32; 3]>
+}
+
+fn normal(a: [f32; 3], b: [f32; 3], c: [f32; 3]) -> [f32; 3] {
+    let (u, v) = ([b[0] - a[0], b[1] - a[1], b[2] - a[2]], [c[0] - a[0], c[1] - a[1], c[2] - a[2]]);
+
+    [u[1] * v[2] - u[2] * v[1], u[2] * v[0] - u[0] * v[2], u[0] * v[1] - u[1] * v[0]]
+}
+
+impl Mesh {
+    // The triangle's unit normal, by the right-hand rule, counterclockwise seen from outside.
+    pub fn normal(&self, [a, b, c]: [u32; 3]) -> [f32; 3] {
+        let n = normal(self.vertices[a as usize], self.vertices[b as usize], self.vertices[c as usize]);
+
+        let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+
+        if length > 0.0 { n.map(|component| component / length) } else { n }
+    }
+
+    // Adds the triangle wound to face the given direction.
+    fn push(&mut self, [a, b, c]: [u32; 3], outward: [f32; 3]) {
+        let n = normal(self.vertices[a as usize], self.vertices[b as usize], self.vertices[c as usize]);
+
+        let facing = n[0] * outward[0] + n[1] * outward[1] + n[2] * outward[2];
+
+        self.triangles.push(if facing < 0.0 { [a, c, b] } else { [a, b, c] });
+    }
+}
+
+// Every `step`th pixel along one side, and the last one so that the mesh covers it all.
+fn samples(length: u32, step: u32) -> Vec<u32> {
+    let mut samples = (0..length).step_by(step as usize).collect::<Vec<_>>();
+
+    if samples.last() != Some(&(length - 1)) {
+        samples.push(length - 1);
+    }
+
+    samples
+}
+
+// The heightfield of every `step`th pixel each way, `scale` units high per unit of log, on a base
+// one unit of log thick. The buffer has to keep continuous values.
+pub fn heightfield(iterations: &IterationBuffer, scale: f32, step: u32) -> Mesh {
+    let (xs, ys) = (samples(iterations.width(), step), samples(iterations.height(), step));
+
+    let (columns, rows) = (xs.len() as u32, ys.len() as u32);
+
+    let bottom = (iterations.height() - 1) as f32;
+
+    let mut mesh = Mesh { vertices: Vec::new(), triangles: Vec::new() };
+
+    for &y in &ys {
+        for &x in &xs {
+            let value = iterations.smooth(x, y).expect("the buffer keeps the continuous values").max(0.0);
+
+            mesh.vertices.push([x as f32, bottom - y as f32, scale * value.ln_1p()]);
+        }
+    }
+
+    let top = |column: u32, row: u32| row * columns + column;
+
+    // The surface, two triangles to a cell.
+    for row in 0..rows.saturating_sub(1) {
+        for column in 0..columns.saturating_sub(1) {
+            let (a, b) = (top(column, row), top(column + 1, row));
+            let (c, d) = (top(column, row + 1), top(column + 1, row + 1));
+
+            mesh.push([a, b, d], [0.0, 0.0, 1.0]);
+            mesh.push([a, d, c], [0.0, 0.0, 1.0]);
+        }
+    }
+
+    if columns < 2 || rows < 2 {
+        return mesh;
+    }
+
+    // The edge of the surface all the way around, each side with the direction it faces.
+    let sides = [
+        ((0..columns).map(|column| top(column, 0)).collect::<Vec<_>>(), [0.0, 1.0, 0.0]),
+        ((0..rows).map(|row| top(columns - 1, row)).collect(), [1.0, 0.0, 0.0]),
+        ((0..columns).map(|column| top(column, rows - 1)).collect(), [0.0, -1.0, 0.0]),
+        ((0..rows).map(|row| top(0, row)).collect(), [-1.0, 0.0, 0.0])
+    ];
+
+    // A vertex of the base under each vertex of the edge, which the walls join.
+    let mut base = std::collections::HashMap::new();
+
+    for (side, outward) in &sides {
+        let below = side
+            .iter()
+            .map(|&vertex| {
+                *base.entry(vertex).or_insert_with(|| {
+                    let [x, y, _] = mesh.vertices[vertex as usize];
+
+                    mesh.vertices.push([x, y, -scale]);
+
+                    mesh.vertices.len() as u32 - 1
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for i in 0..side.len() - 1 {
+            mesh.push([side[i], side[i + 1], below[i + 1]], *outward);
+            mesh.push([side[i], below[i + 1], below[i]], *outward);
+        }
+    }
+
+    // The base, in strips between neighboring columns, each running from the vertices of the edge
+    // along one column to those along the next. The first and last columns have one at every row,
+    // the others at the top and bottom only, and zipping the two up keeps every triangle's
+    // corners on both columns, so none is degenerate and none leaves a vertex on another's edge.
+    let column = |column: u32| match column {
+        0 => (0..rows).map(|row| base[&top(0, row)]).collect::<Vec<_>>(),
+        column if column == columns - 1 => (0..rows).map(|row| base[&top(column, row)]).collect(),
+        column => vec![base[&top(column, 0)], base[&top(column, rows - 1)]]
+    };
+
+    for i in 0..columns - 1 {
+        let (left, right) = (column(i), column(i + 1));
+
+        let (mut l, mut r) = (0, 0);
+
+        while l + 1 < left.len() || r + 1 < right.len() {
+            let y = |vertex: u32| mesh.vertices[vertex as usize][1];
+
+            // Advance along whichever column's next vertex comes first going down.
+            let advance_left = r + 1 == right.len() || (l + 1 < left.len() && y(left[l + 1]) >= y(right[r + 1]));
+
+            if advance_left {
+                mesh.push([left[l], left[l + 1], right[r]], [0.0, 0.0, -1.0]);
+
+                l += 1;
+            } else {
+                mesh.push([left[l], right[r + 1], right[r]], [0.0, 0.0, -1.0]);
+
+                r += 1;
+            }
+        }
+    }
+
+    mesh
+}
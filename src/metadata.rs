@@ -0,0 +1,113 @@
+// Records how an image was made in its own PNG text chunks, so that a saved render carries its
+// provenance wherever it's copied. The chunks go just before the pixel data, where readers find
+// them without decoding the image, so the file is copied around them a chunk at a time rather than
+// decoded and encoded again, which keeps even a poster too large to hold in memory cheap to tag.
+
+use png::text_metadata::{EncodableTextChunk, ITXtChunk, TEXtChunk, ZTXtChunk};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+// The keyword of the chunk holding the parameters as JSON.
+pub const PARAMETERS: &str = "polar_mandelbrot";
+
+// Text any longer than this is compressed.
+const COMPRESS_OVER: usize = 256;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+// tEXt and zTXt only hold Latin-1, so anything else, like a path in another script, goes in iTXt.
+fn encode(keyword: &str, text: &str, output: &mut impl Write) -> io::Result<()> {
+    let compress = text.len() > COMPRESS_OVER;
+
+    let encoded = if text.chars().any(|ch| ch as u32 > 0xff) {
+        let mut chunk = ITXtChunk::new(keyword, text);
+
+        chunk.compressed = compress;
+
+        chunk.encode(output)
+    } else if compress {
+        ZTXtChunk::new(keyword, text).encode(output)
+    } else {
+        TEXtChunk::new(keyword, text).encode(output)
+    };
+
+    encoded.map_err(io::Error::other)
+}
+
+// Adds a text chunk for each keyword and text to the PNG at the path.
+pub fn embed(path: &Path, entries: &[(&str, String)]) -> io::Result<()> {
+    let mut input = BufReader::new(File::open(path)?);
+
+    let mut signature = [0; 8];
+
+    input.read_exact(&mut signature)?;
+
+    if signature != SIGNATURE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a PNG"));
+    }
+
+    let partial = path.with_extension("partial.png");
+
+    let mut output = BufWriter::new(File::create(&partial)?);
+
+    output.write_all(&SIGNATURE)?;
+
+    let mut embedded = false;
+
+    loop {
+        // The length of the data and the type, then the data and its CRC.
+        let mut header = [0; 8];
+
+        input.read_exact(&mut header)?;
+
+        let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+
+        let kind = &header[4..];
+
+        if kind == b"IDAT" && !embedded {
+            for (keyword, text) in entries {
+                encode(keyword, text, &mut output)?;
+            }
+
+            embedded = true;
+        }
+
+        output.write_all(&header)?;
+
+        io::copy(&mut (&mut input).take(length as u64 + 4), &mut output)?;
+
+        if kind == b"IEND" {
+            break;
+        }
+    }
+
+    output.flush()?;
+
+    drop(output);
+
+    std::fs::rename(&partial, path)
+}
+
+// The keywords and texts of every text chunk ahead of the pixel data in the PNG at the path.
+pub fn read(path: &Path) -> Result<Vec<(String, String)>, Box<dyn std::error::Error>> {
+    let reader = png::Decoder::new(BufReader::new(File::open(path)?)).read_info()?;
+
+    let info = reader.info();
+
+    let mut entries = info
+        .uncompressed_latin1_text
+        .iter()
+        .map(|chunk| (chunk.keyword.clone(), chunk.text.clone()))
+        .collect::<Vec<_>>();
+
+    for chunk in &info.compressed_latin1_text {
+        entries.push((chunk.keyword.clone(), chunk.get_text()?));
+    }
+
+    for chunk in &info.utf8_text {
+        entries.push((chunk.keyword.clone(), chunk.get_text()?));
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,90 @@
+// Counters and histograms the server exposes at /metrics in Prometheus's text format, updated with
+// atomics so requests never wait on each other to record them.
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+// Upper bounds of the histogram buckets in seconds, from a small tile up to a large job.
+const BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0, 30.0];
+
+#[derive(Default)]
+pub struct Histogram {
+    // Observations no longer than each bound, and past the last.
+    counts: [AtomicU64; BUCKETS.len() + 1],
+    sum_micros: AtomicU64
+}
+
+impl Histogram {
+    pub fn observe(&self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+
+        let bucket = BUCKETS.iter().position(|&bound| seconds <= bound).unwrap_or(BUCKETS.len());
+
+        self.counts[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    // Prometheus buckets are cumulative, each counting every observation up to its bound.
+    fn write(&self, text: &mut String, name: &str, help: &str) {
+        writeln!(text, "# HELP {} {}\n# TYPE {} histogram", name, help, name).unwrap();
+
+        let mut count = 0;
+
+        for (i, bucket) in self.counts.iter().enumerate() {
+            count += bucket.load(Ordering::Relaxed);
+
+            let bound = BUCKETS.get(i).map_or("+Inf".to_string(), |bound| bound.to_string());
+
+            writeln!(text, "{}_bucket{{le=\"{}\"}} {}", name, bound, count).unwrap();
+        }
+
+        let sum = self.sum_micros.load(Ordering::Relaxed) as f64 / 1e6;
+
+        writeln!(text, "{}_sum {}\n{}_count {}", name, sum, name, count).unwrap();
+    }
+}
+
+#[derive(Default)]
+pub struct Metrics {
+    pub tiles_served: AtomicU64,
+    pub cache_hits: AtomicU64,
+    pub cache_misses: AtomicU64,
+    pub tile_seconds: Histogram,
+    pub job_seconds: Histogram
+}
+
+fn write_metric(text: &mut String, name: &str, kind: &str, help: &str, value: f64) {
+    writeln!(text, "# HELP {} {}\n# TYPE {} {}\n{} {}", name, help, name, kind, name, value).unwrap();
+}
+
+impl Metrics {
+    // Gauges are passed in, since they're read from the server's state rather than counted.
+    pub fn text(&self, cached_tiles: usize, jobs_in_flight: usize) -> String {
+        let mut text = String::new();
+
+        let counter = |counter: &AtomicU64| counter.load(Ordering::Relaxed) as f64;
+
+        let (hits, misses) = (counter(&self.cache_hits), counter(&self.cache_misses));
+
+        let hit_ratio = if hits + misses > 0.0 { hits / (hits + misses) } else { 0.0 };
+
+        let metrics = [
+            ("tiles_served_total", "counter", "Tiles served.", counter(&self.tiles_served)),
+            ("tile_cache_hits_total", "counter", "Tiles served from the cache.", hits),
+            ("tile_cache_misses_total", "counter", "Tiles rendered on demand.", misses),
+            ("tile_cache_hit_ratio", "gauge", "Fraction of tiles served from the cache.", hit_ratio),
+            ("tile_cache_entries", "gauge", "Tiles in the cache.", cached_tiles as f64),
+            ("jobs_in_flight", "gauge", "Jobs still running.", jobs_in_flight as f64)
+        ];
+
+        for (name, kind, help, value) in metrics {
+            write_metric(&mut text, &format!("polar_mandelbrot_{}", name), kind, help, value);
+        }
+
+        self.tile_seconds.write(&mut text, "polar_mandelbrot_tile_render_seconds", "Time to render a tile.");
+        self.job_seconds.write(&mut text, "polar_mandelbrot_job_seconds", "Time from a job's start to its end.");
+
+        text
+    }
+}
@@ -0,0 +1,97 @@
+// Locates Misiurewicz points of the Mandelbrot set, the parameters c where the orbit of 0 isn't
+// periodic itself but lands on a cycle after finitely many steps. They lie on the boundary, at the
+// tips of filaments and where branches meet, and the set looks much the same at every depth around
+// them.
+
+use num::complex::Complex;
+
+const NEWTON_STEPS: u32 = 64;
+const TOLERANCE: f64 = 1e-14;
+
+// How close orbit points have to be to count as the same when checking what a point converged to.
+const MATCH: f64 = 1e-8;
+
+pub struct Misiurewicz {
+    pub c: Complex<f64>,
+    pub preperiod: u32,
+    pub period: u32,
+    // The product of 2z around the cycle: how fast orbits near it are pushed away from it, and how
+    // the set around c rotates and scales from one copy to the next.
+    pub multiplier: Complex<f64>
+}
+
+// The orbit of 0 up to zₙ, with each point's derivative with respect to c.
+fn orbit(c: Complex<f64>, n: u32) -> Vec<(Complex<f64>, Complex<f64>)> {
+    let mut z = Complex::new(0.0, 0.0);
+    let mut dc = Complex::new(0.0, 0.0);
+
+    let mut orbit = vec![(z, dc)];
+
+    for _ in 0..n {
+        dc = 2.0 * z * dc + 1.0;
+        z = z * z + c;
+
+        orbit.push((z, dc));
+    }
+
+    orbit
+}
+
+// The preperiod and period that c actually has, if its orbit reaches a cycle within `steps`.
+pub fn classify(c: Complex<f64>, steps: u32) -> Option<(u32, u32)> {
+    let orbit = orbit(c, steps);
+
+    let same = |a: usize, b: usize| (orbit[a].0 - orbit[b].0).norm() < MATCH;
+
+    (0..steps as usize).find_map(|preperiod| {
+        (1..=steps as usize - preperiod)
+            .find(|&period| same(preperiod + period, preperiod))
+            .map(|period| (preperiod as u32, period as u32))
+    })
+}
+
+// Newton's method from the seed on z_{p+q} − z_p = 0, divided by z_{i+q} − z_i for every i < p and
+// by z_{p+d} − z_p for every proper divisor d of q, which vanish at the points of smaller preperiod
+// or period that solve it too, so that it doesn't converge to those. The result is checked anyway,
+// and None returned if it has another preperiod or period. The preperiod has to be at least 2:
+// z_{q+1} = z_1 means z_q = 0, a center.
+pub fn locate(preperiod: u32, period: u32, seed: Complex<f64>) -> Option<Misiurewicz> {
+    let (p, q) = (preperiod as usize, period as usize);
+
+    let mut c = seed;
+
+    for _ in 0..NEWTON_STEPS {
+        let orbit = orbit(c, preperiod + period);
+
+        // Exactly on the point, the step would divide by zero.
+        if orbit[p + q].0 == orbit[p].0 {
+            break;
+        }
+
+        // The derivative of log(z_a − z_b).
+        let log_derivative = |a: usize, b: usize| (orbit[a].1 - orbit[b].1) / (orbit[a].0 - orbit[b].0);
+
+        let lower = (0..p).map(|i| log_derivative(i + q, i));
+        let divisors = (1..q).filter(|&d| q.is_multiple_of(d)).map(|d| log_derivative(p + d, p));
+
+        let step = 1.0 / lower.chain(divisors).fold(log_derivative(p + q, p), |sum, term| sum - term);
+
+        if !step.re.is_finite() || !step.im.is_finite() {
+            return None;
+        }
+
+        c -= step;
+
+        if step.norm() < TOLERANCE * c.norm().max(1.0) {
+            break;
+        }
+    }
+
+    if classify(c, preperiod + period) != Some((preperiod, period)) {
+        return None;
+    }
+
+    let multiplier = orbit(c, preperiod + period)[p..p + q].iter().map(|&(z, _)| 2.0 * z).product();
+
+    Some(Misiurewicz { c, preperiod, period, multiplier })
+}
@@ -0,0 +1,52 @@
+// Display hooks for the evcxr Jupyter kernel, which shows any value with an `evcxr_display` method
+// by the content it prints between its markers. Wrapping a rendered image or a polar plot in an
+// `Image` as the last expression of a cell shows it inline:
+//
+//     :dep polar_mandelbrot = { path = "...", features = ["evcxr"] }
+//
+//     let image = render::render_view(fractal, colorer, (640, 360), View::default(), 1).await;
+//
+//     notebook::Image(image)
+
+use crate::plot::{self, PolarPlot};
+use crate::polar::Domain;
+use crate::terminal;
+use image::{DynamicImage, ImageOutputFormat, RgbImage};
+use std::io::Cursor;
+
+pub struct Image(pub RgbImage);
+
+impl From<RgbImage> for Image {
+    fn from(image: RgbImage) -> Self {
+        Self(image)
+    }
+}
+
+impl Image {
+    // The r(θ) plot of a profile over the full circle, as analyze draws it.
+    pub fn polar(profile: &[(f32, f32)]) -> Result<Self, Box<dyn std::error::Error>> {
+        let plot = PolarPlot {
+            profile,
+            raw: None,
+            derivative: None,
+            curvature: None,
+            residual: None,
+            analytic: None,
+            caption: None,
+            style: None,
+            domain: Domain::Full
+        };
+
+        Ok(Self(plot::plot_polar_image(&plot)?))
+    }
+
+    pub fn evcxr_display(&self) {
+        let mut png = Vec::new();
+
+        DynamicImage::ImageRgb8(self.0.clone())
+            .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+            .expect("encoding into memory can't fail");
+
+        println!("EVCXR_BEGIN_CONTENT image/png\n{}\nEVCXR_END_CONTENT", terminal::base64(&png));
+    }
+}
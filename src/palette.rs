@@ -0,0 +1,83 @@
+// Lists of colors that escape counts cycle through, read from Fractint's MAP format: one color per
+// line as red, green, and blue from 0 to 255, separated by whitespace, with anything after them on
+// the line ignored as a comment. A few built-in themes are here too, for --theme.
+
+use image::Rgb;
+use std::path::Path;
+
+// The built-in themes by name, each a cycle of colors that wraps from the last back to the first.
+const THEMES: &[(&str, &[[u8; 3]])] = &[
+    ("classic", &[[0, 7, 100], [32, 107, 203], [237, 255, 255], [255, 170, 0], [0, 2, 0]]),
+    ("grayscale", &[[0, 0, 0], [255, 255, 255]]),
+    ("fire", &[[0, 0, 0], [128, 0, 0], [255, 64, 0], [255, 192, 0], [255, 255, 192]]),
+    ("viridis", &[[68, 1, 84], [59, 82, 139], [33, 145, 140], [94, 201, 98], [253, 231, 37]]),
+    // Safe for color-blind viewers: cividis runs from blue to yellow, which every common color vision
+    // deficiency keeps apart, and okabe-ito is Okabe and Ito's palette of colors that stay distinct.
+    ("cividis", &[[0, 34, 78], [62, 75, 108], [124, 123, 120], [187, 175, 113], [254, 232, 56]]),
+    (
+        "okabe-ito",
+        &[[0, 114, 178], [86, 180, 233], [0, 158, 115], [240, 228, 66], [230, 159, 0], [213, 94, 0], [204, 121, 167]]
+    )
+];
+
+// The names of the built-in themes, in the order they're listed.
+pub fn themes() -> impl Iterator<Item = &'static str> {
+    THEMES.iter().map(|&(name, _)| name)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    pub colors: Vec<Rgb<u8>>
+}
+
+impl Palette {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut colors = Vec::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let fields = line.split_whitespace().take(3).map(str::parse::<u8>).collect::<Vec<_>>();
+
+            match fields[..] {
+                [] => continue,
+                [Ok(red), Ok(green), Ok(blue)] => colors.push(Rgb([red, green, blue])),
+                _ => return Err(format!("line {}: expected red, green, and blue from 0 to 255", i + 1))
+            }
+        }
+
+        if colors.is_empty() {
+            return Err("a palette needs at least one color".to_string());
+        }
+
+        Ok(Self { colors })
+    }
+
+    // The built-in theme with the name, if there is one.
+    pub fn theme(name: &str) -> Option<Self> {
+        THEMES.iter().find(|&&(theme, _)| theme == name).map(|&(_, colors)| Self {
+            colors: colors.iter().map(|&color| Rgb(color)).collect()
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?)?)
+    }
+
+    // The color n steps into the palette, wrapping around at the end.
+    pub fn cycle(&self, n: u32) -> Rgb<u8> {
+        self.colors[n as usize % self.colors.len()]
+    }
+
+    // The color t turns around the palette, blending linearly between neighboring colors.
+    pub fn at(&self, t: f32) -> Rgb<u8> {
+        let position = t.rem_euclid(1.0) * self.colors.len() as f32;
+
+        let index = (position as usize).min(self.colors.len() - 1);
+        let fraction = position - index as f32;
+
+        let (from, to) = (self.colors[index], self.colors[(index + 1) % self.colors.len()]);
+
+        Rgb([0, 1, 2].map(|channel| {
+            (from[channel] as f32 + (to[channel] as f32 - from[channel] as f32) * fraction).round() as u8
+        }))
+    }
+}
@@ -0,0 +1,1010 @@
+use crate::analysis::{reconstruct, Harmonic};
+use crate::components::{Component, Nucleus};
+use crate::polar::{Domain, MAX_RADIUS};
+use crate::external::Angle;
+use crate::viewport::{bounds, complex_to_coordinate, complex_to_pixel, View, IMG_HEIGHT, IMG_WIDTH};
+use image::RgbImage;
+use num::complex::Complex;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::path::Path;
+use std::str::FromStr;
+
+// What the θ axis of the r(θ) plot is labeled in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ThetaUnits {
+    #[default]
+    Radians,
+    Degrees,
+    Pi
+}
+
+impl FromStr for ThetaUnits {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "radians" => Ok(ThetaUnits::Radians),
+            "degrees" => Ok(ThetaUnits::Degrees),
+            "pi" => Ok(ThetaUnits::Pi),
+            _ => Err(format!("unknown θ units `{}`; expected radians, degrees, or pi", s))
+        }
+    }
+}
+
+impl ThetaUnits {
+    // The chart is built in these units rather than relabeling radians, so the ticks land on round
+    // values like 45° or 0.25π.
+    fn per_radian(self) -> f32 {
+        match self {
+            ThetaUnits::Radians => 1.0,
+            ThetaUnits::Degrees => 180.0 / std::f32::consts::PI,
+            ThetaUnits::Pi => 1.0 / std::f32::consts::PI
+        }
+    }
+
+    fn label(self, value: f32) -> String {
+        let number = format!("{:.3}", value).trim_end_matches('0').trim_end_matches('.').to_string();
+
+        match (self, number.as_str()) {
+            (ThetaUnits::Radians, _) => number,
+            (ThetaUnits::Degrees, _) => format!("{}°", number),
+            (ThetaUnits::Pi, "0" | "-0") => "0".to_string(),
+            (ThetaUnits::Pi, "1") => "π".to_string(),
+            (ThetaUnits::Pi, "-1") => "-π".to_string(),
+            (ThetaUnits::Pi, _) => format!("{}π", number)
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PlotStyle {
+    pub title: Option<String>,
+    pub x_label: Option<String>,
+    pub y_label: Option<String>,
+    pub size: (u32, u32),
+    // Fonts, margins, and lines are drawn at 96 DPI and scaled from there.
+    pub dpi: u32,
+    pub color: RGBColor,
+    pub line_width: u32,
+    pub y_range: Option<(f32, f32)>,
+    pub theta_units: ThetaUnits,
+    // The number of labeled grid lines along each axis.
+    pub grid: usize
+}
+
+impl Default for PlotStyle {
+    fn default() -> Self {
+        Self {
+            title: None,
+            x_label: None,
+            y_label: None,
+            size: (1280, 960),
+            dpi: 96,
+            color: RED,
+            line_width: 1,
+            y_range: None,
+            theta_units: ThetaUnits::default(),
+            grid: 10
+        }
+    }
+}
+
+impl PlotStyle {
+    fn scaled(&self, length: u32) -> u32 {
+        (length * self.dpi).div_ceil(96)
+    }
+}
+
+// Accepts a few color names or a hex triplet like #ff8000.
+pub fn parse_color(color: &str) -> Result<RGBColor, String> {
+    match color {
+        "red" => return Ok(RED),
+        "green" => return Ok(GREEN),
+        "blue" => return Ok(BLUE),
+        "black" => return Ok(BLACK),
+        _ => {}
+    }
+
+    let hex = color.strip_prefix('#').unwrap_or(color);
+
+    let channel = |i: usize| hex.get(i..i + 2).and_then(|channel| u8::from_str_radix(channel, 16).ok());
+
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(red), Some(green), Some(blue)) => Ok(RGBColor(red, green, blue)),
+        _ => Err(format!("invalid color `{}`; expected a name or a hex triplet like #ff8000", color))
+    }
+}
+
+#[derive(Default)]
+pub struct PolarPlot<'a> {
+    pub profile: &'a [(f32, f32)],
+    pub raw: Option<&'a [(f32, f32)]>,
+    pub derivative: Option<&'a [(f32, f32)]>,
+    pub curvature: Option<&'a [(f32, f32)]>,
+    pub residual: Option<&'a [(f32, f32)]>,
+    pub analytic: Option<&'a [(f32, f32)]>,
+    pub caption: Option<&'a str>,
+    pub style: Option<&'a PlotStyle>,
+    pub domain: Domain
+}
+
+// Writes SVG when the path ends in .svg, and a bitmap otherwise.
+pub fn plot_polar(plot: &PolarPlot, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let size = plot.style.map_or(PlotStyle::default().size, |style| style.size);
+
+    if path.extension().is_some_and(|extension| extension == "svg") {
+        draw_polar(SVGBackend::new(path, size).into_drawing_area(), plot)
+    } else {
+        draw_polar(BitMapBackend::new(path, size).into_drawing_area(), plot)
+    }
+}
+
+// Draws the same plot into an image instead of a file, for compositing onto animation frames.
+pub fn plot_polar_image(plot: &PolarPlot) -> Result<RgbImage, Box<dyn std::error::Error>> {
+    let (width, height) = plot.style.map_or(PlotStyle::default().size, |style| style.size);
+
+    let mut buffer = vec![0; width as usize * height as usize * 3];
+
+    draw_polar(BitMapBackend::with_buffer(&mut buffer, (width, height)).into_drawing_area(), plot)?;
+
+    Ok(RgbImage::from_raw(width, height, buffer).expect("the buffer matches the plot's size"))
+}
+
+fn draw_polar<'a, DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    plot: &PolarPlot<'a>
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static
+{
+    let default_style = PlotStyle::default();
+
+    let style = plot.style.unwrap_or(&default_style);
+
+    let units = style.theta_units;
+
+    let scale = units.per_radian();
+
+    let domain = {
+        let (start, end) = plot.domain.bounds();
+
+        start * scale..end * scale
+    };
+
+    let scaled = |samples: &'a [(f32, f32)]| samples.iter().map(move |&(theta, r)| (theta * scale, r));
+
+    let x_formatter = |x: &f32| units.label(*x);
+
+    root.fill(&WHITE)?;
+
+    // Each derived series gets its own chart below r(θ), since their scales have nothing in common.
+    let panels = [
+        (plot.derivative, "dr/dθ", BLUE),
+        (plot.curvature, "κ", RGBColor(160, 0, 160)),
+        (plot.residual, "fit residual", RGBColor(0, 128, 128))
+    ]
+    .into_iter()
+        .filter_map(|(samples, label, color)| samples.map(|samples| (samples, label, color)))
+        .collect::<Vec<_>>();
+
+    let (upper, lower) = match panels.len() {
+        0 => (root.clone(), Vec::new()),
+        count => {
+            let eighths = match count {
+                1 => 5,
+                2 => 4,
+                _ => 3
+            };
+
+            let (upper, lower) = root.split_vertically(style.size.1 * eighths / 8);
+
+            (upper, lower.split_evenly((count, 1)))
+        }
+    };
+
+    let mut builder = ChartBuilder::on(&upper);
+
+    let caption = match (&style.title, plot.caption) {
+        (Some(title), Some(caption)) => Some(format!("{} — {}", title, caption)),
+        (Some(title), None) => Some(title.clone()),
+        (None, caption) => caption.map(str::to_string)
+    };
+
+    if let Some(caption) = caption {
+        builder.caption(caption, ("sans-serif", style.scaled(20)));
+    }
+
+    let (y_min, y_max) = style.y_range.unwrap_or((0.0, MAX_RADIUS));
+
+    let mut chart = builder
+        .margin(style.scaled(5))
+        .x_label_area_size(style.scaled(30) + if style.x_label.is_some() { style.scaled(20) } else { 0 })
+        .y_label_area_size(style.scaled(30) + if style.y_label.is_some() { style.scaled(20) } else { 0 })
+        .build_cartesian_2d(domain.clone(), y_min..y_max)?;
+
+    let mut mesh = chart.configure_mesh();
+
+    mesh.x_labels(style.grid)
+        .y_labels(style.grid)
+        .label_style(("sans-serif", style.scaled(12)))
+        .axis_desc_style(("sans-serif", style.scaled(14)));
+
+    if units != ThetaUnits::Radians {
+        mesh.x_label_formatter(&x_formatter);
+    }
+
+    if let Some(label) = &style.x_label {
+        mesh.x_desc(label);
+    }
+
+    if let Some(label) = &style.y_label {
+        mesh.y_desc(label);
+    }
+
+    mesh.draw()?;
+
+    let width = style.scaled(style.line_width);
+
+    if let Some(raw) = plot.raw {
+        let gray = RGBColor(160, 160, 160);
+
+        chart
+            .draw_series(LineSeries::new(scaled(raw), gray.stroke_width(width)))?
+            .label("raw")
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], gray));
+    }
+
+    if let Some(analytic) = plot.analytic {
+        let green = RGBColor(0, 160, 0);
+
+        chart
+            .draw_series(LineSeries::new(scaled(analytic), green.stroke_width(width)))?
+            .label("main cardioid and period-2 bulb")
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], green));
+    }
+
+    let color = style.color;
+
+    chart
+        .draw_series(LineSeries::new(scaled(plot.profile), color.stroke_width(width)))?
+        .label(if plot.raw.is_some() { "smoothed" } else { "measured" })
+        .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color));
+
+    if plot.raw.is_some() || plot.analytic.is_some() {
+        chart
+            .configure_series_labels()
+            .label_font(("sans-serif", style.scaled(12)))
+            .background_style(WHITE)
+            .border_style(BLACK)
+            .draw()?;
+    }
+
+    for ((samples, label, color), area) in panels.into_iter().zip(&lower) {
+        let (min, max) = samples
+            .iter()
+            .fold((0.0f32, 0.0f32), |(min, max), &(_, value)| (min.min(value), max.max(value)));
+
+        let margin = ((max - min) * 0.05).max(f32::EPSILON);
+
+        let mut chart = ChartBuilder::on(area)
+            .margin(style.scaled(5))
+            .x_label_area_size(style.scaled(30))
+            .y_label_area_size(style.scaled(55))
+            .build_cartesian_2d(domain.clone(), min - margin..max + margin)?;
+
+        let mut mesh = chart.configure_mesh();
+
+        mesh.x_labels(style.grid)
+            .y_labels(style.grid)
+            .label_style(("sans-serif", style.scaled(12)))
+            .axis_desc_style(("sans-serif", style.scaled(14)))
+            .y_desc(label);
+
+        if units != ThetaUnits::Radians {
+            mesh.x_label_formatter(&x_formatter);
+        }
+
+        mesh.draw()?;
+
+        chart.draw_series(LineSeries::new(scaled(samples), color.stroke_width(width)))?;
+    }
+
+    root.present()?;
+
+    Ok(())
+}
+
+// Overlays several labeled profiles on one r(θ) chart, each in its own color, over the union of
+// their angles.
+pub fn plot_comparison(
+    profiles: &[(String, Vec<(f32, f32)>)],
+    style: &PlotStyle,
+    path: &Path
+) -> Result<(), Box<dyn std::error::Error>> {
+    if path.extension().is_some_and(|extension| extension == "svg") {
+        draw_comparison(SVGBackend::new(path, style.size).into_drawing_area(), profiles, style)
+    } else {
+        draw_comparison(BitMapBackend::new(path, style.size).into_drawing_area(), profiles, style)
+    }
+}
+
+fn draw_comparison<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    profiles: &[(String, Vec<(f32, f32)>)],
+    style: &PlotStyle
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    DB::ErrorType: 'static
+{
+    let units = style.theta_units;
+
+    let scale = units.per_radian();
+
+    let (start, end) = profiles
+        .iter()
+        .flat_map(|(_, profile)| profile)
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(start, end), &(theta, _)| (start.min(theta), end.max(theta)));
+
+    let (start, end) = if start < end { (start, end) } else { Domain::Full.bounds() };
+
+    let x_formatter = |x: &f32| units.label(*x);
+
+    root.fill(&WHITE)?;
+
+    let mut builder = ChartBuilder::on(&root);
+
+    if let Some(title) = &style.title {
+        builder.caption(title, ("sans-serif", style.scaled(20)));
+    }
+
+    let (y_min, y_max) = style.y_range.unwrap_or((0.0, MAX_RADIUS));
+
+    let mut chart = builder
+        .margin(style.scaled(5))
+        .x_label_area_size(style.scaled(30) + if style.x_label.is_some() { style.scaled(20) } else { 0 })
+        .y_label_area_size(style.scaled(30) + if style.y_label.is_some() { style.scaled(20) } else { 0 })
+        .build_cartesian_2d(start * scale..end * scale, y_min..y_max)?;
+
+    let mut mesh = chart.configure_mesh();
+
+    mesh.x_labels(style.grid)
+        .y_labels(style.grid)
+        .label_style(("sans-serif", style.scaled(12)))
+        .axis_desc_style(("sans-serif", style.scaled(14)));
+
+    if units != ThetaUnits::Radians {
+        mesh.x_label_formatter(&x_formatter);
+    }
+
+    if let Some(label) = &style.x_label {
+        mesh.x_desc(label);
+    }
+
+    if let Some(label) = &style.y_label {
+        mesh.y_desc(label);
+    }
+
+    mesh.draw()?;
+
+    let width = style.scaled(style.line_width);
+
+    for (i, (label, profile)) in profiles.iter().enumerate() {
+        let color = Palette99::pick(i).to_rgba();
+
+        chart
+            .draw_series(LineSeries::new(
+                profile.iter().map(|&(theta, r)| (theta * scale, r)),
+                color.stroke_width(width)
+            ))?
+            .label(label)
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color));
+    }
+
+    chart
+        .configure_series_labels()
+        .label_font(("sans-serif", style.scaled(12)))
+        .background_style(WHITE)
+        .border_style(BLACK)
+        .draw()?;
+
+    root.present()?;
+
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct PolarCurve<'a> {
+    pub profile: &'a [(f32, f32)],
+    pub analytic: Option<&'a [(f32, f32)]>,
+    pub components: Option<&'a [Component]>,
+    pub origin: Complex<f32>,
+    pub domain: Domain
+}
+
+pub fn plot_polar_curve<'a>(curve: &PolarCurve<'a>) -> Result<(), Box<dyn std::error::Error>> {
+    let origin = curve.origin;
+
+    let root = BitMapBackend::new("output_polar.png", (960, 960)).into_drawing_area();
+
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .margin(5)
+        .x_label_area_size(30)
+        .y_label_area_size(30)
+        .build_cartesian_2d(
+            origin.re - MAX_RADIUS..origin.re + MAX_RADIUS,
+            origin.im - MAX_RADIUS..origin.im + MAX_RADIUS
+        )?;
+
+    chart.configure_mesh().disable_mesh().draw()?;
+
+    let grid = RGBColor(220, 220, 220);
+
+    for i in 1..=4 {
+        let r = MAX_RADIUS * i as f32 / 4.0;
+
+        chart.draw_series(LineSeries::new(
+            (0..=360).map(|degrees| {
+                let point = origin + Complex::from_polar(r, (degrees as f32).to_radians());
+
+                (point.re, point.im)
+            }),
+
+            &grid
+        ))?;
+    }
+
+    for i in 0..12 {
+        let end = origin + Complex::from_polar(MAX_RADIUS, std::f32::consts::PI / 6.0 * i as f32);
+
+        chart.draw_series(LineSeries::new([(origin.re, origin.im), (end.re, end.im)], &grid))?;
+    }
+
+    let to_point = |&(theta, r): &(f32, f32)| {
+        let point = origin + Complex::from_polar(r, theta);
+
+        (point.re, point.im)
+    };
+
+    // Only a curve over the whole turn is closed back to its first sample.
+    let closed = |samples: &'a [(f32, f32)]| {
+        samples.iter().chain(samples.first().filter(|_| curve.domain == Domain::Full)).map(to_point)
+    };
+
+    if let Some(analytic) = curve.analytic {
+        chart.draw_series(LineSeries::new(closed(analytic), &RGBColor(0, 160, 0)))?;
+    }
+
+    chart.draw_series(LineSeries::new(closed(curve.profile), &RED))?;
+
+    for component in curve.components.unwrap_or_default() {
+        let color = Palette99::pick(component.period as usize);
+
+        for spoke in &component.spokes {
+            chart.draw_series(LineSeries::new(spoke.iter().map(|c| (c.re as f32, c.im as f32)), &color))?;
+        }
+
+        chart.draw_series([Circle::new(
+            (component.center.re as f32, component.center.im as f32),
+            3,
+            color.filled()
+        )])?;
+    }
+
+    chart.draw_series([Cross::new((origin.re, origin.im), 6, BLACK.stroke_width(2))])?;
+
+    root.present()?;
+
+    Ok(())
+}
+
+pub fn plot_external_rays(
+    image: &RgbImage,
+    rays: &[(Angle, Vec<Complex<f64>>)]
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut overlay = image.clone();
+
+    {
+        let root = BitMapBackend::with_buffer(&mut overlay, (IMG_WIDTH, IMG_HEIGHT)).into_drawing_area();
+
+        let to_pixel = |c: &Complex<f64>| {
+            let (x, y) = complex_to_pixel(Complex::new(c.re as f32, c.im as f32));
+
+            (x.round() as i32, y.round() as i32)
+        };
+
+        for (i, (_, ray)) in rays.iter().enumerate() {
+            let color = Palette99::pick(i).stroke_width(3);
+
+            // plotters clamps points outside the image to its edges, so only the runs of the ray inside
+            // it are drawn.
+            for run in ray.iter().map(to_pixel).collect::<Vec<_>>().split(|&(x, y)| {
+                x < 0 || y < 0 || x >= IMG_WIDTH as i32 || y >= IMG_HEIGHT as i32
+            }) {
+                root.draw(&PathElement::new(run.to_vec(), color))?;
+            }
+
+            if let Some(landing) = ray.last() {
+                root.draw(&Circle::new(to_pixel(landing), 8, color.filled()))?;
+            }
+        }
+
+        root.present()?;
+    }
+
+    overlay.save("output_external_rays.png")?;
+
+    Ok(())
+}
+
+// Connects the successive points of the orbit and marks each one, with a larger dot at c.
+pub fn plot_orbit(image: &RgbImage, c: Complex<f32>, orbit: &[Complex<f32>]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut overlay = image.clone();
+
+    {
+        let root = BitMapBackend::with_buffer(&mut overlay, (IMG_WIDTH, IMG_HEIGHT)).into_drawing_area();
+
+        let to_pixel = |c: &Complex<f32>| {
+            let (x, y) = complex_to_pixel(*c);
+
+            (x.round() as i32, y.round() as i32)
+        };
+
+        let color = RGBColor(255, 128, 0);
+
+        // As with external rays, only the runs of the orbit inside the image are drawn, since an
+        // escaping orbit's last steps land far outside it.
+        for run in orbit.iter().map(to_pixel).collect::<Vec<_>>().split(|&(x, y)| {
+            x < 0 || y < 0 || x >= IMG_WIDTH as i32 || y >= IMG_HEIGHT as i32
+        }) {
+            root.draw(&PathElement::new(run.to_vec(), color.stroke_width(2)))?;
+
+            for &point in run {
+                root.draw(&Circle::new(point, 3, color.filled()))?;
+            }
+        }
+
+        root.draw(&Circle::new(to_pixel(&c), 8, RED.filled()))?;
+
+        root.present()?;
+    }
+
+    overlay.save("output_orbit.png")?;
+
+    Ok(())
+}
+
+// Rings each point on a copy of an image of the view, such as a close-up of a point found by
+// Newton's method.
+pub fn plot_marks(
+    image: &RgbImage,
+    view: View,
+    points: &[Complex<f32>],
+    path: &Path
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut overlay = image.clone();
+
+    let size = overlay.dimensions();
+
+    {
+        let root = BitMapBackend::with_buffer(&mut overlay, size).into_drawing_area();
+
+        for &point in points {
+            let (x, y) = view.complex_to_pixel(size, point);
+
+            let pixel = (x.round() as i32, y.round() as i32);
+
+            root.draw(&Circle::new(pixel, 10, RED.stroke_width(2)))?;
+            root.draw(&Circle::new(pixel, 2, RED.filled()))?;
+        }
+
+        root.present()?;
+    }
+
+    overlay.save(path)?;
+
+    Ok(())
+}
+
+// Marks each center with a dot sized by its period, and draws its internal rays.
+pub fn plot_components(image: &RgbImage, components: &[Component]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut overlay = image.clone();
+
+    {
+        let root = BitMapBackend::with_buffer(&mut overlay, (IMG_WIDTH, IMG_HEIGHT)).into_drawing_area();
+
+        let to_pixel = |c: &Complex<f64>| {
+            let (x, y) = complex_to_pixel(Complex::new(c.re as f32, c.im as f32));
+
+            (x.round() as i32, y.round() as i32)
+        };
+
+        for component in components {
+            let color = Palette99::pick(component.period as usize);
+
+            for spoke in &component.spokes {
+                root.draw(&PathElement::new(spoke.iter().map(to_pixel).collect::<Vec<_>>(), color.stroke_width(2)))?;
+            }
+
+            root.draw(&Circle::new(
+                to_pixel(&component.center),
+                (12 - component.period as i32).max(3),
+                color.filled()
+            ))?;
+        }
+
+        root.present()?;
+    }
+
+    overlay.save("output_components.png")?;
+
+    Ok(())
+}
+
+// Rings each nucleus, as wide as its estimated size but always visible, and labels it with its period.
+pub fn plot_nuclei(image: &RgbImage, nuclei: &[Nucleus]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut overlay = image.clone();
+
+    {
+        let root = BitMapBackend::with_buffer(&mut overlay, (IMG_WIDTH, IMG_HEIGHT)).into_drawing_area();
+
+        let scale = IMG_WIDTH as f64 / (bounds().1.re - bounds().0.re) as f64;
+
+        for nucleus in nuclei {
+            let (x, y) = complex_to_pixel(Complex::new(nucleus.center.re as f32, nucleus.center.im as f32));
+
+            let pixel = (x.round() as i32, y.round() as i32);
+
+            let radius = (nucleus.size / 2.0 * scale).clamp(4.0, IMG_HEIGHT as f64) as i32;
+
+            root.draw(&Circle::new(pixel, radius, RED.stroke_width(2)))?;
+
+            root.draw(&Text::new(
+                nucleus.period.to_string(),
+                (pixel.0 + radius + 2, pixel.1 - 8),
+                ("sans-serif", 16).into_font().color(&RED)
+            ))?;
+        }
+
+        root.present()?;
+    }
+
+    overlay.save("output_nuclei.png")?;
+
+    Ok(())
+}
+
+// A round step, 1, 2, or 5 times a power of ten, that splits the span into about `count` parts.
+fn nice_step(span: f32, count: f32) -> f32 {
+    let raw = span / count;
+
+    let magnitude = 10f32.powf(raw.log10().floor());
+
+    [1.0, 2.0, 5.0, 10.0].iter().map(|factor| factor * magnitude).find(|&step| step >= raw).unwrap_or(raw)
+}
+
+// Enough decimal places to tell multiples of the step apart.
+fn step_decimals(step: f32) -> usize {
+    (-step.log10().floor()).max(0.0) as usize
+}
+
+// Draws a coordinate grid with labeled ticks along the real and imaginary axes, the axes
+// themselves, a scale bar, and the center of the view and the polar origin onto a copy of the
+// image, for figures.
+pub fn plot_annotations(image: &RgbImage, origin: Complex<f32>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut overlay = image.clone();
+
+    let (low, high) = bounds();
+
+    let size = (IMG_HEIGHT / 60) as i32;
+
+    let font = ("sans-serif", size).into_font().color(&WHITE);
+
+    let to_pixel = |c: Complex<f32>| {
+        let (x, y) = complex_to_pixel(c);
+
+        (x.round() as i32, y.round() as i32)
+    };
+
+    {
+        let root = BitMapBackend::with_buffer(&mut overlay, (IMG_WIDTH, IMG_HEIGHT)).into_drawing_area();
+
+        // Text on a dark backing, so it reads over members and escaped pixels alike.
+        let label = |text: String, (x, y): (i32, i32)| -> Result<(), Box<dyn std::error::Error>> {
+            let (width, height) = root.estimate_text_size(&text, &font)?;
+
+            let pad = size / 4;
+
+            let corners = [(x - pad, y - pad), (x + width as i32 + pad, y + height as i32 + pad)];
+
+            root.draw(&Rectangle::new(corners, BLACK.mix(0.6).filled()))?;
+            root.draw(&Text::new(text, (x, y), font.clone()))?;
+
+            Ok(())
+        };
+
+        let step = nice_step(high.re - low.re, 10.0);
+
+        let decimals = step_decimals(step);
+
+        let ticks = |from: f32, to: f32| {
+            ((from / step).ceil() as i64..=(to / step).floor() as i64).map(|i| i as f32 * step)
+        };
+
+        for re in ticks(low.re, high.re) {
+            let x = to_pixel(Complex::new(re, 0.0)).0;
+
+            let color = if re == 0.0 { WHITE.mix(0.8) } else { WHITE.mix(0.3) };
+
+            root.draw(&PathElement::new([(x, 0), (x, IMG_HEIGHT as i32)], color.stroke_width(2)))?;
+
+            label(format!("{:.*}", decimals, re), (x + 6, IMG_HEIGHT as i32 - 2 * size))?;
+        }
+
+        for im in ticks(low.im, high.im) {
+            let y = to_pixel(Complex::new(0.0, im)).1;
+
+            let color = if im == 0.0 { WHITE.mix(0.8) } else { WHITE.mix(0.3) };
+
+            root.draw(&PathElement::new([(0, y), (IMG_WIDTH as i32, y)], color.stroke_width(2)))?;
+
+            // Past the real axis' labels along the bottom, this one would cover them.
+            if y + 6 < IMG_HEIGHT as i32 - 3 * size {
+                label(format!("{:.*}i", decimals, im), (size / 2, y + 6))?;
+            }
+        }
+
+        // About a fifth of the width, rounded, in the lower right.
+        let length = nice_step(high.re - low.re, 5.0);
+
+        let pixels = (length / (high.re - low.re) * IMG_WIDTH as f32).round() as i32;
+
+        let (right, bottom) = (IMG_WIDTH as i32 - 2 * size, IMG_HEIGHT as i32 - 4 * size);
+
+        let bar = [(right - pixels, bottom - size / 2), (right, bottom)];
+
+        root.draw(&Rectangle::new(bar, WHITE.filled()))?;
+        root.draw(&Rectangle::new(bar, BLACK.stroke_width(2)))?;
+
+        label(format!("{}", length), (right - pixels, bottom - 2 * size))?;
+
+        let center = (low + high) / 2.0;
+
+        let markers = [(center, "center", YELLOW), (origin, "origin", GREEN)];
+
+        // When the origin is the center, the one marker stands for both.
+        for &(point, name, color) in markers.iter().filter(|(point, name, _)| *name == "origin" || *point != origin) {
+            let pixel = to_pixel(point);
+
+            root.draw(&Cross::new(pixel, size / 2, color.stroke_width(4)))?;
+
+            label(format!("{} {}", name, point), (pixel.0 + size, pixel.1 + size / 2))?;
+        }
+
+        root.present()?;
+    }
+
+    overlay.save("output_annotated.png")?;
+
+    Ok(())
+}
+
+pub fn plot_boundary(polylines: &[Vec<Complex<f32>>], origin: Complex<f32>) -> Result<(), Box<dyn std::error::Error>> {
+    let (min, max) = bounds();
+
+    let root = BitMapBackend::new("output_boundary.png", (1280, 720)).into_drawing_area();
+
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .margin(5)
+        .x_label_area_size(30)
+        .y_label_area_size(30)
+        .build_cartesian_2d(min.re..max.re, min.im..max.im)?;
+
+    chart.configure_mesh().disable_mesh().draw()?;
+
+    for polyline in polylines {
+        chart.draw_series(LineSeries::new(polyline.iter().map(|point| (point.re, point.im)), &RED))?;
+    }
+
+    chart.draw_series([Cross::new((origin.re, origin.im), 6, BLACK.stroke_width(2))])?;
+
+    root.present()?;
+
+    Ok(())
+}
+
+pub fn plot_equipotentials(
+    equipotentials: &[(f32, Vec<Vec<Complex<f32>>>)]
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (min, max) = bounds();
+
+    let root = BitMapBackend::new("output_equipotential.png", (1280, 720)).into_drawing_area();
+
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .margin(5)
+        .x_label_area_size(30)
+        .y_label_area_size(30)
+        .build_cartesian_2d(min.re..max.re, min.im..max.im)?;
+
+    chart.configure_mesh().disable_mesh().draw()?;
+
+    for (i, (level, polylines)) in equipotentials.iter().enumerate() {
+        let color = if *level == 0.0 { BLACK.to_rgba() } else { Palette99::pick(i).to_rgba() };
+
+        for (j, polyline) in polylines.iter().enumerate() {
+            let series =
+                chart.draw_series(LineSeries::new(polyline.iter().map(|point| (point.re, point.im)), &color))?;
+
+            if j == 0 {
+                series
+                    .label(if *level == 0.0 { "boundary".to_string() } else { format!("G = {:.4}", level) })
+                    .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color));
+            }
+        }
+    }
+
+    chart.configure_series_labels().background_style(WHITE).border_style(BLACK).draw()?;
+
+    root.present()?;
+
+    Ok(())
+}
+
+// Counts go on a log scale, since the few pixels that take long to escape are the ones that matter
+// for choosing the iteration limit.
+pub fn plot_histogram(histogram: &[u64], limit: u32) -> Result<(), Box<dyn std::error::Error>> {
+    let root = BitMapBackend::new("output_histogram.png", (1280, 720)).into_drawing_area();
+
+    root.fill(&WHITE)?;
+
+    let max = histogram.iter().copied().max().unwrap_or(1).max(1);
+
+    let mut chart = ChartBuilder::on(&root)
+        .margin(5)
+        .x_label_area_size(30)
+        .y_label_area_size(60)
+        .build_cartesian_2d(0..limit + 1, (1..max * 2).log_scale())?;
+
+    chart.configure_mesh().x_desc("iterations to escape").y_desc("pixels").draw()?;
+
+    chart.draw_series(
+        histogram
+            .iter()
+            .enumerate()
+            .filter(|&(_, &pixels)| pixels > 0)
+            .map(|(iterations, &pixels)| PathElement::new([(iterations as u32, 1), (iterations as u32, pixels)], RED))
+    )?;
+
+    root.present()?;
+
+    Ok(())
+}
+
+// The power spectrum goes on a log scale above the autocorrelation, whose peaks show the angular
+// periods of the bulbs.
+pub fn plot_spectrum(
+    power: &[(usize, f32)],
+    autocorrelation: &[(f32, f32)]
+) -> Result<(), Box<dyn std::error::Error>> {
+    let root = BitMapBackend::new("output_spectrum.png", (1280, 960)).into_drawing_area();
+
+    root.fill(&WHITE)?;
+
+    let (upper, lower) = root.split_vertically(480);
+
+    let positive = power.iter().map(|&(_, power)| power).filter(|&power| power > 0.0);
+
+    let max = positive.clone().fold(f32::MIN_POSITIVE, f32::max);
+    let min = positive.fold(max, f32::min);
+
+    let mut chart = ChartBuilder::on(&upper)
+        .margin(5)
+        .x_label_area_size(30)
+        .y_label_area_size(60)
+        .build_cartesian_2d(0..power.len() + 1, (min..max * 2.0).log_scale())?;
+
+    chart.configure_mesh().x_desc("harmonic").y_desc("power").draw()?;
+
+    chart.draw_series(
+        power
+            .iter()
+            .filter(|&&(_, power)| power > 0.0)
+            .map(|&(order, power)| PathElement::new([(order, min), (order, power)], RED))
+    )?;
+
+    let mut chart = ChartBuilder::on(&lower)
+        .margin(5)
+        .x_label_area_size(30)
+        .y_label_area_size(60)
+        .build_cartesian_2d(0.0..std::f32::consts::PI, -1.0f32..1.0)?;
+
+    chart.configure_mesh().x_desc("lag").y_desc("autocorrelation").draw()?;
+
+    chart.draw_series(LineSeries::new(autocorrelation.iter().copied(), &BLUE))?;
+
+    root.present()?;
+
+    Ok(())
+}
+
+pub fn plot_fourier(profile: &[(f32, f32)], harmonics: &[Harmonic]) -> Result<(), Box<dyn std::error::Error>> {
+    let domain = 0.0..std::f32::consts::PI * 2.0;
+
+    let root = BitMapBackend::new("output_fourier.png", (1280, 960)).into_drawing_area();
+
+    root.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root)
+        .margin(5)
+        .x_label_area_size(30)
+        .y_label_area_size(30)
+        .build_cartesian_2d(domain.start..domain.end, 0.0f32..MAX_RADIUS)?;
+
+    chart.configure_mesh().draw()?;
+
+    chart
+        .draw_series(LineSeries::new(profile.iter().copied(), &RED))?
+        .label("measured")
+        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], RED));
+
+    chart
+        .draw_series(LineSeries::new(
+            profile.iter().map(|&(theta, _)| (theta, reconstruct(harmonics, theta))),
+            &BLUE
+        ))?
+        .label(format!("order {} reconstruction", harmonics.len() - 1))
+        .legend(|(x, y)| PathElement::new([(x, y), (x + 20, y)], BLUE));
+
+    chart.configure_series_labels().background_style(WHITE).border_style(BLACK).draw()?;
+
+    root.present()?;
+
+    Ok(())
+}
+
+// Draws onto a copy of the rendered image in pixel coordinates, so what's shown is exactly what the
+// rays measured.
+pub fn plot_overlay(
+    image: &RgbImage,
+    profile: &[(f32, f32)],
+    origin: Complex<f32>,
+    rays: usize
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut overlay = image.clone();
+
+    {
+        let root = BitMapBackend::with_buffer(&mut overlay, (IMG_WIDTH, IMG_HEIGHT)).into_drawing_area();
+
+        let to_pixel = |c: Complex<f32>| {
+            let (x, y) = complex_to_coordinate(c);
+
+            (x as i32, y as i32)
+        };
+
+        let to_point = |&(theta, r): &(f32, f32)| to_pixel(origin + Complex::from_polar(r, theta));
+
+        let center = to_pixel(origin);
+
+        for sample in profile.iter().step_by((profile.len() / rays.max(1)).max(1)) {
+            root.draw(&PathElement::new([center, to_point(sample)], BLUE.stroke_width(2)))?;
+        }
+
+        for sample in profile {
+            root.draw(&Circle::new(to_point(sample), 3, RED.filled()))?;
+        }
+
+        root.draw(&Cross::new(center, 12, GREEN.stroke_width(4)))?;
+
+        root.present()?;
+    }
+
+    overlay.save("output_overlay.png")?;
+
+    Ok(())
+}
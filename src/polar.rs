@@ -0,0 +1,315 @@
+use crate::fractal::Fractal;
+use crate::render::IterationBuffer;
+use crate::viewport::{coordinate_to_complex, unclamped_coordinate};
+use bresenham::Bresenham;
+use num::complex::Complex;
+
+pub const MAX_RADIUS: f32 = 2.0;
+pub const DOMAIN_SIZE: usize = 1000;
+pub const RAY_STEP: f32 = 1e-3;
+// The finest step callers outside the command line may ask for, since a finer one would march too
+// many points along each ray for a job or a C caller to wait on.
+pub const MIN_RAY_STEP: f32 = MAX_RADIUS / (1 << 16) as f32;
+pub const ADAPTIVE_MAX_DEPTH: u32 = 10;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Bracket {
+    pub inside: f32,
+    pub outside: Option<f32>
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Interval {
+    pub start: Bracket,
+    pub end: Bracket
+}
+
+fn collect_intervals(points: impl Iterator<Item = (f32, bool)>) -> Vec<Interval> {
+    let mut intervals = Vec::new();
+
+    let mut previous: Option<f32> = None;
+
+    let mut start = None;
+
+    for (r, member) in points {
+        match start {
+            None if member => start = Some(Bracket { inside: r, outside: previous }),
+            Some(interval_start) if !member => {
+                intervals.push(Interval {
+                    start: interval_start,
+                    end: Bracket { inside: previous.unwrap(), outside: Some(r) }
+                });
+
+                start = None;
+            }
+
+            _ => {}
+        }
+
+        previous = Some(r);
+    }
+
+    if let Some(interval_start) = start {
+        intervals.push(Interval {
+            start: interval_start,
+            end: Bracket { inside: previous.unwrap(), outside: None }
+        });
+    }
+
+    intervals
+}
+
+pub type Crossing = (f32, Vec<(f32, f32)>);
+pub type Crossings = Vec<Crossing>;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Ray {
+    pub origin: Complex<f32>,
+    pub theta: f32
+}
+
+impl Ray {
+    pub fn new(origin: Complex<f32>, theta: f32) -> Self {
+        Self { origin, theta }
+    }
+
+    pub fn point(&self, r: f32) -> Complex<f32> {
+        self.origin + Complex::from_polar(r, self.theta)
+    }
+}
+
+fn first_exit(points: impl Iterator<Item = (f32, bool)>) -> Bracket {
+    let mut last_member = 0.0;
+
+    for (r, member) in points {
+        if !member {
+            return Bracket { inside: last_member, outside: Some(r) };
+        }
+
+        last_member = r;
+    }
+
+    Bracket { inside: last_member, outside: None }
+}
+
+// Clips the segment from start to end, in pixel coordinates, to the rectangle from the first pixel of
+// an image of the given size to the last, returning the part inside if any (Liang–Barsky).
+pub fn clip_segment(
+    start: (f32, f32),
+    end: (f32, f32),
+    (width, height): (u32, u32)
+) -> Option<((f32, f32), (f32, f32))> {
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let max = |extent: u32| (extent - 1) as f32;
+
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+
+    let (mut enter, mut exit) = (0.0f32, 1.0f32);
+
+    // Each edge as how fast the segment moves towards it and how far inside it the start is.
+    let edges = [(-dx, start.0), (dx, max(width) - start.0), (-dy, start.1), (dy, max(height) - start.1)];
+
+    for (towards, inside) in edges {
+        if towards == 0.0 {
+            if inside < 0.0 {
+                return None;
+            }
+
+            continue;
+        }
+
+        let t = inside / towards;
+
+        if towards < 0.0 {
+            enter = enter.max(t);
+        } else {
+            exit = exit.min(t);
+        }
+    }
+
+    if enter > exit {
+        return None;
+    }
+
+    let at = |t: f32| (start.0 + dx * t, start.1 + dy * t);
+
+    // An end that wasn't clipped is kept exactly, rather than recomputed with rounding error.
+    Some((if enter > 0.0 { at(enter) } else { start }, if exit < 1.0 { at(exit) } else { end }))
+}
+
+// The pixels along the part of the ray inside the image, which may start part of the way out if the
+// origin is off the canvas, or be empty if the ray misses it.
+fn raster_points(iterations: &IterationBuffer, ray: Ray) -> impl Iterator<Item = (f32, bool)> + '_ {
+    let size = (iterations.width(), iterations.height());
+
+    let clipped = clip_segment(unclamped_coordinate(ray.origin), unclamped_coordinate(ray.point(MAX_RADIUS)), size);
+
+    clipped
+        .into_iter()
+        .flat_map(|(start, end)| {
+            // Bresenham leaves out its end, which after clipping is always a pixel of the image. The
+            // casts to isize can't wrap, since the image's buffer couldn't exist if they did.
+            let end = (end.0 as isize, end.1 as isize);
+
+            Bresenham::new((start.0 as isize, start.1 as isize), end).chain(std::iter::once(end))
+        })
+        // Copied rows would only repeat the one above, so the boundary is bracketed by rendered ones.
+        .filter(move |&(_, y)| !iterations.is_copied(y as u32))
+        .map(move |(x, y)| {
+            (
+                (coordinate_to_complex((x as u32, y as u32)) - ray.origin).norm(),
+                iterations.is_member(x as u32, y as u32)
+            )
+        })
+}
+
+fn march_points(fractal: &dyn Fractal, ray: Ray, step: f32) -> impl Iterator<Item = (f32, bool)> + '_ {
+    let steps = (MAX_RADIUS / step).ceil() as u32;
+
+    (0..=steps).map(move |i| {
+        let r = (i as f32 * step).min(MAX_RADIUS);
+
+        (r, fractal.sample(ray.point(r)).is_member())
+    })
+}
+
+pub fn compute_radius(iterations: &IterationBuffer, ray: Ray) -> Bracket {
+    first_exit(raster_points(iterations, ray))
+}
+
+pub fn compute_intervals(iterations: &IterationBuffer, ray: Ray) -> Vec<Interval> {
+    collect_intervals(raster_points(iterations, ray))
+}
+
+pub fn march_radius(fractal: &dyn Fractal, ray: Ray, step: f32) -> Bracket {
+    first_exit(march_points(fractal, ray, step))
+}
+
+pub fn march_intervals(fractal: &dyn Fractal, ray: Ray, step: f32) -> Vec<Interval> {
+    collect_intervals(march_points(fractal, ray, step))
+}
+
+pub fn refine_radius(fractal: &dyn Fractal, ray: Ray, bracket: Bracket, tolerance: f32) -> f32 {
+    let Bracket { mut inside, outside } = bracket;
+
+    let mut outside = match outside {
+        Some(outside) => outside,
+        None => return inside
+    };
+
+    while (outside - inside).abs() > tolerance {
+        let middle = (inside + outside) / 2.0;
+
+        if middle == inside || middle == outside {
+            break;
+        }
+
+        if fractal.sample(ray.point(middle)).is_member() {
+            inside = middle;
+        } else {
+            outside = middle;
+        }
+    }
+
+    inside
+}
+
+// The angles r(θ) is sampled over: the whole turn, which wraps around, or a closed sub-range of
+// it, which doesn't.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Domain {
+    #[default]
+    Full,
+    Range(f32, f32)
+}
+
+impl Domain {
+    pub fn bounds(self) -> (f32, f32) {
+        match self {
+            Domain::Full => (0.0, std::f32::consts::PI * 2.0),
+            Domain::Range(start, end) => (start, end)
+        }
+    }
+}
+
+// A sub-range includes both of its ends, since nothing wraps around to cover the last one.
+pub fn angles(domain: Domain, domain_size: usize) -> impl Iterator<Item = f32> {
+    let (start, end) = domain.bounds();
+
+    let divisions = match domain {
+        Domain::Full => domain_size,
+        Domain::Range(..) => domain_size.saturating_sub(1).max(1)
+    };
+
+    (0..domain_size).map(move |i| start + (end - start) / divisions as f32 * i as f32)
+}
+
+pub fn sample_profile(domain: Domain, domain_size: usize, radius: impl Fn(f32) -> f32) -> Vec<(f32, f32)> {
+    angles(domain, domain_size).map(|theta| (theta, radius(theta))).collect()
+}
+
+pub fn refine_interval(fractal: &dyn Fractal, ray: Ray, interval: Interval, tolerance: Option<f32>) -> (f32, f32) {
+    match tolerance {
+        Some(tolerance) => (
+            refine_radius(fractal, ray, interval.start, tolerance),
+            refine_radius(fractal, ray, interval.end, tolerance)
+        ),
+
+        None => (interval.start.inside, interval.end.inside)
+    }
+}
+
+fn subdivide(
+    a: (f32, f32),
+    b: (f32, f32),
+    depth: u32,
+    tolerance: f32,
+    radius: &dyn Fn(f32) -> f32,
+    profile: &mut Vec<(f32, f32)>
+) {
+    if depth >= ADAPTIVE_MAX_DEPTH || (b.1 - a.1).abs() <= tolerance {
+        return;
+    }
+
+    let theta = (a.0 + b.0) / 2.0;
+
+    let middle = (theta, radius(theta));
+
+    subdivide(a, middle, depth + 1, tolerance, radius, profile);
+
+    profile.push(middle);
+
+    subdivide(middle, b, depth + 1, tolerance, radius, profile);
+}
+
+// Starts from evenly spaced angles and bisects any gap across which r changes by more than the
+// tolerance, so the result is sorted by θ but no longer evenly spaced.
+pub fn sample_adaptive(
+    domain: Domain,
+    domain_size: usize,
+    tolerance: f32,
+    radius: impl Fn(f32) -> f32
+) -> Vec<(f32, f32)> {
+    let coarse = sample_profile(domain, domain_size, &radius);
+
+    let mut profile = Vec::with_capacity(coarse.len());
+
+    for (i, &a) in coarse.iter().enumerate() {
+        let b = match (coarse.get(i + 1), domain) {
+            (Some(&b), _) => Some(b),
+            (None, Domain::Full) => Some((coarse[0].0 + std::f32::consts::PI * 2.0, coarse[0].1)),
+            (None, Domain::Range(..)) => None
+        };
+
+        profile.push(a);
+
+        if let Some(b) = b {
+            subdivide(a, b, 0, tolerance, &radius, &mut profile);
+        }
+    }
+
+    profile
+}
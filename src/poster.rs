@@ -0,0 +1,344 @@
+// Renders an --roi image at print sizes, too large to hold in memory or to render in one sitting,
+// as overlapping tiles saved to a directory one by one, so that a poster can be resumed or split
+// over several runs or machines. Stitching checks that neighboring tiles agree where they overlap
+// and streams them into a single PNG or TIFF a row of tiles at a time.
+
+use crate::fractal::Fractal;
+use crate::render::{self, Colorer};
+use image::RgbImage;
+use num::complex::Complex;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub const TILE: u32 = 2048;
+
+// How many pixels each tile shares with the next one across and down, which stitching checks.
+pub const OVERLAP: u32 = 16;
+
+// Pixels are placed by their positions in single precision, which counts them exactly only up to 2²⁴.
+pub const MAX_SIZE: u32 = 1 << 24;
+
+// The file in the directory recording what the tiles are of, so that runs adding to it agree.
+const MANIFEST: &str = "poster.txt";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Poster {
+    pub size: (u32, u32),
+    pub rect: (Complex<f32>, Complex<f32>),
+    pub tile: u32,
+    pub samples: u32
+}
+
+impl Poster {
+    // The number of tiles across and down.
+    pub fn grid(&self) -> (u32, u32) {
+        (self.size.0.div_ceil(self.tile), self.size.1.div_ceil(self.tile))
+    }
+
+    // The tile's own pixels across or down, and the ones it shares with the next.
+    fn span(&self, index: u32, length: u32) -> (Range<u32>, Range<u32>) {
+        let start = index * self.tile;
+
+        let end = (start + self.tile).min(length);
+
+        (start..end, start..end.saturating_add(OVERLAP).min(length))
+    }
+
+    fn manifest(&self) -> String {
+        let (low, high) = self.rect;
+
+        format!(
+            "size = {}x{}\nroi = {},{},{},{}\ntile = {}\nantialias = {}\n",
+            self.size.0, self.size.1, low.re, high.re, low.im, high.im, self.tile, self.samples
+        )
+    }
+
+    fn parse(manifest: &str) -> Option<Self> {
+        let mut values = manifest.lines().filter_map(|line| line.split_once('=')).map(|(_, value)| value.trim());
+
+        let size = values.next()?.split_once('x')?;
+        let size = (size.0.parse().ok()?, size.1.parse().ok()?);
+
+        let roi = values.next()?.split(',').map(str::parse).collect::<Result<Vec<f32>, _>>().ok()?;
+
+        let [re_min, re_max, im_min, im_max] = roi[..] else {
+            return None;
+        };
+
+        let rect = (Complex::new(re_min, im_min), Complex::new(re_max, im_max));
+
+        Some(Self { size, rect, tile: values.next()?.parse().ok()?, samples: values.next()?.parse().ok()? })
+    }
+}
+
+fn tile_path(directory: &Path, (column, row): (u32, u32)) -> PathBuf {
+    directory.join(format!("tile_{}_{}.png", column, row))
+}
+
+fn load_manifest(directory: &Path) -> Result<Poster, Box<dyn std::error::Error>> {
+    let path = directory.join(MANIFEST);
+
+    let manifest = std::fs::read_to_string(&path).map_err(|error| format!("{}: {}", path.display(), error))?;
+
+    Ok(Poster::parse(&manifest).ok_or_else(|| format!("{}: not a poster manifest", path.display()))?)
+}
+
+// Renders the tiles numbered `shard.0` modulo `shard.1`, row by row, into the directory, skipping
+// any already there. Each is written under another name first and renamed when it's complete, so an
+// interrupted run leaves no partial tiles behind.
+pub async fn render(
+    directory: &Path,
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    poster: &Poster,
+    shard: (u32, u32)
+) -> Result<(), Box<dyn std::error::Error>> {
+    std::fs::create_dir_all(directory)?;
+
+    if directory.join(MANIFEST).exists() {
+        if load_manifest(directory)? != *poster {
+            return Err(format!("{} holds the tiles of a different poster", directory.display()).into());
+        }
+    } else {
+        std::fs::write(directory.join(MANIFEST), poster.manifest())?;
+    }
+
+    let (columns, rows) = poster.grid();
+
+    let tiles = columns as u64 * rows as u64;
+
+    for index in (shard.0 as u64..tiles).step_by(shard.1 as usize) {
+        let tile = ((index % columns as u64) as u32, (index / columns as u64) as u32);
+
+        let path = tile_path(directory, tile);
+
+        if path.exists() {
+            continue;
+        }
+
+        let (_, x) = poster.span(tile.0, poster.size.0);
+        let (_, y) = poster.span(tile.1, poster.size.1);
+
+        let (fractal, colorer) = (fractal.clone(), colorer.clone());
+
+        let image = render::render_rect_region(fractal, colorer, poster.size, poster.rect, poster.samples, x, y).await;
+
+        let partial = path.with_extension("partial.png");
+
+        image.save(&partial)?;
+
+        std::fs::rename(&partial, &path)?;
+
+        eprintln!("poster: tile {} of {}, {}", index + 1, tiles, path.display());
+    }
+
+    Ok(())
+}
+
+// An uncompressed RGB baseline TIFF in a single strip: the header up front, the pixels as they come,
+// and the directory describing them at the end. Classic TIFF's offsets are 32 bits, which caps the
+// whole file at 4 GiB.
+struct Tiff {
+    file: BufWriter<File>,
+    size: (u32, u32)
+}
+
+impl Tiff {
+    const ENTRIES: u16 = 10;
+
+    fn create(path: &Path, size: (u32, u32)) -> std::io::Result<Self> {
+        let length = size.0 as u64 * size.1 as u64 * 3;
+
+        // The pixels, padded to an even length, the directory, and the bits per sample after it.
+        let end = 8 + length.next_multiple_of(2) + 2 + Self::ENTRIES as u64 * 12 + 4 + 6;
+
+        if end > u32::MAX as u64 {
+            return Err(std::io::Error::other("the poster is too large for a TIFF; write a PNG instead"));
+        }
+
+        let mut file = BufWriter::new(File::create(path)?);
+
+        file.write_all(b"II*\0")?;
+        file.write_all(&(8 + length.next_multiple_of(2) as u32).to_le_bytes())?;
+
+        Ok(Self { file, size })
+    }
+
+    fn finish(mut self) -> std::io::Result<()> {
+        let length = self.size.0 * self.size.1 * 3;
+
+        if length % 2 == 1 {
+            self.file.write_all(&[0])?;
+        }
+
+        let directory = 8 + length.next_multiple_of(2);
+
+        let bits_per_sample = directory + 2 + Self::ENTRIES as u32 * 12 + 4;
+
+        // Tag, type (3 for 16-bit, 4 for 32-bit), count, and value, or where the values are when
+        // they don't fit in 4 bytes. Values shorter than that come first, which is the low bytes.
+        let entries: [(u16, u16, u32, u32); Self::ENTRIES as usize] = [
+            (256, 4, 1, self.size.0),
+            (257, 4, 1, self.size.1),
+            (258, 3, 3, bits_per_sample),
+            (259, 3, 1, 1),
+            (262, 3, 1, 2),
+            (273, 4, 1, 8),
+            (277, 3, 1, 3),
+            (278, 4, 1, self.size.1),
+            (279, 4, 1, length),
+            (284, 3, 1, 1)
+        ];
+
+        self.file.write_all(&Self::ENTRIES.to_le_bytes())?;
+
+        for (tag, kind, count, value) in entries {
+            self.file.write_all(&tag.to_le_bytes())?;
+            self.file.write_all(&kind.to_le_bytes())?;
+            self.file.write_all(&count.to_le_bytes())?;
+            self.file.write_all(&value.to_le_bytes())?;
+        }
+
+        self.file.write_all(&0u32.to_le_bytes())?;
+        self.file.write_all(&[8, 0, 8, 0, 8, 0])?;
+
+        self.file.flush()
+    }
+}
+
+enum Output {
+    Png(Box<png::StreamWriter<'static, BufWriter<File>>>),
+    Tiff(Tiff)
+}
+
+impl Output {
+    // A TIFF for paths ending in .tif or .tiff, and a PNG otherwise.
+    fn create(path: &Path, size: (u32, u32)) -> std::io::Result<Self> {
+        let extension = path.extension().and_then(|extension| extension.to_str()).map(str::to_ascii_lowercase);
+
+        if matches!(extension.as_deref(), Some("tif" | "tiff")) {
+            return Ok(Self::Tiff(Tiff::create(path, size)?));
+        }
+
+        let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), size.0, size.1);
+
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let writer = encoder.write_header().map_err(std::io::Error::other)?;
+
+        Ok(Self::Png(Box::new(writer.into_stream_writer().map_err(std::io::Error::other)?)))
+    }
+
+    fn write(&mut self, row: &[u8]) -> std::io::Result<()> {
+        match self {
+            Self::Png(stream) => stream.write_all(row),
+            Self::Tiff(tiff) => tiff.file.write_all(row)
+        }
+    }
+
+    fn finish(self) -> std::io::Result<()> {
+        match self {
+            Self::Png(stream) => stream.finish().map_err(std::io::Error::other),
+            Self::Tiff(tiff) => tiff.finish()
+        }
+    }
+}
+
+// The number of pixels two tiles share, the first being `from` and the second `to` pixels into it
+// across and down, that differ between them.
+fn seam(first: &RgbImage, second: &RgbImage, from: (u32, u32), to: (u32, u32)) -> (u64, u64) {
+    let width = (first.width() - from.0).min(second.width() - to.0);
+    let height = (first.height() - from.1).min(second.height() - to.1);
+
+    let differing = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .filter(|&(x, y)| first.get_pixel(from.0 + x, from.1 + y) != second.get_pixel(to.0 + x, to.1 + y))
+        .count();
+
+    (differing as u64, width as u64 * height as u64)
+}
+
+// Stitches the tiles in the directory into one image at the path, holding two rows of tiles in
+// memory at most, and fails if any are missing or any two disagree on a pixel they share.
+pub fn stitch(directory: &Path, path: &Path) -> Result<Poster, Box<dyn std::error::Error>> {
+    let poster = load_manifest(directory)?;
+
+    let (columns, rows) = poster.grid();
+
+    let missing = (0..rows).flat_map(|row| (0..columns).map(move |column| (column, row)));
+    let missing = missing.filter(|&tile| !tile_path(directory, tile).exists()).collect::<Vec<_>>();
+
+    if let Some(&first) = missing.first() {
+        let tiles = columns as u64 * rows as u64;
+
+        let first = tile_path(directory, first);
+
+        return Err(format!("{} of the {} tiles are missing, such as {}", missing.len(), tiles, first.display()).into());
+    }
+
+    let mut output = Output::create(path, poster.size)?;
+
+    let mut above = Vec::<RgbImage>::new();
+
+    for row in 0..rows {
+        let (own_rows, y) = poster.span(row, poster.size.1);
+
+        let mut tiles = Vec::new();
+
+        for column in 0..columns {
+            let name = tile_path(directory, (column, row));
+
+            let tile = image::open(&name)?.to_rgb8();
+
+            let (_, x) = poster.span(column, poster.size.0);
+
+            if tile.dimensions() != (x.len() as u32, y.len() as u32) {
+                return Err(format!("{} isn't the size of that tile", name.display()).into());
+            }
+
+            let neighbors = [
+                tiles.last().map(|left: &RgbImage| (left, (column - 1, row), (poster.tile, 0))),
+                above.get(column as usize).map(|up| (up, (column, row - 1), (0, poster.tile)))
+            ];
+
+            for (neighbor, at, from) in neighbors.into_iter().flatten() {
+                let (differing, shared) = seam(neighbor, &tile, from, (0, 0));
+
+                if differing > 0 {
+                    return Err(format!(
+                        "{} and {} disagree on {} of the {} pixels they share",
+                        tile_path(directory, at).display(),
+                        name.display(),
+                        differing,
+                        shared
+                    )
+                    .into());
+                }
+            }
+
+            tiles.push(tile);
+        }
+
+        for y in 0..own_rows.len() as u32 {
+            for (column, tile) in tiles.iter().enumerate() {
+                let (own_columns, _) = poster.span(column as u32, poster.size.0);
+
+                let start = (y * tile.width()) as usize * 3;
+
+                output.write(&tile.as_raw()[start..start + own_columns.len() * 3])?;
+            }
+        }
+
+        eprintln!("stitch: row {} of {} of tiles", row + 1, rows);
+
+        above = tiles;
+    }
+
+    output.finish()?;
+
+    Ok(poster)
+}
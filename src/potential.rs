@@ -0,0 +1,70 @@
+// The Green's function of the Mandelbrot set's exterior, G(c) = lim log|zₙ| / 2ⁿ, whose level sets
+// are the equipotential curves around it.
+
+use crate::boundary;
+use crate::fractal::BAILOUT_ITERATIONS;
+use crate::viewport::{pixel_to_complex, IMG_HEIGHT, IMG_WIDTH};
+use num::complex::Complex;
+
+// The limit converges once |z| is large, so iteration carries on well past the escape radius.
+const POTENTIAL_BAILOUT_RADIUS: f32 = 1e4;
+
+// The equipotentials are traced on a grid this many times coarser than the image.
+pub const EQUIPOTENTIAL_SCALE: u32 = 4;
+
+// Returns 0 for members.
+pub fn green(c: Complex<f32>) -> f32 {
+    let mut z = Complex::new(0.0, 0.0);
+
+    for i in 0..BAILOUT_ITERATIONS {
+        z = z * z + c;
+
+        if z.norm() >= POTENTIAL_BAILOUT_RADIUS {
+            return z.norm().ln() / 2f32.powi(i as i32 + 1);
+        }
+    }
+
+    0.0
+}
+
+// Starts with 0, the outline of the set itself. Far from the set G(c) ≈ log|c|, and each halving of
+// the level moves the curve about one iteration closer to the boundary.
+pub fn levels(count: usize) -> Vec<f32> {
+    std::iter::once(0.0).chain((0..count).map(|k| 2f32.ln() / 2f32.powi(k as i32))).collect()
+}
+
+// Returns the curves at each level in the complex plane. They're clipped to the view, so curves
+// that leave it are open.
+pub fn equipotentials(levels: &[f32]) -> Vec<(f32, Vec<Vec<Complex<f32>>>)> {
+    let width = IMG_WIDTH / EQUIPOTENTIAL_SCALE;
+    let height = IMG_HEIGHT / EQUIPOTENTIAL_SCALE;
+
+    let scale = EQUIPOTENTIAL_SCALE as f32;
+
+    let to_complex = |x: f32, y: f32| pixel_to_complex((x + 0.5) * scale - 0.5, (y + 0.5) * scale - 0.5);
+
+    let potentials = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .map(|(x, y)| green(to_complex(x as f32, y as f32)))
+        .collect::<Vec<_>>();
+
+    // Clamping to the nearest pixel keeps the padding from inventing crossings along the edges.
+    let potential = |x: i64, y: i64| {
+        let x = x.clamp(0, width as i64 - 1) as usize;
+        let y = y.clamp(0, height as i64 - 1) as usize;
+
+        potentials[y * width as usize + x]
+    };
+
+    levels
+        .iter()
+        .map(|&level| {
+            let polylines = boundary::trace(width, height, &|x, y| potential(x, y) <= level)
+                .into_iter()
+                .map(|polyline| polyline.into_iter().map(|(x, y)| to_complex(x, y)).collect())
+                .collect();
+
+            (level, polylines)
+        })
+        .collect()
+}
@@ -0,0 +1,940 @@
+use crate::fractal::{Fractal, Limited, Sample, Termination, BAILOUT_ITERATIONS};
+use crate::palette::Palette;
+use crate::viewport::{coordinate_to_complex, log_polar_height, log_polar_to_complex, View, IMG_HEIGHT, IMG_WIDTH};
+use image::{ImageBuffer, Rgb, RgbImage};
+use num::complex::Complex;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+// What a whole image rendered in memory takes per pixel at most: the iteration buffer's count and
+// flag, the colors, and render_view's running sums of them.
+pub const BYTES_PER_PIXEL: usize = 4 + 1 + 3 + 3 * 8;
+
+// The length of a buffer of the given number of bytes per pixel for an image of the given size, or
+// None if it can't be addressed, rather than a product that quietly wrapped around.
+pub fn buffer_len((width, height): (u32, u32), bytes_per_pixel: usize) -> Option<usize> {
+    (width as usize)
+        .checked_mul(height as usize)?
+        .checked_mul(bytes_per_pixel)
+        .filter(|&len| len <= isize::MAX as usize)
+}
+
+// How the pixels of a render ended up. Many capped pixels, or escaped ones running up to the cap,
+// mean the iteration limit is too low for the view. Only the rows rendered count, not those a render
+// out of time copied.
+#[derive(Clone, Copy, Debug)]
+pub struct Statistics {
+    pub pixels: usize,
+    pub escaped: usize,
+    // Members that never escaped or converged, stopped only by the limit.
+    pub capped: usize,
+    pub mean_iterations: f32,
+    pub max_iterations: u32,
+    pub rendered_rows: u32,
+    pub gap: u32
+}
+
+impl Statistics {
+    pub fn interior_fraction(&self) -> f32 {
+        (self.pixels - self.escaped) as f32 / self.pixels as f32
+    }
+
+    pub fn escaped_fraction(&self) -> f32 {
+        self.escaped as f32 / self.pixels as f32
+    }
+}
+
+// The first bytes of a buffer saved with IterationBuffer::save.
+const SAVED_MAGIC: &[u8; 8] = b"PMITERS1";
+
+pub struct IterationBuffer {
+    width: u32,
+    height: u32,
+    iterations: Vec<u32>,
+    escaped: Vec<bool>,
+    // Only kept when asked for, since it takes as much memory again as the counts.
+    smooth: Option<Vec<f32>>,
+    // The rows a render out of time copied from the nearest rendered one above.
+    copied: Vec<bool>
+}
+
+impl IterationBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        let pixels = buffer_len((width, height), 1).expect("the image is too large to address");
+
+        let (iterations, escaped, copied) = (vec![0; pixels], vec![false; pixels], vec![false; height as usize]);
+
+        Self { width, height, iterations, escaped, smooth: None, copied }
+    }
+
+    // A buffer that also keeps each pixel's continuous escape value.
+    pub fn with_smooth(width: u32, height: u32) -> Self {
+        let mut buffer = Self::new(width, height);
+
+        buffer.smooth = Some(vec![0.0; buffer.iterations.len()]);
+
+        buffer
+    }
+
+    // A buffer the size of the image, keeping the continuous escape values with `smooth`.
+    pub fn for_render(smooth: bool) -> Self {
+        if smooth {
+            Self::with_smooth(IMG_WIDTH, IMG_HEIGHT)
+        } else {
+            Self::new(IMG_WIDTH, IMG_HEIGHT)
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, sample: &Sample) {
+        let index = self.index(x, y);
+
+        self.iterations[index] = sample.iterations;
+        self.escaped[index] = !sample.is_member();
+    }
+
+    pub fn has_smooth(&self) -> bool {
+        self.smooth.is_some()
+    }
+
+    pub fn set_smooth(&mut self, x: u32, y: u32, value: f32) {
+        let index = self.index(x, y);
+
+        if let Some(smooth) = &mut self.smooth {
+            smooth[index] = value;
+        }
+    }
+
+    // The continuous escape value, if the buffer keeps them.
+    pub fn smooth(&self, x: u32, y: u32) -> Option<f32> {
+        self.smooth.as_ref().map(|smooth| smooth[self.index(x, y)])
+    }
+
+    pub fn is_member(&self, x: u32, y: u32) -> bool {
+        !self.escaped[self.index(x, y)]
+    }
+
+    pub fn set_copied(&mut self, y: u32) {
+        self.copied[y as usize] = true;
+    }
+
+    pub fn is_copied(&self, y: u32) -> bool {
+        self.copied[y as usize]
+    }
+
+    pub fn rendered_rows(&self) -> u32 {
+        self.copied.iter().filter(|&&copied| !copied).count() as u32
+    }
+
+    // The most rows from one rendered row to the next, or to the bottom: 1 if none were copied.
+    pub fn gap(&self) -> u32 {
+        self.copied.split(|&copied| !copied).map(|run| run.len() as u32 + 1).max().unwrap_or(1)
+    }
+
+    // The iterations and whether they escaped of each pixel in the rows rendered.
+    fn rendered(&self) -> impl Iterator<Item = (u32, bool)> + '_ {
+        let width = self.width.max(1) as usize;
+
+        let rows = self.iterations.chunks(width).zip(self.escaped.chunks(width)).zip(&self.copied);
+
+        rows.filter(|(_, &copied)| !copied)
+            .flat_map(|((iterations, escaped), _)| iterations.iter().copied().zip(escaped.iter().copied()))
+    }
+
+    // Among the rows rendered.
+    pub fn member_count(&self) -> usize {
+        self.rendered().filter(|&(_, escaped)| !escaped).count()
+    }
+
+    // Mean and max iterations are among the escaped pixels, the interior being at the limit anyway.
+    pub fn statistics(&self, limit: u32) -> Statistics {
+        let mut statistics = Statistics {
+            pixels: 0,
+            escaped: 0,
+            capped: 0,
+            mean_iterations: 0.0,
+            max_iterations: 0,
+            rendered_rows: self.rendered_rows(),
+            gap: self.gap()
+        };
+
+        let mut total = 0u64;
+
+        for (iterations, escaped) in self.rendered() {
+            statistics.pixels += 1;
+
+            if escaped {
+                statistics.escaped += 1;
+                statistics.max_iterations = statistics.max_iterations.max(iterations);
+
+                total += iterations as u64;
+            } else if iterations >= limit {
+                statistics.capped += 1;
+            }
+        }
+
+        if statistics.escaped > 0 {
+            statistics.mean_iterations = (total as f64 / statistics.escaped as f64) as f32;
+        }
+
+        statistics
+    }
+
+    // The number of escaped pixels in the rows rendered by how many iterations they took to escape,
+    // from none up to the most any took.
+    pub fn histogram(&self) -> Vec<u64> {
+        let escaped = self.rendered().filter(|&(_, escaped)| escaped);
+
+        let mut histogram = Vec::new();
+
+        for (iterations, _) in escaped {
+            if histogram.len() <= iterations as usize {
+                histogram.resize(iterations as usize + 1, 0);
+            }
+
+            histogram[iterations as usize] += 1;
+        }
+
+        histogram
+    }
+
+    // Returns the number of horizontally and vertically adjacent pixel pairs where one pixel is a
+    // member and the other isn't.
+    pub fn boundary_edges(&self) -> (usize, usize) {
+        let mut horizontal = 0;
+        let mut vertical = 0;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let member = self.is_member(x, y);
+
+                if x + 1 < self.width && self.is_member(x + 1, y) != member {
+                    horizontal += 1;
+                }
+
+                if y + 1 < self.height && self.is_member(x, y + 1) != member {
+                    vertical += 1;
+                }
+            }
+        }
+
+        (horizontal, vertical)
+    }
+
+    // The samples as far as the buffer keeps them, with the point each pixel of the full view
+    // samples. What members stopped on isn't kept, so they're left without a termination.
+    pub fn sample(&self, x: u32, y: u32) -> Sample {
+        let index = self.index(x, y);
+
+        Sample {
+            c: coordinate_to_complex((x, y)),
+            z: Complex::new(0.0, 0.0),
+            iterations: self.iterations[index],
+            termination: self.escaped[index].then_some(Termination::Escaped)
+        }
+    }
+
+    // Saved with a header of its own, so a file that isn't a buffer is rejected rather than misread.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(SAVED_MAGIC)?;
+
+        self.write_to(&mut writer)?;
+
+        writer.flush()
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0; SAVED_MAGIC.len()];
+
+        reader.read_exact(&mut magic)?;
+
+        if &magic != SAVED_MAGIC {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "not a saved iteration buffer"));
+        }
+
+        Self::read_from(&mut reader)
+    }
+
+    // The size, then every pixel's iteration count and whether it escaped, in little-endian order,
+    // then whether the continuous values follow and, if so, each of them.
+    pub fn write_to(&self, writer: &mut impl Write) -> std::io::Result<()> {
+        writer.write_all(&self.width.to_le_bytes())?;
+        writer.write_all(&self.height.to_le_bytes())?;
+
+        for (&iterations, &escaped) in self.iterations.iter().zip(&self.escaped) {
+            writer.write_all(&iterations.to_le_bytes())?;
+            writer.write_all(&[escaped as u8])?;
+        }
+
+        writer.write_all(&[self.smooth.is_some() as u8])?;
+
+        for value in self.smooth.iter().flatten() {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    // The header's size isn't trusted until that many pixels have been read, so a truncated or
+    // corrupt file is an error rather than an allocation of whatever size it claims.
+    pub fn read_from(reader: &mut impl Read) -> std::io::Result<Self> {
+        let mut word = [0; 4];
+
+        let mut read_u32 = |reader: &mut dyn Read| reader.read_exact(&mut word).map(|_| u32::from_le_bytes(word));
+
+        let (width, height) = (read_u32(reader)?, read_u32(reader)?);
+
+        if buffer_len((width, height), BYTES_PER_PIXEL).is_none() {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "the buffer is too large"));
+        }
+
+        let pixels = width as usize * height as usize;
+
+        let counts = read_bytes(reader, pixels * 5)?;
+
+        let mut buffer = Self::new(width, height);
+
+        for (i, pixel) in counts.chunks_exact(5).enumerate() {
+            buffer.iterations[i] = u32::from_le_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+            buffer.escaped[i] = pixel[4] != 0;
+        }
+
+        drop(counts);
+
+        let mut smooth = [0];
+
+        reader.read_exact(&mut smooth)?;
+
+        if smooth[0] != 0 {
+            let values = read_bytes(reader, pixels * 4)?;
+
+            let value = |bytes: &[u8]| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+            buffer.smooth = Some(values.chunks_exact(4).map(value).collect());
+        }
+
+        Ok(buffer)
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+}
+
+// Exactly `len` bytes, read into a buffer that grows as they arrive rather than one allocated up
+// front, since the length may come from a corrupt header.
+fn read_bytes(reader: &mut impl Read, len: usize) -> std::io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+
+    if bytes.len() < len {
+        return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "the buffer is truncated"));
+    }
+
+    Ok(bytes)
+}
+
+pub trait Colorer: Send + Sync {
+    fn color(&self, sample: &Sample) -> Rgb<u8>;
+}
+
+pub struct MembershipColorer;
+
+impl Colorer for MembershipColorer {
+    fn color(&self, sample: &Sample) -> Rgb<u8> {
+        Rgb::<u8>(
+            if sample.is_member() {
+                [255, 255, 255]
+            } else {
+                [0, 0, 0]
+            }
+        )
+    }
+}
+
+// Iterations per cycle of the palette.
+const PALETTE_PERIOD: f32 = 32.0;
+
+// Colors escaped points by escape time through a cosine palette, or the theme if there is one,
+// offset by the phase in turns so that animating it cycles the colors, and members black.
+pub struct PaletteColorer {
+    pub phase: f32,
+    pub theme: Option<Palette>
+}
+
+impl Colorer for PaletteColorer {
+    fn color(&self, sample: &Sample) -> Rgb<u8> {
+        if sample.is_member() {
+            return Rgb([0, 0, 0]);
+        }
+
+        let t = sample.iterations as f32 / PALETTE_PERIOD + self.phase;
+
+        if let Some(theme) = &self.theme {
+            return theme.at(t);
+        }
+
+        Rgb([0.0, 1.0 / 3.0, 2.0 / 3.0].map(|offset| {
+            ((0.5 + 0.5 * (std::f32::consts::TAU * (t + offset)).cos()) * 255.0).round() as u8
+        }))
+    }
+}
+
+// Colors escaped points by the palette color as many steps in as they took to escape, and members
+// black.
+pub struct CycleColorer {
+    pub palette: Palette
+}
+
+impl Colorer for CycleColorer {
+    fn color(&self, sample: &Sample) -> Rgb<u8> {
+        if sample.is_member() {
+            Rgb([0, 0, 0])
+        } else {
+            self.palette.cycle(sample.iterations)
+        }
+    }
+}
+
+struct Canvas {
+    iterations: IterationBuffer,
+    image: RgbImage
+}
+
+type Position = Arc<dyn Fn((u32, u32)) -> Complex<f32> + Send + Sync>;
+
+fn compute_row(fractal: &dyn Fractal, position: &Position, width: u32, y: u32) -> Vec<Sample> {
+    (0..width).map(|x| fractal.sample(position((x, y)))).collect()
+}
+
+async fn compute_and_set_row(
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    position: Position,
+    canvas_mutex: Arc<Mutex<Canvas>>,
+    y: u32
+) {
+    let (width, smooth) = {
+        let canvas = canvas_mutex.lock().unwrap();
+
+        (canvas.iterations.width(), canvas.iterations.has_smooth())
+    };
+
+    let row = compute_row(fractal.as_ref(), &position, width, y);
+
+    let colors = row.iter().map(|sample| colorer.color(sample)).collect::<Vec<_>>();
+
+    let escape = fractal.escape();
+
+    let smooth = smooth.then(|| row.iter().map(|sample| sample.smooth_iterations(escape)).collect::<Vec<_>>());
+
+    let mut canvas = canvas_mutex.lock().unwrap();
+
+    for x in 0..width {
+        canvas.iterations.set(x, y, &row[x as usize]);
+        canvas.image.put_pixel(x, y, colors[x as usize]);
+    }
+
+    for (x, &value) in smooth.iter().flatten().enumerate() {
+        canvas.iterations.set_smooth(x as u32, y, value);
+    }
+}
+
+// Fills in the given buffer, which sets the size, and an image of it.
+async fn render_into(
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    iterations: IterationBuffer,
+    position: Position
+) -> (IterationBuffer, RgbImage) {
+    let (width, height) = (iterations.width(), iterations.height());
+
+    let canvas_mutex = Arc::new(Mutex::new(Canvas { iterations, image: ImageBuffer::new(width, height) }));
+
+    let mut futures = Vec::new();
+
+    for y in 0..height {
+        futures.push(tokio::spawn(compute_and_set_row(
+            fractal.clone(),
+            colorer.clone(),
+            position.clone(),
+            canvas_mutex.clone(),
+            y
+        )));
+    }
+
+    futures::future::join_all(futures).await;
+
+    let canvas = match Arc::try_unwrap(canvas_mutex) {
+        Ok(canvas_mutex) => canvas_mutex.into_inner().unwrap(),
+        Err(_) => unreachable!("every row task has finished")
+    };
+
+    (canvas.iterations, canvas.image)
+}
+
+async fn render_with(
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    (width, height): (u32, u32),
+    position: Position
+) -> (IterationBuffer, RgbImage) {
+    render_into(fractal, colorer, IterationBuffer::new(width, height), position).await
+}
+
+// With `smooth`, the buffer also keeps each pixel's continuous escape value.
+pub async fn render(fractal: Arc<dyn Fractal>, colorer: Arc<dyn Colorer>, smooth: bool) -> (IterationBuffer, RgbImage) {
+    render_into(fractal, colorer, IterationBuffer::for_render(smooth), Arc::new(coordinate_to_complex)).await
+}
+
+// Rows of a render under a time budget are started interlaced in groups of this many, so that the
+// ones finished when time runs out are spread evenly over the image.
+const BUDGET_INTERLACE: u32 = 16;
+
+// Like render, but starting no row after the deadline, other than the first. The rows are taken
+// coarse to fine, and each left out is copied from the nearest rendered one above it and marked as
+// copied in the buffer.
+pub async fn render_within(
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    smooth: bool,
+    deadline: Instant
+) -> (IterationBuffer, RgbImage) {
+    let order = interlace_offsets(BUDGET_INTERLACE)
+        .flat_map(|offset| (offset..IMG_HEIGHT).step_by(BUDGET_INTERLACE as usize))
+        .collect::<Vec<_>>();
+
+    let (order, position) = (Arc::new(order), Arc::new(coordinate_to_complex) as Position);
+
+    // Tasks take the next row in order when they start, however they're scheduled, so the rows done
+    // are always the first ones.
+    let next = Arc::new(AtomicUsize::new(0));
+
+    let futures = (0..IMG_HEIGHT).map(|_| {
+        let (fractal, order, next, position) = (fractal.clone(), order.clone(), next.clone(), position.clone());
+
+        tokio::spawn(async move {
+            let i = next.fetch_add(1, Ordering::Relaxed);
+
+            if i > 0 && Instant::now() >= deadline {
+                return None;
+            }
+
+            let y = order[i];
+
+            Some((y, compute_row(fractal.as_ref(), &position, IMG_WIDTH, y)))
+        })
+    });
+
+    let mut rows = vec![None; IMG_HEIGHT as usize];
+
+    for row in futures::future::join_all(futures).await {
+        if let Some((y, row)) = row.expect("a row task panicked") {
+            rows[y as usize] = Some(row);
+        }
+    }
+
+    let mut iterations = IterationBuffer::for_render(smooth);
+
+    let mut image = ImageBuffer::new(IMG_WIDTH, IMG_HEIGHT);
+
+    let escape = fractal.escape();
+
+    let mut above = 0;
+
+    for y in 0..IMG_HEIGHT {
+        if rows[y as usize].is_some() {
+            above = y;
+        } else {
+            iterations.set_copied(y);
+        }
+
+        // Row 0 is always rendered, so there's always one above.
+        let row = rows[above as usize].as_ref().expect("the nearest row above was rendered");
+
+        for (x, sample) in row.iter().enumerate() {
+            let x = x as u32;
+
+            iterations.set(x, y, sample);
+
+            if smooth {
+                iterations.set_smooth(x, y, sample.smooth_iterations(escape));
+            }
+
+            image.put_pixel(x, y, colorer.color(sample));
+        }
+    }
+
+    (iterations, image)
+}
+
+// How many times coarser the exploratory pass of auto_iterations is than the render each way, and
+// the cap of its pixels.
+const AUTO_SCALE: u32 = 8;
+const AUTO_CEILING: u32 = 16384;
+
+// The fraction of the pass's escaping pixels allowed to take longer than the count picked from it.
+const AUTO_TAIL: f64 = 1e-3;
+
+// The count by which all but AUTO_TAIL of a pass's escaping pixels, the ones nearest the boundary
+// last, have escaped, doubled because a render's finer pixels land closer to it still. Falls back
+// to the usual cap if nothing in the pass escapes.
+fn auto_limit(histogram: &[u64]) -> u32 {
+    let escaped = histogram.iter().sum::<u64>();
+
+    let mut remaining = (escaped as f64 * AUTO_TAIL) as u64;
+
+    let count = histogram.iter().rposition(|&pixels| {
+        let past = remaining < pixels;
+
+        remaining = remaining.saturating_sub(pixels);
+
+        past
+    });
+
+    match count {
+        Some(count) => (count as u32).saturating_mul(2).clamp(1, AUTO_CEILING),
+        None => BAILOUT_ITERATIONS
+    }
+}
+
+// An iteration cap for render, picked by auto_limit from a pass AUTO_SCALE times coarser that goes
+// up to AUTO_CEILING. Returns the size of the pass too.
+pub async fn auto_iterations(fractal: Arc<dyn Fractal>) -> (u32, (u32, u32)) {
+    let size = (IMG_WIDTH.div_ceil(AUTO_SCALE), IMG_HEIGHT.div_ceil(AUTO_SCALE));
+
+    let position = Arc::new(|(x, y): (u32, u32)| {
+        coordinate_to_complex((
+            (x * AUTO_SCALE + AUTO_SCALE / 2).min(IMG_WIDTH - 1),
+            (y * AUTO_SCALE + AUTO_SCALE / 2).min(IMG_HEIGHT - 1)
+        ))
+    });
+
+    let fractal = Arc::new(Limited { fractal, iterations: AUTO_CEILING });
+
+    let histogram = render_with(fractal, Arc::new(MembershipColorer), size, position).await.0.histogram();
+
+    (auto_limit(&histogram), size)
+}
+
+// The same for a view rendered at the given size.
+pub async fn view_auto_iterations(fractal: Arc<dyn Fractal>, size: (u32, u32), view: View) -> u32 {
+    let size = (size.0.div_ceil(AUTO_SCALE), size.1.div_ceil(AUTO_SCALE));
+
+    let fractal = Arc::new(Limited { fractal, iterations: AUTO_CEILING });
+
+    auto_limit(&render_view_iterations(fractal, size, view).await.histogram())
+}
+
+// Colors a buffer again, as rendering it would have with the given colorer.
+pub fn recolor(iterations: &IterationBuffer, colorer: &dyn Colorer) -> RgbImage {
+    ImageBuffer::from_fn(iterations.width(), iterations.height(), |x, y| colorer.color(&iterations.sample(x, y)))
+}
+
+// The cap of the first pass of an escalating render, and how many times higher each pass's is.
+const ESCALATION_START: u32 = 256;
+const ESCALATION_FACTOR: u32 = 4;
+
+// The fraction of the pixels a pass has to let escape for another to be worth it, by default.
+pub const ESCALATION_TOLERANCE: f64 = 1e-4;
+
+// Runs every row through a pass on threads of their own, keeping the rows in order.
+async fn escalation_pass<T: Send + 'static>(
+    rows: Vec<Vec<T>>,
+    pass: Arc<dyn Fn(T) -> Sample + Send + Sync>
+) -> Vec<Vec<Sample>> {
+    let futures = rows.into_iter().map(|row| {
+        let pass = pass.clone();
+
+        tokio::spawn(async move { row.into_iter().map(|sample| pass(sample)).collect::<Vec<_>>() })
+    });
+
+    futures::future::join_all(futures).await.into_iter().map(|row| row.expect("a row task panicked")).collect()
+}
+
+// Like render, but with a cap that starts low and rises each pass towards `target`, every pass only
+// resuming the pixels the last left capped, from where they stopped. A whole render to the target
+// spends it on every member; here, once a pass lets no more than `tolerance` of the image's pixels
+// escape or converge, the rest are taken as members, as they are once a pass ends after the
+// deadline. Returns the cap of the last pass too.
+pub async fn render_escalating(
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    smooth: bool,
+    target: u32,
+    tolerance: f64,
+    deadline: Option<Instant>
+) -> (IterationBuffer, RgbImage, u32) {
+    let mut limit = ESCALATION_START.min(target);
+
+    let points = (0..IMG_HEIGHT).map(|y| (0..IMG_WIDTH).map(|x| coordinate_to_complex((x, y))).collect()).collect();
+
+    let first = fractal.clone();
+
+    let mut rows = escalation_pass(points, Arc::new(move |c| first.sample_with_limit(c, limit))).await;
+
+    let capped = |rows: &[Vec<Sample>]| rows.iter().flatten().filter(|sample| sample.termination.is_none()).count();
+
+    let mut remaining = capped(&rows);
+
+    eprintln!("escalate: {} pixels capped at {} iterations", remaining, limit);
+
+    while limit < target && remaining > 0 {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            eprintln!("budget: out of time, so the {} pixels capped at {} iterations are members", remaining, limit);
+
+            break;
+        }
+
+        limit = limit.saturating_mul(ESCALATION_FACTOR).min(target);
+
+        let fractal = fractal.clone();
+
+        rows = escalation_pass(rows, Arc::new(move |sample| fractal.resume(sample, limit))).await;
+
+        let freed = remaining - capped(&rows);
+
+        remaining -= freed;
+
+        eprintln!("escalate: {} pixels capped at {} iterations", remaining, limit);
+
+        if freed as f64 <= tolerance * IMG_WIDTH as f64 * IMG_HEIGHT as f64 {
+            break;
+        }
+    }
+
+    let mut iterations = IterationBuffer::for_render(smooth);
+
+    let mut image = ImageBuffer::new(IMG_WIDTH, IMG_HEIGHT);
+
+    let escape = fractal.escape();
+
+    for (y, row) in rows.iter().enumerate() {
+        for (x, sample) in row.iter().enumerate() {
+            let (x, y) = (x as u32, y as u32);
+
+            iterations.set(x, y, sample);
+
+            if smooth {
+                iterations.set_smooth(x, y, sample.smooth_iterations(escape));
+            }
+
+            image.put_pixel(x, y, colorer.color(sample));
+        }
+    }
+
+    (iterations, image, limit)
+}
+
+// Renders the set unrolled around the origin, with θ increasing to the right and log r upward.
+pub async fn render_log_polar(fractal: Arc<dyn Fractal>, colorer: Arc<dyn Colorer>, origin: Complex<f32>) -> RgbImage {
+    let (_, image) = render_with(
+        fractal,
+        colorer,
+        (IMG_WIDTH, log_polar_height()),
+        Arc::new(move |coordinate| log_polar_to_complex(origin, coordinate))
+    )
+    .await;
+
+    image
+}
+
+// The offsets of the n-th jittered sample within a pixel, from the R2 low-discrepancy sequence, so
+// any number of samples covers the pixel evenly and the same samples are taken in every frame.
+fn jitter(n: u32) -> (f32, f32) {
+    const ALPHA: (f64, f64) = (0.754_877_666_246_692_7, 0.569_840_290_998_053_3);
+
+    (
+        ((0.5 + ALPHA.0 * n as f64) % 1.0) as f32,
+        ((0.5 + ALPHA.1 * n as f64) % 1.0) as f32
+    )
+}
+
+// The membership of the middle of every pixel of the view, without coloring it.
+pub async fn render_view_iterations(fractal: Arc<dyn Fractal>, size: (u32, u32), view: View) -> IterationBuffer {
+    let position = Arc::new(move |(x, y): (u32, u32)| view.pixel_to_complex(size, (x as f32 + 0.5, y as f32 + 0.5)));
+
+    render_with(fractal, Arc::new(MembershipColorer), size, position).await.0
+}
+
+// Renders the view with the given number of jittered samples per pixel, averaging their colors.
+pub async fn render_view(
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    size: (u32, u32),
+    view: View,
+    samples: u32
+) -> RgbImage {
+    render_view_rows(fractal, colorer, size, view, samples, Arc::new((0..size.1).collect())).await
+}
+
+// The offsets 0, 8, 4, 12, 2, ... within every group of `stride` rows, a power of two, in which each
+// splits the widest gap left by those before it. Rendering the rows at each offset in turn shows the
+// whole image coarsely first and then ever more finely.
+pub fn interlace_offsets(stride: u32) -> impl Iterator<Item = u32> {
+    let bits = stride.trailing_zeros();
+
+    (0..stride).map(move |i| i.reverse_bits().checked_shr(u32::BITS - bits).unwrap_or(0))
+}
+
+// Maps a pixel and an offset within it, from 0 to 1 across and down, to the point sampled there.
+type Sampler = Arc<dyn Fn((u32, u32), (f32, f32)) -> Complex<f32> + Send + Sync>;
+
+// Renders with the given number of jittered samples per pixel, averaging their colors.
+async fn render_averaged(
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    size: (u32, u32),
+    samples: u32,
+    sampler: Sampler
+) -> RgbImage {
+    debug_assert!(samples > 0, "averaging needs at least one sample");
+
+    // Sums of 64 bits can't overflow however many samples there are.
+    let mut sums = vec![0u64; buffer_len(size, 3).expect("the image is too large to address")];
+
+    for n in 0..samples {
+        let offset = if samples == 1 { (0.5, 0.5) } else { jitter(n) };
+
+        let sampler = sampler.clone();
+
+        let (_, image) =
+            render_with(fractal.clone(), colorer.clone(), size, Arc::new(move |pixel| sampler(pixel, offset))).await;
+
+        for (sum, &channel) in sums.iter_mut().zip(image.as_raw()) {
+            *sum += channel as u64;
+        }
+    }
+
+    let samples = samples as u64;
+
+    ImageBuffer::from_raw(size.0, size.1, sums.iter().map(|&sum| ((sum + samples / 2) / samples) as u8).collect())
+        .expect("the buffer matches the image size")
+}
+
+// Like render_view, but only the given rows of it, stacked in that order.
+pub async fn render_view_rows(
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    size: (u32, u32),
+    view: View,
+    samples: u32,
+    rows: Arc<Vec<u32>>
+) -> RgbImage {
+    let output = (size.0, rows.len() as u32);
+
+    let sampler = move |(x, i): (u32, u32), (dx, dy): (f32, f32)| {
+        view.pixel_to_complex(size, (x as f32 + dx, rows[i as usize] as f32 + dy))
+    };
+
+    render_averaged(fractal, colorer, output, samples, Arc::new(sampler)).await
+}
+
+// The size of an image of the rectangle from low to high whose longer side is `resolution` pixels,
+// with pixels as nearly square as whole numbers of them allow.
+pub fn rect_size((low, high): (Complex<f32>, Complex<f32>), resolution: u32) -> (u32, u32) {
+    let span = high - low;
+
+    let shorter = |a: f32, b: f32| ((resolution as f32 * a / b).round() as u32).max(1);
+
+    if span.re >= span.im {
+        (resolution, shorter(span.im, span.re))
+    } else {
+        (shorter(span.re, span.im), resolution)
+    }
+}
+
+// Renders exactly the rectangle from low to high, its edges those of the image, where a view would
+// widen it to keep pixels square.
+pub async fn render_rect(
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    size: (u32, u32),
+    rect: (Complex<f32>, Complex<f32>),
+    samples: u32
+) -> RgbImage {
+    render_rect_rows(fractal, colorer, size, rect, samples, 0..size.1).await
+}
+
+// Like render_rect, but only the given rows of it.
+pub async fn render_rect_rows(
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    size: (u32, u32),
+    rect: (Complex<f32>, Complex<f32>),
+    samples: u32,
+    rows: Range<u32>
+) -> RgbImage {
+    render_rect_region(fractal, colorer, size, rect, samples, 0..size.0, rows).await
+}
+
+// Like render_rect, but only the given columns of the given rows of it, every pixel exactly as it
+// comes out in the whole.
+pub async fn render_rect_region(
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    size: (u32, u32),
+    (low, high): (Complex<f32>, Complex<f32>),
+    samples: u32,
+    columns: Range<u32>,
+    rows: Range<u32>
+) -> RgbImage {
+    let (width, height) = (size.0 as f32, size.1 as f32);
+
+    let span = high - low;
+
+    let output = (columns.len() as u32, rows.len() as u32);
+
+    let sampler = move |(i, j): (u32, u32), (dx, dy): (f32, f32)| {
+        let (x, y) = ((columns.start + i) as f32, (rows.start + j) as f32);
+
+        Complex::new(low.re + (x + dx) / width * span.re, high.im - (y + dy) / height * span.im)
+    };
+
+    render_averaged(fractal, colorer, output, samples, Arc::new(sampler)).await
+}
+
+// The largest factor downscale takes, past which a block's sums could overflow.
+pub const MAX_DOWNSCALE: u32 = 1024;
+
+// Shrinks an image by a whole factor, each pixel the mean of the block of factor×factor pixels it
+// covers. The right and bottom edges keep the blocks cut short by an image that doesn't divide
+// evenly, averaging only the pixels they have.
+pub fn downscale(image: &RgbImage, factor: u32) -> RgbImage {
+    let (width, height) = image.dimensions();
+
+    ImageBuffer::from_fn(width.div_ceil(factor), height.div_ceil(factor), |x, y| {
+        let (xs, ys) = (x * factor..((x + 1) * factor).min(width), y * factor..((y + 1) * factor).min(height));
+
+        let count = (xs.len() * ys.len()) as u32;
+
+        let mut sum = [0; 3];
+
+        for y in ys {
+            for x in xs.clone() {
+                for (sum, channel) in sum.iter_mut().zip(image.get_pixel(x, y).0) {
+                    *sum += channel as u32;
+                }
+            }
+        }
+
+        Rgb(sum.map(|sum| ((sum + count / 2) / count) as u8))
+    })
+}
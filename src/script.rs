@@ -0,0 +1,565 @@
+// Fractal definition scripts.
+//
+// A script is a list of `key = expression` lines (`#` starts a comment):
+//
+//     start = 0
+//     step = z^2 + c
+//     escape = |z| > 2
+//     converge = |z - 1| < 0.001
+//     red = n / limit
+//     green = 0
+//     blue = escaped * 0.5
+//
+// Only `step` is required. Iteration stops once `escape` or `converge` is nonzero; converged points
+// count as members of the set. Expressions are evaluated over complex numbers and may use `z`, `c`,
+// `i` (the imaginary unit), `n` (the iterations so far), `limit` (the iteration limit) and
+// `escaped` (1 if the point escaped, 0 otherwise). Comparisons yield 1 or 0 and compare real parts.
+// The color channels range over 0 to 1; if none are given, members are white and escapees black.
+
+use crate::fractal::{Fractal, Sample, Termination, BAILOUT_ITERATIONS};
+use crate::render::{Colorer, MembershipColorer};
+use image::Rgb;
+use num::complex::Complex;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug)]
+pub struct ScriptError {
+    line: usize,
+    message: String
+}
+
+impl ScriptError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self { line, message: message.into() }
+    }
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ScriptError {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Variable {
+    Z,
+    C,
+    Iterations,
+    Limit,
+    Escaped
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Function {
+    Abs,
+    Re,
+    Im,
+    Arg,
+    Conj,
+    Exp,
+    Ln,
+    Sqrt,
+    Sin,
+    Cos,
+    Floor
+}
+
+impl Function {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "abs" => Self::Abs,
+            "re" => Self::Re,
+            "im" => Self::Im,
+            "arg" => Self::Arg,
+            "conj" => Self::Conj,
+            "exp" => Self::Exp,
+            "ln" => Self::Ln,
+            "sqrt" => Self::Sqrt,
+            "sin" => Self::Sin,
+            "cos" => Self::Cos,
+            "floor" => Self::Floor,
+            _ => return None
+        })
+    }
+
+    fn apply(self, x: Complex<f32>) -> Complex<f32> {
+        match self {
+            Self::Abs => Complex::new(x.norm(), 0.0),
+            Self::Re => Complex::new(x.re, 0.0),
+            Self::Im => Complex::new(x.im, 0.0),
+            Self::Arg => Complex::new(x.arg(), 0.0),
+            Self::Conj => x.conj(),
+            Self::Exp => x.exp(),
+            Self::Ln => x.ln(),
+            Self::Sqrt => x.sqrt(),
+            Self::Sin => x.sin(),
+            Self::Cos => x.cos(),
+            Self::Floor => Complex::new(x.re.floor(), x.im.floor())
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Power,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual
+}
+
+impl Operator {
+    fn apply(self, a: Complex<f32>, b: Complex<f32>) -> Complex<f32> {
+        let truth = |value: bool| Complex::new(if value { 1.0 } else { 0.0 }, 0.0);
+
+        match self {
+            Self::Add => a + b,
+            Self::Subtract => a - b,
+            Self::Multiply => a * b,
+            Self::Divide => a / b,
+            Self::Power => {
+                if b.im == 0.0 && b.re.fract() == 0.0 && b.re.abs() <= i32::MAX as f32 {
+                    a.powi(b.re as i32)
+                } else {
+                    a.powc(b)
+                }
+            }
+            Self::Less => truth(a.re < b.re),
+            Self::LessEqual => truth(a.re <= b.re),
+            Self::Greater => truth(a.re > b.re),
+            Self::GreaterEqual => truth(a.re >= b.re)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expression {
+    Constant(Complex<f32>),
+    Variable(Variable),
+    Negate(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    Call(Function, Box<Expression>)
+}
+
+struct Environment {
+    z: Complex<f32>,
+    c: Complex<f32>,
+    iterations: u32,
+    limit: u32,
+    escaped: bool
+}
+
+impl Expression {
+    fn evaluate(&self, environment: &Environment) -> Complex<f32> {
+        match self {
+            Self::Constant(value) => *value,
+            Self::Variable(variable) => match variable {
+                Variable::Z => environment.z,
+                Variable::C => environment.c,
+                Variable::Iterations => Complex::new(environment.iterations as f32, 0.0),
+                Variable::Limit => Complex::new(environment.limit as f32, 0.0),
+                Variable::Escaped => Complex::new(if environment.escaped { 1.0 } else { 0.0 }, 0.0)
+            },
+            Self::Negate(operand) => -operand.evaluate(environment),
+            Self::Binary(operator, a, b) => operator.apply(a.evaluate(environment), b.evaluate(environment)),
+            Self::Call(function, argument) => function.apply(argument.evaluate(environment))
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f32),
+    Identifier(String),
+    Symbol(&'static str)
+}
+
+const SYMBOLS: [&str; 12] = ["<=", ">=", "<", ">", "+", "-", "*", "/", "^", "(", ")", "|"];
+
+// Expressions are parsed, evaluated, and dropped recursively, so how deeply they nest, and their
+// length, which bounds how deep a long chain of operators makes them, are capped to keep malformed
+// scripts from overflowing the stack.
+const MAX_DEPTH: usize = 64;
+const MAX_TOKENS: usize = 1000;
+
+fn tokenize(source: &str, line: usize) -> Result<Vec<Token>, ScriptError> {
+    let mut tokens = Vec::new();
+
+    let mut rest = source.trim_start();
+
+    while !rest.is_empty() {
+        if tokens.len() == MAX_TOKENS {
+            return Err(ScriptError::new(line, format!("expression longer than {} tokens", MAX_TOKENS)));
+        }
+
+        let first = rest.chars().next().unwrap();
+
+        let length = if first.is_ascii_digit() || first == '.' {
+            let length = rest
+                .find(|ch: char| !(ch.is_ascii_digit() || ch == '.'))
+                .unwrap_or(rest.len());
+
+            let number = rest[..length]
+                .parse()
+                .map_err(|_| ScriptError::new(line, format!("invalid number `{}`", &rest[..length])))?;
+
+            tokens.push(Token::Number(number));
+
+            length
+        } else if first.is_ascii_alphabetic() || first == '_' {
+            let length = rest
+                .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_'))
+                .unwrap_or(rest.len());
+
+            tokens.push(Token::Identifier(rest[..length].to_string()));
+
+            length
+        } else if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(**symbol)) {
+            tokens.push(Token::Symbol(symbol));
+
+            symbol.len()
+        } else {
+            return Err(ScriptError::new(line, format!("unexpected character `{}`", first)));
+        };
+
+        rest = rest[length..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    line: usize,
+    depth: usize
+}
+
+impl Parser {
+    fn parse(source: &str, line: usize) -> Result<Expression, ScriptError> {
+        let mut parser = Self {
+            tokens: tokenize(source, line)?,
+            position: 0,
+            line,
+            depth: 0
+        };
+
+        let expression = parser.comparison()?;
+
+        match parser.peek() {
+            None => Ok(expression),
+            Some(token) => Err(parser.error(format!("unexpected token {:?}", token)))
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> ScriptError {
+        ScriptError::new(self.line, message)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+
+        self.position += 1;
+
+        token
+    }
+
+    fn accept(&mut self, symbols: &[&'static str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Symbol(symbol)) if symbols.contains(symbol) => {
+                let symbol = *symbol;
+
+                self.position += 1;
+
+                Some(symbol)
+            }
+
+            _ => None
+        }
+    }
+
+    fn expect(&mut self, symbol: &'static str) -> Result<(), ScriptError> {
+        self.accept(&[symbol])
+            .map(|_| ())
+            .ok_or_else(|| self.error(format!("expected `{}`", symbol)))
+    }
+
+    fn comparison(&mut self) -> Result<Expression, ScriptError> {
+        let a = self.sum()?;
+
+        let operator = match self.accept(&["<=", ">=", "<", ">"]) {
+            Some("<=") => Operator::LessEqual,
+            Some(">=") => Operator::GreaterEqual,
+            Some("<") => Operator::Less,
+            Some(">") => Operator::Greater,
+            _ => return Ok(a)
+        };
+
+        Ok(Expression::Binary(operator, Box::new(a), Box::new(self.sum()?)))
+    }
+
+    fn sum(&mut self) -> Result<Expression, ScriptError> {
+        let mut a = self.product()?;
+
+        while let Some(symbol) = self.accept(&["+", "-"]) {
+            let operator = if symbol == "+" { Operator::Add } else { Operator::Subtract };
+
+            a = Expression::Binary(operator, Box::new(a), Box::new(self.product()?));
+        }
+
+        Ok(a)
+    }
+
+    fn product(&mut self) -> Result<Expression, ScriptError> {
+        let mut a = self.unary()?;
+
+        while let Some(symbol) = self.accept(&["*", "/"]) {
+            let operator = if symbol == "*" { Operator::Multiply } else { Operator::Divide };
+
+            a = Expression::Binary(operator, Box::new(a), Box::new(self.unary()?));
+        }
+
+        Ok(a)
+    }
+
+    // Every nested expression is parsed through here, so it's where the depth is counted.
+    fn unary(&mut self) -> Result<Expression, ScriptError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(format!("expression nested more than {} deep", MAX_DEPTH)));
+        }
+
+        self.depth += 1;
+
+        let expression = if self.accept(&["-"]).is_some() {
+            self.unary().map(|expression| Expression::Negate(Box::new(expression)))
+        } else {
+            self.power()
+        };
+
+        self.depth -= 1;
+
+        expression
+    }
+
+    fn power(&mut self) -> Result<Expression, ScriptError> {
+        let base = self.atom()?;
+
+        if self.accept(&["^"]).is_some() {
+            Ok(Expression::Binary(Operator::Power, Box::new(base), Box::new(self.unary()?)))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expression, ScriptError> {
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Expression::Constant(Complex::new(value, 0.0))),
+            Some(Token::Identifier(name)) => self.identifier(&name),
+            Some(Token::Symbol("(")) => {
+                let expression = self.comparison()?;
+
+                self.expect(")")?;
+
+                Ok(expression)
+            }
+
+            Some(Token::Symbol("|")) => {
+                let expression = self.sum()?;
+
+                self.expect("|")?;
+
+                Ok(Expression::Call(Function::Abs, Box::new(expression)))
+            }
+
+            Some(token) => Err(self.error(format!("unexpected token {:?}", token))),
+            None => Err(self.error("unexpected end of expression"))
+        }
+    }
+
+    fn identifier(&mut self, name: &str) -> Result<Expression, ScriptError> {
+        let variable = match name {
+            "i" => return Ok(Expression::Constant(Complex::new(0.0, 1.0))),
+            "z" => Variable::Z,
+            "c" => Variable::C,
+            "n" => Variable::Iterations,
+            "limit" => Variable::Limit,
+            "escaped" => Variable::Escaped,
+            _ => {
+                let function = Function::from_name(name)
+                    .ok_or_else(|| self.error(format!("unknown identifier `{}`", name)))?;
+
+                self.expect("(")?;
+
+                let argument = self.comparison()?;
+
+                self.expect(")")?;
+
+                return Ok(Expression::Call(function, Box::new(argument)));
+            }
+        };
+
+        Ok(Expression::Variable(variable))
+    }
+}
+
+pub struct Script {
+    start: Option<Expression>,
+    step: Expression,
+    escape: Option<Expression>,
+    converge: Option<Expression>,
+    color: Option<[Expression; 3]>,
+    limit: u32
+}
+
+impl Script {
+    pub fn parse(source: &str) -> Result<Self, ScriptError> {
+        let mut start = None;
+        let mut step = None;
+        let mut escape = None;
+        let mut converge = None;
+        let mut color = [None, None, None];
+
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+
+            let line = line.split('#').next().unwrap().trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            let (key, expression) = line
+                .split_once('=')
+                .ok_or_else(|| ScriptError::new(line_number, "expected `key = expression`"))?;
+
+            let expression = Parser::parse(expression, line_number)?;
+
+            let slot = match key.trim() {
+                "start" => &mut start,
+                "step" => &mut step,
+                "escape" => &mut escape,
+                "converge" => &mut converge,
+                "red" => &mut color[0],
+                "green" => &mut color[1],
+                "blue" => &mut color[2],
+                key => return Err(ScriptError::new(line_number, format!("unknown key `{}`", key)))
+            };
+
+            if slot.replace(expression).is_some() {
+                return Err(ScriptError::new(line_number, format!("`{}` is defined twice", key.trim())));
+            }
+        }
+
+        let color = if color.iter().any(Option::is_some) {
+            Some(color.map(|channel| channel.unwrap_or(Expression::Constant(Complex::new(0.0, 0.0)))))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            start,
+            step: step.ok_or_else(|| ScriptError::new(source.lines().count(), "missing `step`"))?,
+            escape,
+            converge,
+            color,
+            limit: BAILOUT_ITERATIONS
+        })
+    }
+
+    // The iteration limit `limit` evaluates to, the usual one unless the render runs to another.
+    pub fn with_limit(self, limit: u32) -> Self {
+        Self { limit, ..self }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+impl Script {
+    // What the expressions see at z for c after the given number of iterations.
+    fn environment(&self, z: Complex<f32>, c: Complex<f32>, iterations: u32) -> Environment {
+        Environment { z, c, iterations, limit: self.limit, escaped: false }
+    }
+
+    fn terminated_in(&self, environment: &Environment) -> Option<Termination> {
+        let escaped = match &self.escape {
+            Some(escape) => escape.evaluate(environment).re != 0.0,
+            None => self.escape().escaped(environment.z)
+        };
+
+        if escaped {
+            Some(Termination::Escaped)
+        } else if self.converge.as_ref().is_some_and(|converge| converge.evaluate(environment).re != 0.0) {
+            Some(Termination::Converged)
+        } else {
+            None
+        }
+    }
+}
+
+// Only resume knows how many iterations came before, so `step` and `terminated` on their own see `n` as
+// 0, as it is at the start.
+impl Fractal for Script {
+    fn start(&self, c: Complex<f32>) -> Complex<f32> {
+        match &self.start {
+            Some(start) => start.evaluate(&self.environment(Complex::new(0.0, 0.0), c, 0)),
+            None => Complex::new(0.0, 0.0)
+        }
+    }
+
+    fn step(&self, z: Complex<f32>, c: Complex<f32>) -> Complex<f32> {
+        self.step.evaluate(&self.environment(z, c, 0))
+    }
+
+    fn terminated(&self, z: Complex<f32>, c: Complex<f32>) -> Option<Termination> {
+        self.terminated_in(&self.environment(z, c, 0))
+    }
+
+    fn resume(&self, sample: Sample, limit: u32) -> Sample {
+        let Sample { c, mut z, iterations: mut i, mut termination } = sample;
+
+        while termination.is_none() && i < limit {
+            z = self.step.evaluate(&self.environment(z, c, i));
+
+            i += 1;
+
+            termination = self.terminated_in(&self.environment(z, c, i));
+        }
+
+        Sample { c, z, iterations: i, termination }
+    }
+}
+
+impl Colorer for Script {
+    fn color(&self, sample: &Sample) -> Rgb<u8> {
+        match &self.color {
+            Some(channels) => {
+                let environment = Environment {
+                    z: sample.z,
+                    c: sample.c,
+                    iterations: sample.iterations,
+                    limit: self.limit,
+                    escaped: !sample.is_member()
+                };
+
+                Rgb::<u8>(channels.each_ref().map(|channel| {
+                    (channel.evaluate(&environment).re.clamp(0.0, 1.0) * 255.0).round() as u8
+                }))
+            }
+
+            None => MembershipColorer.color(sample)
+        }
+    }
+}
@@ -0,0 +1,512 @@
+// Serves slippy-map tiles of a fractal over HTTP, rendered on demand, so Leaflet or OpenLayers can
+// browse it as an infinite canvas, along with a JSON API for render and analysis jobs and Prometheus
+// metrics. Only as much of HTTP/1.1 as these clients need is implemented: one request per
+// connection, with a body only as long as its Content-Length says, or a WebSocket streaming a job's
+// progress.
+
+use crate::fractal::{Fractal, Limited};
+use crate::jobs::{Band, Job, Kind, Output, Parameters, State};
+use crate::json::Value;
+use crate::metrics::Metrics;
+use crate::render::{self, Colorer};
+use crate::viewport::View;
+use crate::websocket;
+use image::{DynamicImage, ImageOutputFormat};
+use num::complex::Complex;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+pub const PORT: u16 = 8080;
+// Only this machine, since anyone who can reach the server can make it run jobs.
+pub const ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+pub const TILE_SIZE: u32 = 256;
+pub const CACHE_TILES: usize = 1024;
+
+// Zoom level 0 is a single tile spanning the default view's width, centered on the origin. Past
+// this level, single precision can no longer tell neighboring pixels apart.
+pub const MAX_ZOOM: u32 = 20;
+
+// The largest request head and body that are read before giving up on a client.
+const MAX_REQUEST: usize = 8192;
+const MAX_BODY: usize = 65536;
+
+// Finished jobs past this many are forgotten, oldest first, along with their output.
+const KEPT_JOBS: usize = 64;
+
+// Jobs submitted while this many are running are turned away, since each can hold a large image.
+const RUNNING_JOBS: usize = 4;
+
+// How often a streamed job is checked for new bands and progress.
+const STREAM_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Tile {
+    pub z: u32,
+    pub x: u32,
+    pub y: u32
+}
+
+impl Tile {
+    // Parses `/{z}/{x}/{y}.png`, rejecting tiles outside the grid at that zoom.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let mut parts = path.strip_prefix('/')?.strip_suffix(".png")?.split('/');
+
+        let (z, x, y): (u32, u32, u32) =
+            (parts.next()?.parse().ok()?, parts.next()?.parse().ok()?, parts.next()?.parse().ok()?);
+
+        (parts.next().is_none() && z <= MAX_ZOOM && x < 1 << z && y < 1 << z).then_some(Self { z, x, y })
+    }
+
+    pub fn view(&self) -> View {
+        let width = View::default().width / (1u32 << self.z) as f32;
+
+        let corner = Complex::new(-View::default().width / 2.0, View::default().width / 2.0);
+
+        View {
+            center: corner + Complex::new((self.x as f32 + 0.5) * width, -(self.y as f32 + 0.5) * width),
+            width
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Settings {
+    pub port: u16,
+    pub address: IpAddr,
+    pub cache_tiles: usize,
+    // The iteration limit at zoom level 0.
+    pub iterations: u32,
+    // Extra iterations per zoom level, each of which doubles the magnification.
+    pub iteration_growth: u32,
+    pub samples: u32
+}
+
+// Keeps the most recently used tiles, evicting the least recently used one when full.
+struct TileCache {
+    capacity: usize,
+    tiles: HashMap<Tile, (Arc<Vec<u8>>, u64)>,
+    clock: u64
+}
+
+impl TileCache {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, tiles: HashMap::new(), clock: 0 }
+    }
+
+    fn get(&mut self, tile: Tile) -> Option<Arc<Vec<u8>>> {
+        self.clock += 1;
+
+        let clock = self.clock;
+
+        self.tiles.get_mut(&tile).map(|(png, used)| {
+            *used = clock;
+
+            png.clone()
+        })
+    }
+
+    fn insert(&mut self, tile: Tile, png: Arc<Vec<u8>>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.tiles.len() >= self.capacity && !self.tiles.contains_key(&tile) {
+            if let Some(&oldest) = self.tiles.iter().min_by_key(|(_, (_, used))| *used).map(|(tile, _)| tile) {
+                self.tiles.remove(&oldest);
+            }
+        }
+
+        self.clock += 1;
+
+        self.tiles.insert(tile, (png, self.clock));
+    }
+}
+
+struct Server {
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    settings: Settings,
+    cache: Mutex<TileCache>,
+    jobs: Mutex<Vec<Arc<Job>>>,
+    metrics: Metrics
+}
+
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: Arc<Vec<u8>>
+}
+
+impl Response {
+    fn text(status: &'static str, body: &str) -> Self {
+        Self { status, content_type: "text/plain; charset=utf-8", body: Arc::new(body.as_bytes().to_vec()) }
+    }
+
+    fn json(status: &'static str, value: &Value) -> Self {
+        Self { status, content_type: "application/json", body: Arc::new(format!("{}\n", value).into_bytes()) }
+    }
+
+    fn error(status: &'static str, message: &str) -> Self {
+        Self::json(status, &Value::object([("error", message.into())]))
+    }
+}
+
+impl Server {
+    async fn tile(&self, tile: Tile) -> Arc<Vec<u8>> {
+        if let Some(png) = self.cache.lock().unwrap().get(tile) {
+            self.metrics.cache_hits.fetch_add(1, Ordering::Relaxed);
+
+            return png;
+        }
+
+        self.metrics.cache_misses.fetch_add(1, Ordering::Relaxed);
+
+        let start = Instant::now();
+
+        // Held at the largest limit rather than wrapping around for a large --iteration-growth.
+        let iterations = self.settings.iterations.saturating_add(self.settings.iteration_growth.saturating_mul(tile.z));
+
+        let fractal = Arc::new(Limited { fractal: self.fractal.clone(), iterations });
+
+        let size = (TILE_SIZE, TILE_SIZE);
+
+        let image = render::render_view(fractal, self.colorer.clone(), size, tile.view(), self.settings.samples).await;
+
+        let mut png = Vec::new();
+
+        DynamicImage::ImageRgb8(image)
+            .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+            .expect("encoding into memory can't fail");
+
+        let png = Arc::new(png);
+
+        self.metrics.tile_seconds.observe(start.elapsed());
+
+        self.cache.lock().unwrap().insert(tile, png.clone());
+
+        png
+    }
+
+    fn job(&self, id: &str) -> Option<Arc<Job>> {
+        let id = id.parse::<u64>().ok()?;
+
+        self.jobs.lock().unwrap().iter().find(|job| job.id == id).cloned()
+    }
+
+    fn submit(self: &Arc<Self>, body: &[u8]) -> Response {
+        let parameters = std::str::from_utf8(body)
+            .map_err(|_| "the body isn't UTF-8".to_string())
+            .and_then(Value::parse)
+            .and_then(|value| Parameters::from_json(&value));
+
+        let parameters = match parameters {
+            Ok(parameters) => parameters,
+            Err(message) => return Response::error("400 Bad Request", &message)
+        };
+
+        let job = {
+            let mut jobs = self.jobs.lock().unwrap();
+
+            if jobs.iter().filter(|job| job.state() == State::Running).count() >= RUNNING_JOBS {
+                let message = format!("{} jobs are already running; try again once one finishes", RUNNING_JOBS);
+
+                return Response::error("503 Service Unavailable", &message);
+            }
+
+            // The newest job is never forgotten, so the ids carry on from it.
+            let finished = jobs.iter().filter(|job| job.state() != State::Running).count();
+
+            let mut forgotten = finished.saturating_sub(KEPT_JOBS);
+
+            jobs.retain(|job| {
+                let forget = forgotten > 0 && job.state() != State::Running;
+
+                forgotten -= forget as usize;
+
+                !forget
+            });
+
+            let job = Arc::new(Job::new(jobs.last().map_or(1, |job| job.id + 1), parameters));
+
+            jobs.push(job.clone());
+
+            job
+        };
+
+        let (server, running) = (self.clone(), job.clone());
+
+        tokio::spawn(async move {
+            let start = Instant::now();
+
+            running.run(server.fractal.clone(), server.colorer.clone()).await;
+
+            server.metrics.job_seconds.observe(start.elapsed());
+        });
+
+        Response::json("202 Accepted", &job.document())
+    }
+
+    // The output of a finished job, if it's of the kind asked for.
+    fn result(job: &Job, kind: Kind) -> Response {
+        if job.parameters.kind != kind {
+            return Response::error("404 Not Found", "the job doesn't produce this output");
+        }
+
+        match (job.state(), job.output()) {
+            (State::Done, Some(Output::Image(png))) => {
+                Response { status: "200 OK", content_type: "image/png", body: png }
+            }
+
+            (State::Done, Some(Output::Polar(document))) => Response::json("200 OK", &document),
+            (State::Cancelled, _) => Response::error("410 Gone", "the job was cancelled"),
+            _ => Response::error("409 Conflict", "the job is still running")
+        }
+    }
+
+    async fn respond(self: &Arc<Self>, method: &str, path: &str, body: &[u8]) -> Response {
+        let path = path.split('?').next().unwrap_or(path);
+
+        let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+
+        match (method, segments.as_slice()) {
+            ("POST", ["jobs"]) => self.submit(body),
+
+            ("GET", ["jobs"]) => {
+                let jobs = self.jobs.lock().unwrap().clone();
+
+                Response::json("200 OK", &Value::array(jobs.iter().map(|job| job.document())))
+            }
+
+            (_, ["jobs", id, ..]) => {
+                let Some(job) = self.job(id) else {
+                    return Response::error("404 Not Found", "no such job");
+                };
+
+                match (method, &segments[2..]) {
+                    ("GET", []) => Response::json("200 OK", &job.document()),
+
+                    ("DELETE", []) => {
+                        job.cancel();
+
+                        Response::json("200 OK", &job.document())
+                    }
+
+                    ("GET", ["image.png"]) => Self::result(&job, Kind::Render),
+                    ("GET", ["polar.json"]) => Self::result(&job, Kind::Analyze),
+                    ("GET", ["stream"]) => Response::error("426 Upgrade Required", "connect with a WebSocket"),
+
+                    (_, [] | ["image.png"] | ["polar.json"] | ["stream"]) => {
+                        Response::error("405 Method Not Allowed", "unsupported method")
+                    }
+
+                    _ => Response::error("404 Not Found", "expected /jobs/{id}/image.png or /jobs/{id}/polar.json")
+                }
+            }
+
+            ("GET", ["metrics"]) => {
+                let cached_tiles = self.cache.lock().unwrap().tiles.len();
+
+                let jobs = self.jobs.lock().unwrap().clone();
+
+                let jobs_in_flight = jobs.iter().filter(|job| job.state() == State::Running).count();
+
+                Response {
+                    status: "200 OK",
+                    content_type: "text/plain; version=0.0.4",
+                    body: Arc::new(self.metrics.text(cached_tiles, jobs_in_flight).into_bytes())
+                }
+            }
+
+            ("GET", _) => match Tile::from_path(path) {
+                Some(tile) => {
+                    let png = self.tile(tile).await;
+
+                    self.metrics.tiles_served.fetch_add(1, Ordering::Relaxed);
+
+                    Response { status: "200 OK", content_type: "image/png", body: png }
+                }
+
+                None => Response::text("404 Not Found", "expected /{z}/{x}/{y}.png, /jobs, or /metrics\n")
+            },
+
+            _ => Response::text("405 Method Not Allowed", "unsupported method\n")
+        }
+    }
+
+    // Pushes the job's progress, and each band of a render as it finishes, until the job ends. A
+    // band is sent as a text message giving its position, followed by a binary one holding its PNG.
+    // The bands are dropped when the job ends, so a finished render is last sent whole, as one band.
+    async fn stream(&self, job: Arc<Job>, mut stream: TcpStream, key: &str) -> std::io::Result<()> {
+        let handshake = [
+            "HTTP/1.1 101 Switching Protocols".to_string(),
+            "Upgrade: websocket".to_string(),
+            "Connection: Upgrade".to_string(),
+            format!("Sec-WebSocket-Accept: {}\r\n\r\n", websocket::accept_key(key))
+        ]
+        .join("\r\n");
+
+        stream.write_all(handshake.as_bytes()).await?;
+
+        let (mut sent, mut last) = (0, None);
+
+        loop {
+            let state = job.state();
+
+            let mut bands = job.bands(sent);
+
+            if let (State::Done, Some(Output::Image(png))) = (state, job.output()) {
+                bands = vec![Band { top: 0, stride: 1, rows: job.parameters.size.1, png }];
+            }
+
+            for band in bands {
+                let position = Value::object([
+                    ("event", "band".into()),
+                    ("top", band.top.into()),
+                    ("stride", band.stride.into()),
+                    ("rows", band.rows.into())
+                ]);
+
+                stream.write_all(&websocket::text(&position.to_string())).await?;
+                stream.write_all(&websocket::binary(&band.png)).await?;
+
+                sent += 1;
+            }
+
+            let progress = Value::object([
+                ("event", "progress".into()),
+                ("state", state.name().into()),
+                ("progress", job.progress().into())
+            ]);
+
+            if last.as_ref() != Some(&progress) {
+                stream.write_all(&websocket::text(&progress.to_string())).await?;
+
+                last = Some(progress);
+            }
+
+            if state != State::Running {
+                break;
+            }
+
+            tokio::time::sleep(STREAM_INTERVAL).await;
+        }
+
+        stream.write_all(&websocket::close()).await?;
+        stream.shutdown().await
+    }
+
+    async fn handle(self: &Arc<Self>, mut stream: TcpStream) -> std::io::Result<()> {
+        let mut request = Vec::new();
+
+        let mut buffer = [0; 1024];
+
+        let end = loop {
+            if let Some(end) = request.windows(4).position(|window| window == b"\r\n\r\n") {
+                break end;
+            }
+
+            let read = stream.read(&mut buffer).await?;
+
+            if read == 0 || request.len() > MAX_REQUEST {
+                return Ok(());
+            }
+
+            request.extend_from_slice(&buffer[..read]);
+        };
+
+        let head = String::from_utf8_lossy(&request[..end]).into_owned();
+
+        let header = |name: &str| {
+            head.lines()
+                .filter_map(|line| line.split_once(':'))
+                .find(|(header, _)| header.trim().eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.trim())
+        };
+
+        let length = header("content-length").and_then(|value| value.parse::<usize>().ok()).unwrap_or(0);
+
+        if length > MAX_BODY {
+            return Ok(());
+        }
+
+        let mut body = request.split_off(end + 4);
+
+        while body.len() < length {
+            let read = stream.read(&mut buffer).await?;
+
+            if read == 0 {
+                return Ok(());
+            }
+
+            body.extend_from_slice(&buffer[..read]);
+        }
+
+        body.truncate(length);
+
+        let mut line = head.lines().next().unwrap_or_default().split_whitespace();
+
+        let (method, path) = (line.next(), line.next());
+
+        // Requests to stream a job upgrade to a WebSocket if the job exists, and otherwise get the
+        // same error as any other request for it.
+        if let (Some("GET"), Some(path), Some(key)) = (method, path, header("sec-websocket-key")) {
+            let id = path.strip_prefix("/jobs/").and_then(|path| path.strip_suffix("/stream"));
+
+            if let Some(job) = id.and_then(|id| self.job(id)) {
+                return self.stream(job, stream, key).await;
+            }
+        }
+
+        let response = match (method, path) {
+            (Some(method), Some(path)) => self.respond(method, path, &body).await,
+            _ => Response::text("400 Bad Request", "malformed request\n")
+        };
+
+        // Tiles and jobs are fetched from pages served elsewhere, so any origin may read them.
+        let header = [
+            format!("HTTP/1.1 {}", response.status),
+            format!("Content-Type: {}", response.content_type),
+            format!("Content-Length: {}", response.body.len()),
+            "Access-Control-Allow-Origin: *".to_string(),
+            "Connection: close\r\n\r\n".to_string()
+        ]
+        .join("\r\n");
+
+        stream.write_all(header.as_bytes()).await?;
+        stream.write_all(&response.body).await?;
+        stream.shutdown().await
+    }
+}
+
+// Runs until the process is stopped, handling every connection in its own task.
+pub async fn serve(fractal: Arc<dyn Fractal>, colorer: Arc<dyn Colorer>, settings: Settings) -> std::io::Result<()> {
+    let address = SocketAddr::new(settings.address, settings.port);
+
+    let listener = TcpListener::bind(address).await?;
+
+    let cache = Mutex::new(TileCache::new(settings.cache_tiles));
+
+    let (jobs, metrics) = (Mutex::new(Vec::new()), Metrics::default());
+
+    let server = Arc::new(Server { fractal, colorer, settings, cache, jobs, metrics });
+
+    eprintln!("serve: listening on http://{}/{{z}}/{{x}}/{{y}}.png", address);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+
+        let server = server.clone();
+
+        tokio::spawn(async move {
+            if let Err(error) = server.handle(stream).await {
+                eprintln!("serve: {}", error);
+            }
+        });
+    }
+}
@@ -0,0 +1,129 @@
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Smoothing {
+    MovingAverage(usize),
+    SavitzkyGolay(usize)
+}
+
+impl FromStr for Smoothing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, window) = s.split_once(':').unwrap_or((s, "5"));
+
+        let window = window
+            .parse::<usize>()
+            .ok()
+            .filter(|window| window % 2 == 1)
+            .ok_or_else(|| format!("smoothing window `{}` must be an odd number", window))?;
+
+        match name {
+            "moving-average" => Ok(Self::MovingAverage(window)),
+            "savitzky-golay" if window >= 5 => Ok(Self::SavitzkyGolay(window)),
+            "savitzky-golay" => Err("the Savitzky-Golay window must be at least 5".to_string()),
+            _ => Err(format!("unknown smoothing method `{}`", name))
+        }
+    }
+}
+
+impl Smoothing {
+    fn weights(self) -> Vec<f32> {
+        match self {
+            Self::MovingAverage(window) => vec![1.0 / window as f32; window],
+
+            // Quadratic least-squares fit over 2m + 1 points, evaluated at the center.
+            Self::SavitzkyGolay(window) => {
+                let m = (window / 2) as f32;
+
+                let denominator = (4.0 * m * m - 1.0) * (2.0 * m + 3.0);
+
+                (0..window)
+                    .map(|i| {
+                        let i = i as f32 - m;
+
+                        3.0 * (3.0 * m * m + 3.0 * m - 1.0 - 5.0 * i * i) / denominator
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    // r(θ) is periodic, so the window wraps around the ends of the profile.
+    pub fn apply(self, profile: &[(f32, f32)]) -> Vec<(f32, f32)> {
+        let weights = self.weights();
+
+        let n = profile.len() as isize;
+        let m = (weights.len() / 2) as isize;
+
+        profile
+            .iter()
+            .enumerate()
+            .map(|(i, &(theta, _))| {
+                let r = weights
+                    .iter()
+                    .enumerate()
+                    .map(|(j, weight)| weight * profile[(i as isize + j as isize - m).rem_euclid(n) as usize].1)
+                    .sum();
+
+                (theta, r)
+            })
+            .collect()
+    }
+}
+
+// Resamples a uniformly sampled periodic profile at `size` angles using a periodic cubic spline.
+pub fn interpolate(profile: &[(f32, f32)], size: usize) -> Vec<(f32, f32)> {
+    let n = profile.len();
+
+    if n < 3 {
+        return profile.to_vec();
+    }
+
+    let h = std::f32::consts::PI * 2.0 / n as f32;
+
+    let y = profile.iter().map(|&(_, r)| r).collect::<Vec<_>>();
+
+    let rhs = (0..n)
+        .map(|i| 6.0 / (h * h) * (y[(i + 1) % n] - 2.0 * y[i] + y[(i + n - 1) % n]))
+        .collect::<Vec<_>>();
+
+    // The cyclic system M[i - 1] + 4 M[i] + M[i + 1] = rhs[i] is strictly diagonally dominant, so
+    // Gauss-Seidel converges quickly.
+    let mut second_derivatives = vec![0.0f32; n];
+
+    for _ in 0..100 {
+        let mut change = 0.0f32;
+
+        for i in 0..n {
+            let updated =
+                (rhs[i] - second_derivatives[(i + n - 1) % n] - second_derivatives[(i + 1) % n]) / 4.0;
+
+            change = change.max((updated - second_derivatives[i]).abs());
+
+            second_derivatives[i] = updated;
+        }
+
+        if change <= f32::EPSILON * 4.0 {
+            break;
+        }
+    }
+
+    crate::polar::angles(crate::polar::Domain::Full, size)
+        .map(|theta| {
+            let position = (theta - profile[0].0) / h;
+
+            let i = (position.floor() as isize).rem_euclid(n as isize) as usize;
+            let j = (i + 1) % n;
+
+            let t = position - position.floor();
+            let s = 1.0 - t;
+
+            let r = s * y[i]
+                + t * y[j]
+                + h * h / 6.0 * ((s * s * s - s) * second_derivatives[i] + (t * t * t - t) * second_derivatives[j]);
+
+            (theta, r)
+        })
+        .collect()
+}
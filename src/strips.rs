@@ -0,0 +1,50 @@
+// Renders an --roi image too large for a memory budget a strip of rows at a time, streaming each
+// into the PNG as it's done so that only one strip is ever held in memory.
+
+use crate::fractal::Fractal;
+use crate::render::{self, Colorer};
+use num::complex::Complex;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+// The number of rows of the given width that fit in the budget together, or None if not even one.
+pub fn rows(width: u32, budget: u64) -> Option<u32> {
+    let row = width as u64 * render::BYTES_PER_PIXEL as u64;
+
+    match budget / row {
+        0 => None,
+        rows => Some(rows.min(u32::MAX as u64) as u32)
+    }
+}
+
+// Writes the rectangle to the PNG at the path in strips of at most `rows` rows.
+pub async fn write(
+    path: &Path,
+    fractal: Arc<dyn Fractal>,
+    colorer: Arc<dyn Colorer>,
+    size: (u32, u32),
+    rect: (Complex<f32>, Complex<f32>),
+    samples: u32,
+    rows: u32
+) -> std::io::Result<()> {
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), size.0, size.1);
+
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder.write_header().map_err(std::io::Error::other)?;
+
+    let mut stream = writer.stream_writer().map_err(std::io::Error::other)?;
+
+    for start in (0..size.1).step_by(rows as usize) {
+        let strip = start..start.saturating_add(rows).min(size.1);
+
+        let image = render::render_rect_rows(fractal.clone(), colorer.clone(), size, rect, samples, strip).await;
+
+        stream.write_all(image.as_raw())?;
+    }
+
+    stream.finish().map_err(std::io::Error::other)
+}
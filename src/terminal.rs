@@ -0,0 +1,180 @@
+// Displays images inline in terminals that support one of the common graphics protocols, so the
+// rendered set and plots can be viewed over ssh without copying the files back.
+
+use image::imageops::FilterType;
+use image::RgbImage;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+// Sixel images are sent uncompressed, so they're scaled down to at most this wide.
+const SIXEL_WIDTH: u32 = 1000;
+
+// The kitty protocol takes base64 in chunks of at most this many bytes.
+const KITTY_CHUNK: usize = 4096;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Protocol {
+    Kitty,
+    Iterm,
+    Sixel
+}
+
+impl FromStr for Protocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "kitty" => Ok(Self::Kitty),
+            "iterm" => Ok(Self::Iterm),
+            "sixel" => Ok(Self::Sixel),
+            "auto" => detect().ok_or_else(|| "couldn't detect the terminal's graphics protocol".to_string()),
+            _ => Err(format!("unknown protocol `{}`; expected auto, kitty, iterm, or sixel", s))
+        }
+    }
+}
+
+// Guesses from the environment variables terminals set, since querying the terminal itself would
+// mean reading its replies from standard input.
+pub fn detect() -> Option<Protocol> {
+    let variable = |name: &str| std::env::var(name).unwrap_or_default();
+
+    let term = variable("TERM");
+
+    let program = variable("TERM_PROGRAM");
+
+    if std::env::var_os("KITTY_WINDOW_ID").is_some() || term.contains("kitty") || term.contains("ghostty") {
+        Some(Protocol::Kitty)
+    } else if program == "iTerm.app" || program == "WezTerm" || variable("LC_TERMINAL") == "iTerm2" {
+        Some(Protocol::Iterm)
+    } else if term.contains("sixel") || term.starts_with("foot") || term.starts_with("mlterm") {
+        Some(Protocol::Sixel)
+    } else {
+        None
+    }
+}
+
+pub(crate) fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| group | (byte as u32) << (16 - 8 * i));
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+// Each band of six rows is sent once per color in it, as one character per column whose bits mark
+// which of the six pixels have that color. Colors are reduced to a 6×6×6 cube.
+fn sixel(image: &RgbImage) -> String {
+    let image = if image.width() > SIXEL_WIDTH {
+        let height = (image.height() as u64 * SIXEL_WIDTH as u64 / image.width() as u64).max(1) as u32;
+
+        image::imageops::resize(image, SIXEL_WIDTH, height, FilterType::Triangle)
+    } else {
+        image.clone()
+    };
+
+    let level = |channel: u8| (channel as u32 * 5 + 127) / 255;
+
+    let index = |x: u32, y: u32| {
+        let [red, green, blue] = image.get_pixel(x, y).0;
+
+        (level(red) * 36 + level(green) * 6 + level(blue)) as usize
+    };
+
+    let mut encoded = format!("\x1bP0;1;0q\"1;1;{};{}", image.width(), image.height());
+
+    for color in 0..216 {
+        let percent = |level: usize| level * 100 / 5;
+
+        let (red, green, blue) = (percent(color / 36), percent(color / 6 % 6), percent(color % 6));
+
+        encoded.push_str(&format!("#{};2;{};{};{}", color, red, green, blue));
+    }
+
+    for band in (0..image.height()).step_by(6) {
+        let rows = band..(band + 6).min(image.height());
+
+        let mut used = [false; 216];
+
+        for y in rows.clone() {
+            for x in 0..image.width() {
+                used[index(x, y)] = true;
+            }
+        }
+
+        for color in (0..216).filter(|&color| used[color]) {
+            encoded.push_str(&format!("#{}", color));
+
+            let columns = (0..image.width())
+                .map(|x| rows.clone().filter(|&y| index(x, y) == color).fold(0, |bits, y| bits | 1 << (y - band)))
+                .collect::<Vec<u8>>();
+
+            // Runs of the same character are run-length encoded.
+            for run in columns.chunk_by(|a, b| a == b) {
+                let character = (b'?' + run[0]) as char;
+
+                if run.len() > 3 {
+                    encoded.push_str(&format!("!{}{}", run.len(), character));
+                } else {
+                    encoded.extend(std::iter::repeat_n(character, run.len()));
+                }
+            }
+
+            encoded.push('$');
+        }
+
+        encoded.push('-');
+    }
+
+    encoded.push_str("\x1b\\");
+
+    encoded
+}
+
+// Writes the image at the path to standard error, which stays attached to the terminal when standard
+// output is redirected to save the samples.
+pub fn display(path: &Path, protocol: Protocol) -> Result<(), Box<dyn std::error::Error>> {
+    let mut terminal = std::io::stderr().lock();
+
+    match protocol {
+        Protocol::Kitty => {
+            let encoded = base64(&std::fs::read(path)?);
+
+            let chunks = encoded.as_bytes().chunks(KITTY_CHUNK).collect::<Vec<_>>();
+
+            for (i, chunk) in chunks.iter().enumerate() {
+                let more = (i + 1 < chunks.len()) as u8;
+
+                let control = if i == 0 { format!("f=100,a=T,m={}", more) } else { format!("m={}", more) };
+
+                write!(terminal, "\x1b_G{};{}\x1b\\", control, std::str::from_utf8(chunk)?)?;
+            }
+        }
+
+        Protocol::Iterm => {
+            let bytes = std::fs::read(path)?;
+
+            let arguments = format!("inline=1;size={};preserveAspectRatio=1", bytes.len());
+
+            write!(terminal, "\x1b]1337;File={}:{}\x07", arguments, base64(&bytes))?;
+        }
+
+        Protocol::Sixel => write!(terminal, "{}", sixel(&image::open(path)?.to_rgb8()))?
+    }
+
+    writeln!(terminal)?;
+
+    Ok(())
+}
@@ -0,0 +1,337 @@
+// Checks the renderer against known results. `validate area` counts member pixels over a square
+// around the Mandelbrot set at a sequence of levels, each doubling the resolution and the iteration
+// limit. Points that haven't escaped by the limit count as members, as do pixels straddling the
+// boundary, so the counts approach the published area from above; how fast they close in gives the
+// order of convergence, from which Richardson extrapolation estimates where they're heading.
+//
+// `verify` spot-checks the single-precision kernel instead: random pixels of the rendered image are
+// sampled as the render samples them and again in double precision with a much higher iteration
+// limit, counting where the two disagree on membership.
+//
+// `selftest` runs a battery of quick checks with known answers, to confirm that a new build, or the
+// same build on a new platform, computes what it should.
+
+use crate::analysis::{self, PUBLISHED_AREA};
+use crate::fractal::{Fractal, Julia, Limited, Mandelbrot, BAILOUT_RADIUS};
+use crate::polar::{self, Ray, RAY_STEP};
+use crate::random::Random;
+use crate::render;
+use crate::viewport::{self, View, IMG_HEIGHT, IMG_WIDTH};
+use num::complex::Complex;
+use std::sync::Arc;
+
+pub const LEVELS: usize = 5;
+
+const BASE_RESOLUTION: u32 = 128;
+const BASE_ITERATIONS: u32 = 256;
+
+// The square spanning re from -2 to 0.5, which contains the whole set.
+const VIEW: View = View { center: Complex { re: -0.75, im: 0.0 }, width: 2.5 };
+
+// The extrapolated area must be within this relative error of the published area.
+pub const TOLERANCE: f32 = 0.01;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Level {
+    pub resolution: u32,
+    pub iterations: u32,
+    pub area: f32
+}
+
+#[derive(Clone, Debug)]
+pub struct AreaValidation {
+    pub levels: Vec<Level>,
+    // The exponent p in error ∝ 2^(-p·level), fitted to the differences between levels.
+    pub order: Option<f32>,
+    pub extrapolated: f32
+}
+
+impl AreaValidation {
+    pub fn relative_error(&self) -> f32 {
+        (self.extrapolated - PUBLISHED_AREA).abs() / PUBLISHED_AREA
+    }
+
+    // Whether every level came closer to the published area than the last and the extrapolation
+    // lands within the tolerance.
+    pub fn passed(&self) -> bool {
+        let errors = self.levels.iter().map(|level| (level.area - PUBLISHED_AREA).abs()).collect::<Vec<_>>();
+
+        errors.windows(2).all(|pair| pair[1] <= pair[0]) && self.relative_error() <= TOLERANCE
+    }
+}
+
+// Least squares fit of log₂ of the differences between consecutive levels against the level, whose
+// slope is -p. Needs at least two differences, all shrinking toward the limit from the same side.
+fn fit_order(areas: &[f32]) -> Option<f32> {
+    let differences = areas.windows(2).map(|pair| pair[0] - pair[1]).collect::<Vec<_>>();
+
+    if differences.len() < 2 || !differences.iter().all(|&difference| difference > 0.0) {
+        return None;
+    }
+
+    let points = differences.iter().enumerate().map(|(i, difference)| (i as f32, difference.log2()));
+
+    let n = differences.len() as f32;
+
+    let (sum_x, sum_y, sum_xx, sum_xy) = points.fold((0.0, 0.0, 0.0, 0.0), |(sx, sy, sxx, sxy), (x, y)| {
+        (sx + x, sy + y, sxx + x * x, sxy + x * y)
+    });
+
+    let slope = (n * sum_xy - sum_x * sum_y) / (n * sum_xx - sum_x * sum_x);
+
+    (slope < 0.0).then_some(-slope)
+}
+
+pub async fn area(fractal: Arc<dyn Fractal>, levels: usize) -> AreaValidation {
+    let mut results = Vec::with_capacity(levels);
+
+    for level in 0..levels {
+        let (resolution, iterations) = (BASE_RESOLUTION << level, BASE_ITERATIONS << level);
+
+        let limited = Arc::new(Limited { fractal: fractal.clone(), iterations });
+
+        let buffer = render::render_view_iterations(limited, (resolution, resolution), VIEW).await;
+
+        let pixel_area = (VIEW.width / resolution as f32).powi(2);
+
+        let area = buffer.member_count() as f32 * pixel_area;
+
+        eprintln!("validate: {}x{} at {} iterations: area {}", resolution, resolution, iterations, area);
+
+        results.push(Level { resolution, iterations, area });
+    }
+
+    let areas = results.iter().map(|level| level.area).collect::<Vec<_>>();
+
+    let order = fit_order(&areas);
+
+    // With error ∝ 2^(-p·level), the remaining error after the last level is its difference from
+    // the one before divided by 2^p − 1.
+    let last = areas[areas.len() - 1];
+
+    let extrapolated = match (order, areas.len()) {
+        (Some(order), 2..) => last - (areas[areas.len() - 2] - last) / (order.exp2() - 1.0),
+        _ => last
+    };
+
+    AreaValidation { levels: results, order, extrapolated }
+}
+
+// How many times more iterations the reference gets than the render.
+pub const REFERENCE_ITERATION_FACTOR: u32 = 16;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SpotCheck {
+    pub samples: usize,
+    // Pixels the render counts as members that escape in the reference, and the other way around.
+    pub false_members: usize,
+    pub false_escapes: usize,
+    // Disagreements that remain when the reference stops at the render's own iteration limit, and so
+    // come from single precision rather than from the limit.
+    pub precision: usize
+}
+
+impl SpotCheck {
+    pub fn disagreements(&self) -> usize {
+        self.false_members + self.false_escapes
+    }
+}
+
+// Whether z² + c escapes within the limit in double precision, starting from 0 for the Mandelbrot
+// set and from the point itself for the Julia set of k.
+fn escapes_f64(point: Complex<f64>, k: Option<Complex<f64>>, limit: u32) -> bool {
+    let (mut z, c) = match k {
+        Some(k) => (point, k),
+        None => (Complex::new(0.0, 0.0), point)
+    };
+
+    for _ in 0..=limit {
+        if z.norm() >= BAILOUT_RADIUS as f64 {
+            return true;
+        }
+
+        z = z * z + c;
+    }
+
+    false
+}
+
+// Only the Mandelbrot and Julia sets have a double-precision reference.
+pub fn spot_check(k: Option<Complex<f32>>, samples: usize, limit: u32, random: &mut Random) -> SpotCheck {
+    let fractal: Arc<dyn Fractal> = match k {
+        Some(k) => Arc::new(Julia { k }),
+        None => Arc::new(Mandelbrot)
+    };
+
+    let fractal = Limited { fractal, iterations: limit };
+
+    let k = k.map(|k| Complex::new(k.re as f64, k.im as f64));
+
+    let mut check = SpotCheck { samples, ..Default::default() };
+
+    for _ in 0..samples {
+        let pixel = ((random.next_u64() % IMG_WIDTH as u64) as u32, (random.next_u64() % IMG_HEIGHT as u64) as u32);
+
+        let c = viewport::coordinate_to_complex(pixel);
+
+        // The reference evaluates exactly the point the render does, so only the arithmetic differs.
+        let point = Complex::new(c.re as f64, c.im as f64);
+
+        let member = fractal.sample(c).is_member();
+
+        let escapes = escapes_f64(point, k, limit.saturating_mul(REFERENCE_ITERATION_FACTOR));
+
+        match (member, escapes) {
+            (true, true) => check.false_members += 1,
+            (false, false) => check.false_escapes += 1,
+            _ => continue
+        }
+
+        if member == escapes_f64(point, k, limit) {
+            check.precision += 1;
+        }
+    }
+
+    check
+}
+
+pub struct Check {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: String
+}
+
+// Members at the default iteration limit: the centers of the main cardioid and of components of
+// periods 2, 3, and 4, and i, whose orbit lands on a cycle exactly.
+const INTERIOR: [(f32, f32); 6] =
+    [(0.0, 0.0), (-1.0, 0.0), (-0.1226, 0.7449), (-1.7549, 0.0), (-1.3107, 0.0), (0.0, 1.0)];
+
+// Points well outside the set, and one just past the cusp.
+const EXTERIOR: [(f32, f32); 6] = [(1.0, 0.0), (0.26, 0.0), (-2.1, 0.0), (0.0, 2.0), (0.5, 0.5), (-1.5, 0.5)];
+
+fn points(points: &[(f32, f32)]) -> impl Iterator<Item = Complex<f32>> + '_ {
+    points.iter().map(|&(re, im)| Complex::new(re, im))
+}
+
+fn check(name: &'static str, passed: bool, detail: String) -> Check {
+    Check { name, passed, detail }
+}
+
+fn known_points() -> Check {
+    let wrong = points(&INTERIOR)
+        .filter(|&c| !Mandelbrot.sample(c).is_member())
+        .chain(points(&EXTERIOR).filter(|&c| Mandelbrot.sample(c).is_member()))
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>();
+
+    let detail = if wrong.is_empty() {
+        format!("{} members and {} escapees", INTERIOR.len(), EXTERIOR.len())
+    } else {
+        format!("misclassified {}", wrong.join(", "))
+    };
+
+    check("known points", wrong.is_empty(), detail)
+}
+
+// The orbit of ½ runs 0, 0.5, 0.75, 1.0625, 1.6289..., 3.1533..., reaching the bailout radius on the
+// fifth iteration.
+fn escape_time() -> Check {
+    let iterations = Mandelbrot.sample(Complex::new(0.5, 0.0)).iterations;
+
+    check("escape time", iterations == 5, format!("0.5 escapes after {} iterations, expecting 5", iterations))
+}
+
+// Random points inside the main cardioid and period-2 bulb by their closed forms must all be
+// members by brute force.
+fn cardioid_shortcut(random: &mut Random) -> Check {
+    const POINTS: usize = 10000;
+
+    let (mut inside, mut disagreements) = (0, 0);
+
+    for _ in 0..POINTS {
+        let c = Complex::new(random.next_f32() * 2.5 - 2.0, random.next_f32() * 2.4 - 1.2);
+
+        if analysis::in_main_components(c) {
+            inside += 1;
+
+            if !Mandelbrot.sample(c).is_member() {
+                disagreements += 1;
+            }
+        }
+    }
+
+    check(
+        "cardioid shortcut",
+        inside > 0 && disagreements == 0,
+        format!("{} of {} points inside the closed forms escape by brute force", disagreements, inside)
+    )
+}
+
+// The set is symmetric about the real axis, which holds exactly in floating point since squaring a
+// conjugate only changes signs.
+fn symmetry(random: &mut Random) -> Check {
+    const POINTS: usize = 10000;
+
+    let asymmetric = (0..POINTS)
+        .filter(|_| {
+            let c = Complex::new(random.next_f32() * 2.5 - 2.0, random.next_f32() * 1.2);
+
+            Mandelbrot.sample(c).is_member() != Mandelbrot.sample(c.conj()).is_member()
+        })
+        .count();
+
+    check("symmetry", asymmetric == 0, format!("{} of {} points differ from their conjugates", asymmetric, POINTS))
+}
+
+// Along θ = 0 from the origin, the set ends at the cusp of the main cardioid, c = ¼.
+fn cusp_radius() -> Check {
+    const TOLERANCE: f32 = 1e-3;
+
+    let ray = Ray::new(Complex::new(0.0, 0.0), 0.0);
+
+    let bracket = polar::march_radius(&Mandelbrot, ray, RAY_STEP);
+
+    let radius = polar::refine_radius(&Mandelbrot, ray, bracket, 1e-6);
+
+    check(
+        "cusp radius",
+        (radius - 0.25).abs() <= TOLERANCE && (analysis::analytic_radius(ray) - 0.25).abs() <= TOLERANCE,
+        format!("r(0) = {}, expecting 0.25 to within {}", radius, TOLERANCE)
+    )
+}
+
+// The renderer spreads rows across tasks, which must come out exactly as sampling each pixel does.
+async fn render_agreement() -> Check {
+    const SIZE: (u32, u32) = (160, 90);
+
+    let buffer = render::render_view_iterations(Arc::new(Mandelbrot), SIZE, View::default()).await;
+
+    let wrong = (0..SIZE.1)
+        .flat_map(|y| (0..SIZE.0).map(move |x| (x, y)))
+        .filter(|&(x, y)| {
+            let c = View::default().pixel_to_complex(SIZE, (x as f32 + 0.5, y as f32 + 0.5));
+
+            buffer.is_member(x, y) != Mandelbrot.sample(c).is_member()
+        })
+        .count();
+
+    check(
+        "render agreement",
+        wrong == 0,
+        format!("{} of {} rendered pixels differ from sampling them directly", wrong, SIZE.0 * SIZE.1)
+    )
+}
+
+// The random points come from a fixed seed, so every run checks the same ones.
+pub async fn selftest() -> Vec<Check> {
+    let mut random = Random::new(0);
+
+    vec![
+        known_points(),
+        escape_time(),
+        cardioid_shortcut(&mut random),
+        symmetry(&mut random),
+        cusp_radius(),
+        render_agreement().await
+    ]
+}
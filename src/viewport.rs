@@ -0,0 +1,145 @@
+use num::complex::Complex;
+
+pub const IMG_WIDTH: u32 = 3960;
+pub const IMG_HEIGHT: u32 = 2160;
+
+// The size of the --close-up images of points found by Newton's method.
+pub const CLOSE_UP_SIZE: (u32, u32) = (960, 540);
+
+pub const LOG_POLAR_MIN_RADIUS: f32 = 0.1;
+
+pub fn complex_to_coordinate(c: Complex<f32>) -> (u32, u32) {
+    let (x, y) = unclamped_coordinate(c);
+
+    (x as u32, y as u32)
+}
+
+// complex_to_coordinate before it rounds down and clamps to the image, so exactly the same for
+// points on it and still meaningful for points off it.
+pub fn unclamped_coordinate(c: Complex<f32>) -> (f32, f32) {
+    (
+        (c.re / 2.0 + 1.0) / 2.0 * IMG_WIDTH as f32,
+        (1.0 - (c.im / (2.0 * IMG_HEIGHT as f32 / IMG_WIDTH as f32) + 1.0) / 2.0) * IMG_HEIGHT as f32
+    )
+}
+
+pub fn coordinate_to_complex((x, y): (u32, u32)) -> Complex<f32> {
+    Complex::new(
+        2.0 * (x as f32 / IMG_WIDTH as f32 * 2.0 - 1.0),
+        2.0 * IMG_HEIGHT as f32 / IMG_WIDTH as f32 * ((1.0 - y as f32 / IMG_HEIGHT as f32) * 2.0 - 1.0)
+    )
+}
+
+// The same mapping as coordinate_to_complex, extended to fractional and out-of-range pixels.
+pub fn pixel_to_complex(x: f32, y: f32) -> Complex<f32> {
+    let origin = coordinate_to_complex((0, 0));
+
+    let across = coordinate_to_complex((1, 0)) - origin;
+    let down = coordinate_to_complex((0, 1)) - origin;
+
+    origin + across * x + down * y
+}
+
+// The inverse of pixel_to_complex, which unlike complex_to_coordinate doesn't clamp or round.
+pub fn complex_to_pixel(c: Complex<f32>) -> (f32, f32) {
+    let origin = coordinate_to_complex((0, 0));
+
+    let (width, height) = pixel_size();
+
+    ((c.re - origin.re) / width, (origin.im - c.im) / height)
+}
+
+pub fn pixel_size() -> (f32, f32) {
+    let origin = coordinate_to_complex((0, 0));
+
+    let corner = coordinate_to_complex((1, 1));
+
+    ((corner.re - origin.re).abs(), (corner.im - origin.im).abs())
+}
+
+pub fn pixel_area() -> f32 {
+    let (width, height) = pixel_size();
+
+    width * height
+}
+
+pub fn bounds() -> (Complex<f32>, Complex<f32>) {
+    let top_left = coordinate_to_complex((0, 0));
+
+    let bottom_right = coordinate_to_complex((IMG_WIDTH, IMG_HEIGHT));
+
+    (
+        Complex::new(top_left.re.min(bottom_right.re), top_left.im.min(bottom_right.im)),
+        Complex::new(top_left.re.max(bottom_right.re), top_left.im.max(bottom_right.im))
+    )
+}
+
+// Tall enough that θ and log r share a scale, so shapes are locally undistorted.
+pub fn log_polar_height() -> u32 {
+    ((crate::polar::MAX_RADIUS / LOG_POLAR_MIN_RADIUS).ln() / (std::f32::consts::PI * 2.0) * IMG_WIDTH as f32).round()
+        as u32
+}
+
+pub fn log_polar_to_complex(origin: Complex<f32>, (x, y): (u32, u32)) -> Complex<f32> {
+    let theta = (x as f32 + 0.5) / IMG_WIDTH as f32 * std::f32::consts::PI * 2.0;
+
+    let log_radius = LOG_POLAR_MIN_RADIUS.ln()
+        + (1.0 - (y as f32 + 0.5) / log_polar_height() as f32)
+            * (crate::polar::MAX_RADIUS / LOG_POLAR_MIN_RADIUS).ln();
+
+    origin + Complex::from_polar(log_radius.exp(), theta)
+}
+
+// A view of the complex plane by its center and the width it spans. Pixels are square, so the
+// height it spans follows from the image's aspect ratio; the default is the same view as
+// coordinate_to_complex.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct View {
+    pub center: Complex<f32>,
+    pub width: f32
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self { center: Complex::new(0.0, 0.0), width: 4.0 }
+    }
+}
+
+impl View {
+    pub fn fit_width(center: Complex<f32>, width: f32) -> Self {
+        Self { center, width }
+    }
+
+    // Spans the given height, and as much width as the image's aspect ratio gives.
+    pub fn fit_height(center: Complex<f32>, height: f32, (width, image_height): (u32, u32)) -> Self {
+        Self { center, width: height * width as f32 / image_height as f32 }
+    }
+
+    // The smallest view containing the rectangle between the corners, centered on it. Pixels stay
+    // square, so when the rectangle's aspect ratio differs from the image's, the view spans more
+    // than the rectangle in one direction.
+    pub fn fit_bounds(a: Complex<f32>, b: Complex<f32>, (width, height): (u32, u32)) -> Self {
+        let span = a - b;
+
+        Self { center: (a + b) / 2.0, width: span.re.abs().max(span.im.abs() * width as f32 / height as f32) }
+    }
+
+    pub fn height(&self, (width, height): (u32, u32)) -> f32 {
+        self.width * height as f32 / width as f32
+    }
+
+    pub fn pixel_to_complex(&self, (width, height): (u32, u32), (x, y): (f32, f32)) -> Complex<f32> {
+        let scale = self.width / width as f32;
+
+        self.center + Complex::new((x - width as f32 / 2.0) * scale, (height as f32 / 2.0 - y) * scale)
+    }
+
+    // The inverse of pixel_to_complex.
+    pub fn complex_to_pixel(&self, (width, height): (u32, u32), c: Complex<f32>) -> (f32, f32) {
+        let scale = self.width / width as f32;
+
+        let offset = (c - self.center) / scale;
+
+        (offset.re + width as f32 / 2.0, height as f32 / 2.0 - offset.im)
+    }
+}
@@ -0,0 +1,108 @@
+// Just enough of WebSockets (RFC 6455) for the server to push messages to a browser: the opening
+// handshake and unfragmented, unmasked frames from the server. Nothing the client sends after the
+// handshake is read.
+
+use crate::terminal;
+
+// Appended to the client's key before hashing, as fixed by the RFC.
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+const TEXT: u8 = 0x1;
+const BINARY: u8 = 0x2;
+const CLOSE: u8 = 0x8;
+
+fn sha1(message: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    // The message is padded with a one bit, zeros, and its length in bits to a multiple of 64 bytes.
+    let mut padded = message.to_vec();
+
+    padded.push(0x80);
+
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+
+    padded.extend_from_slice(&(message.len() as u64 * 8).to_be_bytes());
+
+    for block in padded.chunks(64) {
+        let mut words = [0u32; 80];
+
+        for (i, word) in block.chunks(4).enumerate() {
+            words[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+
+        for (i, &word) in words.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6)
+            };
+
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+
+            (e, d, c, b, a) = (d, c, b.rotate_left(30), a, temp);
+        }
+
+        for (word, value) in state.iter_mut().zip([a, b, c, d, e]) {
+            *word = word.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0; 20];
+
+    for (bytes, word) in digest.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+
+    digest
+}
+
+// The Sec-WebSocket-Accept value answering a client's Sec-WebSocket-Key.
+pub fn accept_key(key: &str) -> String {
+    terminal::base64(&sha1(format!("{}{}", key.trim(), GUID).as_bytes()))
+}
+
+fn frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x80 | opcode];
+
+    // Payloads up to 125 bytes give their length in the second byte; longer ones follow it with a
+    // 16- or 64-bit length.
+    match payload.len() {
+        length @ 0..=125 => frame.push(length as u8),
+
+        length @ 126..=0xFFFF => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+
+    frame.extend_from_slice(payload);
+
+    frame
+}
+
+pub fn text(message: &str) -> Vec<u8> {
+    frame(TEXT, message.as_bytes())
+}
+
+pub fn binary(message: &[u8]) -> Vec<u8> {
+    frame(BINARY, message)
+}
+
+// A normal closure, status 1000.
+pub fn close() -> Vec<u8> {
+    frame(CLOSE, &1000u16.to_be_bytes())
+}
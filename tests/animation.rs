@@ -0,0 +1,33 @@
+// A zoom's iteration limit grows with its magnification, and has to hold at the largest limit rather
+// than overflow however fast it's asked to grow.
+
+use num::complex::Complex;
+use polar_mandelbrot::animation::Zoom;
+
+fn zoom(iteration_growth: u32) -> Zoom {
+    Zoom {
+        target: Complex::new(-0.75, 0.1),
+        magnification: 1024.0,
+        frames: 11,
+        iterations: 1000,
+        iteration_growth,
+        samples: 1,
+        size: (64, 36)
+    }
+}
+
+#[test]
+fn iterations_grow_with_each_doubling() {
+    let zoom = zoom(100);
+
+    // Each frame doubles the magnification.
+    for frame in 0..11 {
+        assert_eq!(zoom.iterations(frame), 1000 + 100 * frame as u32);
+    }
+}
+
+#[test]
+fn iterations_saturate() {
+    assert_eq!(zoom(u32::MAX).iterations(10), u32::MAX);
+    assert_eq!(zoom(u32::MAX / 4).iterations(10), u32::MAX);
+}
@@ -0,0 +1,95 @@
+// Rendering must come out bit-identical however many threads the rows are spread over, and random
+// sampling with a fixed seed must repeat exactly, or --deterministic and the golden images can't
+// be relied on. Posters likewise have to stitch into the very image rendered whole, and a render out
+// of time before it starts has to come out the same every time, its first row copied all the way down.
+
+use num::complex::Complex;
+use polar_mandelbrot::analysis;
+use polar_mandelbrot::fractal::{Limited, Mandelbrot};
+use polar_mandelbrot::poster::{self, Poster};
+use polar_mandelbrot::random::Random;
+use polar_mandelbrot::render::{self, PaletteColorer};
+use polar_mandelbrot::viewport::{self, View};
+use std::sync::Arc;
+use std::time::Instant;
+
+fn render_on(threads: usize) -> Vec<u8> {
+    let runtime = tokio::runtime::Builder::new_multi_thread().worker_threads(threads).build().unwrap();
+
+    let fractal = Arc::new(Limited { fractal: Arc::new(Mandelbrot), iterations: 300 });
+
+    let view = View { center: Complex::new(-0.75, 0.1), width: 0.5 };
+
+    let colorer = Arc::new(PaletteColorer { phase: 0.0, theme: None });
+
+    let image = runtime.block_on(render::render_view(fractal, colorer, (200, 120), view, 4));
+
+    image.into_raw()
+}
+
+#[test]
+fn renders_match_across_thread_counts() {
+    let reference = render_on(1);
+
+    for threads in [2, 3, 8] {
+        assert!(render_on(threads) == reference, "rendering on {} threads changed the image", threads);
+    }
+}
+
+#[test]
+fn monte_carlo_repeats_with_a_seed() {
+    let estimate = |seed| analysis::monte_carlo_area(&Mandelbrot, viewport::bounds(), 10000, &mut Random::new(seed));
+
+    let (first, second) = (estimate(1), estimate(1));
+
+    assert_eq!(first.estimate.to_bits(), second.estimate.to_bits());
+    assert_eq!(first.standard_error.to_bits(), second.standard_error.to_bits());
+}
+
+#[tokio::test]
+async fn posters_stitch_into_the_whole_render() {
+    let fractal = Arc::new(Limited { fractal: Arc::new(Mandelbrot), iterations: 300 });
+
+    let colorer = Arc::new(PaletteColorer { phase: 0.0, theme: None });
+
+    let rect = (Complex::new(-0.8, 0.05), Complex::new(-0.7, 0.12));
+
+    let poster = Poster { size: (150, 100), rect, tile: 40, samples: 2 };
+
+    let whole = render::render_rect(fractal.clone(), colorer.clone(), poster.size, poster.rect, poster.samples).await;
+
+    let directory = std::env::temp_dir().join(format!("polar_mandelbrot_poster_{}", std::process::id()));
+
+    // Split over two runs, as if on two machines.
+    for shard in [(0, 2), (1, 2)] {
+        poster::render(&directory, fractal.clone(), colorer.clone(), &poster, shard).await.unwrap();
+    }
+
+    for name in ["poster.png", "poster.tif"] {
+        let path = directory.join(name);
+
+        poster::stitch(&directory, &path).unwrap();
+
+        assert!(image::open(&path).unwrap().to_rgb8() == whole, "the stitched {} differs from the whole render", name);
+    }
+
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[tokio::test]
+async fn renders_out_of_time_keep_the_first_row() {
+    let fractal = Arc::new(Limited { fractal: Arc::new(Mandelbrot), iterations: 50 });
+
+    let colorer = Arc::new(PaletteColorer { phase: 0.0, theme: None });
+
+    let (iterations, image) = render::render_within(fractal, colorer, false, Instant::now()).await;
+
+    assert_eq!((iterations.rendered_rows(), iterations.gap()), (1, viewport::IMG_HEIGHT));
+
+    // Only the row rendered counts.
+    assert_eq!(iterations.statistics(50).pixels, viewport::IMG_WIDTH as usize);
+
+    let first = &image.as_raw()[..viewport::IMG_WIDTH as usize * 3];
+
+    assert!(image.as_raw().chunks(first.len()).all(|row| row == first), "a row isn't a copy of the first");
+}
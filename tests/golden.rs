@@ -0,0 +1,110 @@
+// Renders small canonical views and compares them against the reference images in tests/golden, so
+// changes to the kernels or colorers can't alter the output unnoticed. A few pixels on the boundary
+// may flip with the platform's floating point, so images match if nearly all pixels look the same.
+// Run with UPDATE_GOLDEN=1 to rewrite the references after an intended change. The fixed-point
+// kernel is held to the floating-point one the same way.
+
+use num::complex::Complex;
+use image::RgbImage;
+use polar_mandelbrot::fixed::Fixed;
+use polar_mandelbrot::fractal::{Escape, Fractal, Julia, Limited, Mandelbrot};
+use polar_mandelbrot::render::{self, Colorer, MembershipColorer, PaletteColorer};
+use polar_mandelbrot::viewport::View;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const SIZE: (u32, u32) = (160, 90);
+
+// Pixels whose luma differs by more than this out of 255 look different.
+const LUMA_TOLERANCE: f32 = 16.0;
+
+// At most this fraction of the pixels may look different.
+const MAX_DIFFERENT: f32 = 0.005;
+
+// The fraction of the pixels that look different between the images.
+fn different(image: &RgbImage, reference: &RgbImage) -> f32 {
+    let luma = |[red, green, blue]: [u8; 3]| 0.299 * red as f32 + 0.587 * green as f32 + 0.114 * blue as f32;
+
+    let different = image
+        .pixels()
+        .zip(reference.pixels())
+        .filter(|(pixel, expected)| (luma(pixel.0) - luma(expected.0)).abs() > LUMA_TOLERANCE)
+        .count();
+
+    different as f32 / (image.width() * image.height()) as f32
+}
+
+async fn check(name: &str, fractal: Arc<dyn Fractal>, colorer: Arc<dyn Colorer>, view: View, samples: u32) {
+    let image = render::render_view(fractal, colorer, SIZE, view, samples).await;
+
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(format!("{}.png", name));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        image.save(&path).unwrap();
+
+        return;
+    }
+
+    let reference = image::open(&path)
+        .unwrap_or_else(|error| panic!("{}: {}; run with UPDATE_GOLDEN=1 to create it", path.display(), error))
+        .to_rgb8();
+
+    assert_eq!(image.dimensions(), reference.dimensions(), "{}: the size changed", name);
+
+    let fraction = different(&image, &reference);
+
+    let (percent, path) = (fraction * 100.0, path.display());
+
+    assert!(fraction <= MAX_DIFFERENT, "{}: {:.2}% of the pixels differ from {}", name, percent, path);
+}
+
+#[tokio::test]
+async fn full_set() {
+    check("full_set", Arc::new(Mandelbrot), Arc::new(MembershipColorer), View::default(), 1).await;
+}
+
+#[tokio::test]
+async fn seahorse_valley() {
+    let fractal = Arc::new(Limited { fractal: Arc::new(Mandelbrot), iterations: 500 });
+
+    let view = View { center: Complex::new(-0.7453, 0.1127), width: 0.02 };
+
+    check("seahorse_valley", fractal, Arc::new(PaletteColorer { phase: 0.0, theme: None }), view, 4).await;
+}
+
+#[tokio::test]
+async fn julia() {
+    let fractal = Arc::new(Julia { k: Complex::new(-0.8, 0.156) });
+
+    let view = View { center: Complex::new(0.0, 0.0), width: 3.5 };
+
+    check("julia", fractal, Arc::new(PaletteColorer { phase: 0.25, theme: None }), view, 1).await;
+}
+
+// Deep orbits near the boundary are chaotic, so f32 rounding alone parts the two there; a modest
+// limit keeps the comparison to the orbits both kernels follow faithfully.
+#[tokio::test]
+async fn fixed_point_matches_floating_point() {
+    let julia = Complex::new(-0.8, 0.156);
+
+    let views = [(None, View::default()), (Some(julia), View { center: Complex::new(0.0, 0.0), width: 3.5 })];
+
+    for (k, view) in views {
+        let float: Arc<dyn Fractal> = match k {
+            Some(k) => Arc::new(Julia { k }),
+            None => Arc::new(Mandelbrot)
+        };
+
+        let fixed = Arc::new(Fixed::new(k, Escape::Norm(2.0)).unwrap());
+
+        let limited = |fractal| Arc::new(Limited { fractal, iterations: 100 });
+
+        let colorer = Arc::new(PaletteColorer { phase: 0.0, theme: None });
+
+        let reference = render::render_view(limited(float), colorer.clone(), SIZE, view, 1).await;
+
+        let fraction = different(&render::render_view(limited(fixed), colorer, SIZE, view, 1).await, &reference);
+
+        assert!(fraction <= MAX_DIFFERENT, "{:?}: {:.2}% of the pixels differ", k, fraction * 100.0);
+    }
+}
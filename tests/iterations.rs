@@ -0,0 +1,82 @@
+// Saved iteration buffers and cache entries are read back from disk, where they may have been cut
+// short or corrupted, so reading one has to fail cleanly rather than misread or exhaust memory.
+
+use num::complex::Complex;
+use polar_mandelbrot::fractal::{Sample, Termination};
+use polar_mandelbrot::render::IterationBuffer;
+
+fn buffer(smooth: bool) -> IterationBuffer {
+    let mut buffer = if smooth { IterationBuffer::with_smooth(3, 2) } else { IterationBuffer::new(3, 2) };
+
+    for y in 0..2 {
+        for x in 0..3 {
+            let escaped = (x + y) % 2 == 0;
+
+            let sample = Sample {
+                c: Complex::new(0.0, 0.0),
+                z: Complex::new(0.0, 0.0),
+                iterations: 1000 * x + y,
+                termination: escaped.then_some(Termination::Escaped)
+            };
+
+            buffer.set(x, y, &sample);
+            buffer.set_smooth(x, y, x as f32 + y as f32 / 4.0);
+        }
+    }
+
+    buffer
+}
+
+fn written(buffer: &IterationBuffer) -> Vec<u8> {
+    let mut bytes = Vec::new();
+
+    buffer.write_to(&mut bytes).unwrap();
+
+    bytes
+}
+
+#[test]
+fn written_buffers_read_back() {
+    for smooth in [false, true] {
+        let original = buffer(smooth);
+        let read = IterationBuffer::read_from(&mut &written(&original)[..]).unwrap();
+
+        assert_eq!((read.width(), read.height(), read.has_smooth()), (3, 2, smooth));
+
+        for y in 0..2 {
+            for x in 0..3 {
+                assert_eq!(read.sample(x, y).iterations, original.sample(x, y).iterations);
+                assert_eq!(read.is_member(x, y), original.is_member(x, y));
+                assert_eq!(read.smooth(x, y), original.smooth(x, y));
+            }
+        }
+    }
+}
+
+#[test]
+fn truncated_buffers_are_rejected() {
+    for smooth in [false, true] {
+        let bytes = written(&buffer(smooth));
+
+        for len in 0..bytes.len() {
+            assert!(IterationBuffer::read_from(&mut &bytes[..len]).is_err(), "{} of {} bytes read", len, bytes.len());
+        }
+    }
+}
+
+#[test]
+fn oversized_headers_are_rejected_before_allocating() {
+    // A header claiming 200000 × 200000 pixels, which would take 160 GB, and then a few bytes.
+    let mut bytes = [200_000u32.to_le_bytes(), 200_000u32.to_le_bytes()].concat();
+
+    bytes.extend([0; 8]);
+
+    let error = IterationBuffer::read_from(&mut &bytes[..]).err().unwrap();
+
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+
+    // Too large to address at all.
+    let bytes = [u32::MAX.to_le_bytes(), u32::MAX.to_le_bytes()].concat();
+
+    assert_eq!(IterationBuffer::read_from(&mut &bytes[..]).err().unwrap().kind(), std::io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,152 @@
+// Job requests come from anyone who can reach the server, so every field is checked for its type and
+// range before a job is made of it, and the bounds that keep one job from holding the server hold.
+
+use num::complex::Complex;
+use polar_mandelbrot::jobs::{Kind, Parameters};
+use polar_mandelbrot::json::Value;
+use polar_mandelbrot::viewport::View;
+
+fn parameters(source: &str) -> Parameters {
+    Parameters::from_json(&Value::parse(source).unwrap()).unwrap_or_else(|error| panic!("{}: {}", source, error))
+}
+
+fn error(source: &str) -> String {
+    Parameters::from_json(&Value::parse(source).unwrap()).expect_err(source)
+}
+
+#[test]
+fn fields_default_to_the_command_line_defaults() {
+    let render = parameters(r#"{"kind": "render"}"#);
+
+    assert_eq!(render.kind, Kind::Render);
+    assert_eq!((render.fractal, render.julia), (None, None));
+    assert_eq!((render.view, render.size), (View::default(), (1280, 720)));
+    assert_eq!((render.iterations, render.samples), (1000, 1));
+
+    let analyze = parameters(r#"{"kind": "analyze", "julia": null, "width": null}"#);
+
+    assert_eq!(analyze.kind, Kind::Analyze);
+    assert_eq!((analyze.polar_origin, analyze.angles, analyze.ray_step), (Complex::new(0.0, 0.0), 1000, 0.001));
+}
+
+#[test]
+fn kind_is_required() {
+    for source in [r#"{}"#, r#"{"kind": "plot"}"#, r#"{"kind": 1}"#, r#"[]"#] {
+        assert_eq!(error(source), "`kind` must be \"render\" or \"analyze\"", "{}", source);
+    }
+}
+
+#[test]
+fn fractal_and_julia_are_exclusive() {
+    let julia = parameters(r#"{"kind": "render", "julia": {"re": -0.8, "im": 0.156}}"#);
+
+    assert_eq!(julia.julia, Some(Complex::new(-0.8, 0.156)));
+    assert_eq!(parameters(r#"{"kind": "render", "fractal": "magnet1"}"#).fractal.as_deref(), Some("magnet1"));
+
+    assert_eq!(
+        error(r#"{"kind": "render", "fractal": "mandelbrot", "julia": {"re": 0, "im": 0}}"#),
+        "`fractal` and `julia` cannot be combined"
+    );
+
+    assert_eq!(
+        error(r#"{"kind": "render", "fractal": "tricorn"}"#),
+        "`fractal` must be mandelbrot, magnet1, or magnet2"
+    );
+
+    for julia in [r#"[0, 0]"#, r#"{"re": 0}"#, r#"{"re": 0, "im": "0"}"#] {
+        assert_eq!(
+            error(&format!(r#"{{"kind": "render", "julia": {}}}"#, julia)),
+            "`julia` must be an object with numbers `re` and `im`",
+            "{}",
+            julia
+        );
+    }
+}
+
+#[test]
+fn size_is_bounded() {
+    assert_eq!(parameters(r#"{"kind": "render", "size": [8192, 8192]}"#).size, (8192, 8192));
+
+    let too_large = "`size` must be two whole numbers with at most 67108864 pixels";
+
+    for size in ["[8193, 8192]", "[67108865, 1]", "[0, 720]", "[1280.5, 720]", "[-1280, 720]", "[1280, \"720\"]"] {
+        assert_eq!(error(&format!(r#"{{"kind": "render", "size": {}}}"#, size)), too_large, "{}", size);
+    }
+
+    for size in ["[]", "[1280]", "[1280, 720, 1]"] {
+        assert_eq!(error(&format!(r#"{{"kind": "render", "size": {}}}"#, size)), "`size` must be [width, height]");
+    }
+}
+
+#[test]
+fn the_view_takes_one_of_width_height_and_bounds() {
+    let width = parameters(r#"{"kind": "render", "center": {"re": -0.75, "im": 0.1}, "width": 0.5}"#);
+
+    assert_eq!(width.view, View { center: Complex::new(-0.75, 0.1), width: 0.5 });
+
+    let bounds = r#"{"kind": "render", "size": [200, 100], "bounds": [{"re": -2, "im": -1}, {"re": 1, "im": 2}]}"#;
+    let bounds = parameters(bounds);
+
+    // Widened to contain the corners without stretching.
+    assert_eq!(bounds.view, View { center: Complex::new(-0.5, 0.5), width: 6.0 });
+
+    let corners = r#""bounds": [{"re": 0, "im": 0}, {"re": 1, "im": 1}]"#;
+    let combined = "`width`, `height`, and `bounds` cannot be combined";
+
+    assert_eq!(error(r#"{"kind": "render", "width": 1, "height": 1}"#), combined);
+    assert_eq!(error(&format!(r#"{{"kind": "render", "width": 1, {}}}"#, corners)), combined);
+    assert_eq!(error(&format!(r#"{{"kind": "render", "height": 1, {}}}"#, corners)), combined);
+
+    assert_eq!(
+        error(&format!(r#"{{"kind": "render", "center": {{"re": 0, "im": 0}}, {}}}"#, corners)),
+        "`bounds` must be distinct corners, without `center`"
+    );
+
+    assert_eq!(
+        error(r#"{"kind": "render", "bounds": [{"re": 1, "im": 1}, {"re": 1, "im": 1}]}"#),
+        "`bounds` must be distinct corners, without `center`"
+    );
+
+    assert_eq!(error(r#"{"kind": "render", "bounds": [{"re": 1, "im": 1}]}"#), "`bounds` must be two corners");
+    assert_eq!(error(r#"{"kind": "render", "width": 0}"#), "`width` and `height` must be positive");
+    assert_eq!(error(r#"{"kind": "render", "height": -2}"#), "`width` and `height` must be positive");
+    assert_eq!(error(r#"{"kind": "render", "width": "wide"}"#), "`width` must be a number");
+}
+
+#[test]
+fn counts_are_whole_and_bounded() {
+    let samples = "`samples` must be a whole number from 1 to 64";
+
+    for value in ["0", "1.5", "65", "-1"] {
+        assert_eq!(error(&format!(r#"{{"kind": "render", "samples": {}}}"#, value)), samples, "{}", value);
+    }
+
+    assert_eq!(error(r#"{"kind": "render", "samples": "4"}"#), "`samples` must be a number");
+    assert_eq!(parameters(r#"{"kind": "render", "samples": 64}"#).samples, 64);
+
+    assert_eq!(parameters(r#"{"kind": "render", "iterations": 65536}"#).iterations, 65536);
+
+    assert_eq!(
+        error(r#"{"kind": "render", "iterations": 65537}"#),
+        "`iterations` must be a whole number from 1 to 65536"
+    );
+
+    assert_eq!(
+        error(r#"{"kind": "analyze", "angles": 1048577}"#),
+        "`angles` must be a whole number from 1 to 1048576"
+    );
+}
+
+#[test]
+fn ray_steps_are_bounded_below() {
+    assert_eq!(parameters(r#"{"kind": "analyze", "ray_step": 0.0000305176}"#).ray_step, 0.0000305176);
+
+    for value in ["0.00003", "1e-9", "0", "-0.001"] {
+        assert_eq!(
+            error(&format!(r#"{{"kind": "analyze", "ray_step": {}}}"#, value)),
+            "`ray_step` must be at least 0.000030517578",
+            "{}",
+            value
+        );
+    }
+}
@@ -0,0 +1,95 @@
+// The server parses job requests from anyone with this parser, so besides reading back what the
+// writer wrote, it has to reject malformed input with an error rather than a panic or a wrong value.
+
+use polar_mandelbrot::json::Value;
+
+fn error(source: &str) -> String {
+    Value::parse(source).expect_err(source)
+}
+
+#[test]
+fn written_values_parse_back() {
+    let value = Value::object([
+        ("null", Value::Null),
+        ("bool", true.into()),
+        ("numbers", Value::array([0.0, -1.5, 1e-7, 6.02e23, f64::MAX])),
+        ("string", "quotes \" and \\ backslashes, \n\r\t, \u{1} \u{1f} and θ, 2π".into()),
+        ("empty", Value::object::<&str>([])),
+        ("nested", Value::array([Value::array::<Value>([]), Value::object([("", Value::Null)])]))
+    ]);
+
+    assert_eq!(Value::parse(&value.to_string()), Ok(value));
+}
+
+#[test]
+fn whitespace_is_skipped() {
+    let value = Value::parse(" \n{ \"a\" : [ 1 , 2 ] ,\t\"b\" : null }\r\n").unwrap();
+
+    assert_eq!(value, Value::object([("a", Value::array([1.0, 2.0])), ("b", Value::Null)]));
+}
+
+#[test]
+fn escapes_are_decoded() {
+    let string = Value::parse(r#""\" \\ \/ \b \f \n \r \t \u0041\u03b8 é""#).unwrap();
+
+    assert_eq!(string, "\" \\ / \u{8} \u{c} \n \r \t Aθ é".into());
+
+    // Surrogates aren't characters, so each half of a pair is replaced.
+    assert_eq!(Value::parse(r#""\ud83d\ude00""#), Ok("\u{fffd}\u{fffd}".into()));
+
+    for source in [r#""\u12""#, r#""\u12g4""#, r#""\u+123""#, r#""\u-123""#, r#""\x""#, r#""\"#, r#""abc"#] {
+        assert!(Value::parse(source).is_err(), "{} parsed", source);
+    }
+}
+
+#[test]
+fn numbers_take_exponents() {
+    for (source, number) in [("1e3", 1e3), ("-2.5E-3", -2.5e-3), ("1e+2", 100.0), ("0", 0.0), ("-0.125", -0.125)] {
+        assert_eq!(Value::parse(source), Ok(Value::Number(number)), "{}", source);
+    }
+
+    assert_eq!(error("1e999"), "number `1e999` out of range at byte 0");
+    assert_eq!(error("[1, -1e400]"), "number `-1e400` out of range at byte 4");
+    assert_eq!(error("1e"), "invalid number `1e` at byte 0");
+    assert_eq!(error("-"), "invalid number `-` at byte 0");
+}
+
+#[test]
+fn non_finite_numbers_are_written_as_null() {
+    let value = Value::array([f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1.0]);
+
+    assert_eq!(value.to_string(), "[null,null,null,1]");
+}
+
+#[test]
+fn trailing_characters_are_rejected() {
+    assert_eq!(error("{} x"), "trailing characters at byte 3");
+    assert_eq!(error("[1] [2]"), "trailing characters at byte 4");
+    assert_eq!(error("truex"), "trailing characters at byte 4");
+
+    assert!(Value::parse("{}  \n").is_ok());
+}
+
+#[test]
+fn malformed_input_is_rejected() {
+    assert_eq!(error(""), "unexpected end of input at byte 0");
+    assert_eq!(error("[1,"), "unexpected end of input at byte 3");
+    assert_eq!(error("[1 2]"), "expected `,` or `]` at byte 3");
+    assert_eq!(error("{\"a\" 1}"), "expected `:` at byte 5");
+    assert_eq!(error("{1: 2}"), "expected a key at byte 1");
+    assert_eq!(error("{\"a\": 1,}"), "expected a key at byte 8");
+    assert_eq!(error("nul"), "expected `null` at byte 0");
+    assert_eq!(error("'a'"), "expected a value at byte 0");
+}
+
+#[test]
+fn nesting_is_limited_to_128() {
+    let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+    assert!(Value::parse(&nested(128)).is_ok());
+
+    assert_eq!(error(&nested(129)), "nested too deeply at byte 128");
+
+    // Far deeper input is rejected the same way rather than overflowing the stack.
+    assert!(error(&"{\"a\":".repeat(100_000)).starts_with("nested too deeply"));
+}
@@ -0,0 +1,112 @@
+// Keyframe files have to start at frame 0, increase, and set k from the start if at all; what parses
+// has to interpolate through every keyframe exactly and write back out as the same keyframes.
+
+use num::complex::Complex;
+use polar_mandelbrot::keyframes::{Easing, Keyframe, Keyframes};
+use polar_mandelbrot::viewport::View;
+
+const EASINGS: [Easing; 4] = [Easing::Linear, Easing::EaseIn, Easing::EaseOut, Easing::EaseInOut];
+
+fn error(source: &str) -> String {
+    Keyframes::parse(source).expect_err(source).to_string()
+}
+
+fn close(a: Complex<f32>, b: Complex<f32>) -> bool {
+    (a - b).norm() <= 1e-6 * b.norm().max(1.0)
+}
+
+#[test]
+fn keyframes_start_at_frame_0_and_increase() {
+    assert_eq!(error("[5]\nzoom = 2"), "line 1: the first keyframe must be [0]");
+    assert_eq!(error("[0]\n[10]\n\n[10]"), "line 4: keyframes must be in increasing order");
+    assert_eq!(error("[0]\n[10]\n[5]"), "line 3: keyframes must be in increasing order");
+    assert_eq!(error("# no header yet\nzoom = 2"), "line 2: expected a `[frame]` header first");
+    assert_eq!(error("# nothing\n"), "line 1: no keyframes");
+}
+
+#[test]
+fn k_is_set_in_the_first_keyframe_if_at_all() {
+    assert_eq!(error("[0]\n[10]\nk = 0.3+0.5i"), "line 3: `k` must be set in the first keyframe if at all");
+
+    let keyframes = Keyframes::parse("[0]\nk = -0.8+0.156i\n[10]\nk = 0.285+0.01i").unwrap();
+
+    assert!(keyframes.is_julia());
+    assert!(keyframes.state(10).k.is_some_and(|k| close(k, Complex::new(0.285, 0.01))));
+
+    // Unset later, it carries over.
+    assert_eq!(Keyframes::parse("[0]\nk = 0.25+0i\n[10]").unwrap().state(5).k, Some(Complex::new(0.25, 0.0)));
+}
+
+#[test]
+fn values_are_checked() {
+    assert_eq!(error("[0]\nzoom = 0"), "line 2: `zoom` must be positive");
+    assert_eq!(error("[0]\nzoom = inf"), "line 2: `zoom` must be positive");
+    assert_eq!(error("[0]\niterations = -1"), "line 2: invalid value `-1` for `iterations`");
+    assert_eq!(error("[0]\ncentre = 0+0i"), "line 2: unknown key `centre`");
+    assert_eq!(error("[x]"), "line 1: invalid value `x` for `frame`");
+    assert!(error("[0]\neasing = smooth").starts_with("line 2: unknown easing `smooth`"));
+}
+
+#[test]
+fn easings_run_from_0_to_1() {
+    for easing in EASINGS {
+        assert_eq!((easing.apply(0.0), easing.apply(1.0)), (0.0, 1.0), "{}", easing);
+
+        assert!((0..=100).all(|i| (0.0..=1.0).contains(&easing.apply(i as f32 / 100.0))), "{}", easing);
+    }
+
+    assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+}
+
+#[test]
+fn states_pass_through_every_keyframe() {
+    let center = Complex::new(-0.7436, 0.1318);
+
+    for easing in EASINGS {
+        let source = format!(
+            "[0]\ncenter = -0.75+0i\nphase = 0\n[60]\ncenter = {}\nzoom = 4\niterations = 3000\nphase = 1\neasing = {}",
+            center,
+            easing
+        );
+
+        let keyframes = Keyframes::parse(&source).unwrap();
+
+        assert_eq!(keyframes.frames(), 61);
+
+        let start = keyframes.state(0);
+
+        assert_eq!(start.view, View { center: Complex::new(-0.75, 0.0), ..View::default() });
+        assert_eq!((start.iterations, start.phase), (1000, 0.0));
+
+        // The last keyframe holds past the end.
+        for frame in [60, 61, 1000] {
+            let end = keyframes.state(frame);
+
+            assert!(close(end.view.center, center), "{} at frame {}: {}", easing, frame, end.view.center);
+            assert!((end.view.width - View::default().width / 4.0).abs() < 1e-6, "{} at frame {}", easing, frame);
+            assert_eq!((end.iterations, end.phase), (3000, 1.0), "{} at frame {}", easing, frame);
+        }
+    }
+}
+
+#[test]
+fn keyframes_write_back_out_as_themselves() {
+    let sources = [
+        include_str!("../example/seahorse.keyframes"),
+        "[0]\nk = -0.8+0.156i\niterations = 500\n[90]\nk = 0.285-0.01i\nzoom = 8\neasing = ease-in",
+        "[0]\ncenter = 0.25-0.5i\n[1]\nzoom = 1e6\n[2]\nzoom = 0.125\neasing = ease-out"
+    ];
+
+    for source in sources {
+        let keyframes = Keyframes::parse(source).unwrap();
+
+        assert_eq!(Keyframes::parse(&keyframes.to_string()).unwrap(), keyframes, "{}", keyframes);
+    }
+
+    let made = Keyframes::new(vec![
+        Keyframe::default(),
+        Keyframe { frame: 30, center: Complex::new(-1.401_155, 0.0), zoom: 300.0, ..Keyframe::default() }
+    ]);
+
+    assert_eq!(Keyframes::parse(&made.to_string()).unwrap(), made);
+}
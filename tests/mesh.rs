@@ -0,0 +1,44 @@
+// The heightfield mesh has to be a closed solid for slicers to print it: every edge shared by
+// exactly two triangles, which traverse it in opposite directions when they face the same way.
+
+use polar_mandelbrot::mesh;
+use polar_mandelbrot::random::Random;
+use polar_mandelbrot::render::IterationBuffer;
+use std::collections::HashMap;
+
+fn buffer((width, height): (u32, u32), random: &mut Random) -> IterationBuffer {
+    let mut buffer = IterationBuffer::with_smooth(width, height);
+
+    for y in 0..height {
+        for x in 0..width {
+            buffer.set_smooth(x, y, random.next_f32() * 100.0);
+        }
+    }
+
+    buffer
+}
+
+#[test]
+fn heightfields_are_closed() {
+    let mut random = Random::new(1);
+
+    for (size, step) in [((13, 9), 1), ((13, 9), 3), ((40, 25), 7), ((5, 4), 20)] {
+        let mesh = mesh::heightfield(&buffer(size, &mut random), mesh::SCALE, step);
+
+        let mut edges = HashMap::<(u32, u32), i32>::new();
+
+        for &[a, b, c] in &mesh.triangles {
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                *edges.entry((from, to)).or_default() += 1;
+            }
+
+            assert!(mesh.normal([a, b, c]) != [0.0; 3], "a triangle of the {:?} mesh is degenerate", size);
+        }
+
+        for (&(from, to), &count) in &edges {
+            assert_eq!(count, 1, "the edge {}–{} of the {:?} mesh is traversed {} times", from, to, size, count);
+
+            assert!(edges.contains_key(&(to, from)), "the edge {}–{} of the {:?} mesh is open", from, to, size);
+        }
+    }
+}
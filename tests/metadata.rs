@@ -0,0 +1,42 @@
+// Embedded text has to read back as it was written whichever chunk it lands in, and leave the
+// pixels alone.
+
+use image::RgbImage;
+use polar_mandelbrot::metadata;
+use polar_mandelbrot::random::Random;
+
+#[test]
+fn embedded_text_reads_back() {
+    let mut random = Random::new(1);
+
+    let image = RgbImage::from_fn(37, 23, |_, _| image::Rgb([0, 1, 2].map(|_| (random.next_f32() * 255.0) as u8)));
+
+    let path = std::env::temp_dir().join(format!("polar_mandelbrot_metadata_{}.png", std::process::id()));
+
+    image.save(&path).unwrap();
+
+    // Short Latin-1 in tEXt, long Latin-1 in zTXt, and anything else in iTXt.
+    let entries = [
+        ("Software", "polar_mandelbrot".to_string()),
+        (metadata::PARAMETERS, "{\"iterations\": 256}, ".repeat(40)),
+        ("Comment", "θ from 0 to 2π".to_string())
+    ];
+
+    metadata::embed(&path, &entries).unwrap();
+
+    let mut read = metadata::read(&path).unwrap();
+
+    let decoded = image::open(&path).unwrap().to_rgb8();
+
+    std::fs::remove_file(&path).unwrap();
+
+    read.sort();
+
+    let mut expected = entries.map(|(keyword, text)| (keyword.to_string(), text));
+
+    expected.sort();
+
+    assert_eq!(read, expected);
+
+    assert!(decoded == image, "embedding the text changed the pixels");
+}
@@ -0,0 +1,99 @@
+// Property tests of clipping rays to the image, on random segments from a fixed seed that start and
+// end anywhere from well inside the image to well outside it.
+
+use polar_mandelbrot::polar;
+use polar_mandelbrot::random::Random;
+
+const CASES: usize = 10000;
+const SEED: u64 = 0x5eed;
+
+const SIZE: (u32, u32) = (640, 360);
+
+fn point(random: &mut Random) -> (f32, f32) {
+    ((random.next_f32() * 3.0 - 1.0) * SIZE.0 as f32, (random.next_f32() * 3.0 - 1.0) * SIZE.1 as f32)
+}
+
+fn in_image((x, y): (f32, f32)) -> bool {
+    (0.0..=(SIZE.0 - 1) as f32).contains(&x) && (0.0..=(SIZE.1 - 1) as f32).contains(&y)
+}
+
+// How far a point is from the line through start and end, in pixels.
+fn distance_from_line(start: (f32, f32), end: (f32, f32), (x, y): (f32, f32)) -> f32 {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+
+    ((x - start.0) * dy - (y - start.1) * dx).abs() / dx.hypot(dy)
+}
+
+#[test]
+fn clipped_segments_stay_in_the_image_and_on_the_ray() {
+    let mut random = Random::new(SEED);
+
+    let slack = 1e-3 * SIZE.0 as f32;
+
+    for _ in 0..CASES {
+        let (start, end) = (point(&mut random), point(&mut random));
+
+        let Some((clipped_start, clipped_end)) = polar::clip_segment(start, end, SIZE) else {
+            continue;
+        };
+
+        for clipped in [clipped_start, clipped_end] {
+            let nudged = (clipped.0.clamp(0.0, (SIZE.0 - 1) as f32), clipped.1.clamp(0.0, (SIZE.1 - 1) as f32));
+
+            assert!(
+                (nudged.0 - clipped.0).abs() < slack && (nudged.1 - clipped.1).abs() < slack,
+                "{:?} to {:?} clipped to {:?}, off the image",
+                start,
+                end,
+                clipped
+            );
+
+            assert!(
+                distance_from_line(start, end, clipped) < slack,
+                "{:?} to {:?} clipped to {:?}, off the ray",
+                start,
+                end,
+                clipped
+            );
+        }
+
+        // The clipped part runs the same way as the ray, so its start is the nearer to the origin.
+        let distance = |(x, y): (f32, f32)| (x - start.0).hypot(y - start.1);
+
+        assert!(distance(clipped_start) <= distance(clipped_end) + slack);
+    }
+}
+
+#[test]
+fn segments_inside_are_unchanged() {
+    let mut random = Random::new(SEED);
+
+    for _ in 0..CASES {
+        let (start, end) = (point(&mut random), point(&mut random));
+
+        if in_image(start) && in_image(end) {
+            assert_eq!(polar::clip_segment(start, end, SIZE), Some((start, end)));
+        }
+    }
+}
+
+#[test]
+fn segments_missing_the_image_clip_to_nothing() {
+    let mut random = Random::new(SEED);
+
+    for _ in 0..CASES {
+        let (start, end) = (point(&mut random), point(&mut random));
+
+        // Both ends past the same edge can't cross into the image.
+        let (right, bottom) = ((SIZE.0 - 1) as f32, (SIZE.1 - 1) as f32);
+
+        let outside = (start.0 < 0.0 && end.0 < 0.0)
+            || (start.0 > right && end.0 > right)
+            || (start.1 < 0.0 && end.1 < 0.0)
+            || (start.1 > bottom && end.1 > bottom);
+
+        if outside {
+            assert_eq!(polar::clip_segment(start, end, SIZE), None, "{:?} to {:?}", start, end);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+// Scripts are parsed by hand, so these pin down the precedence of their operators, the errors they
+// report, and the limits that keep malformed ones from overflowing the stack.
+
+use num::complex::Complex;
+use polar_mandelbrot::fractal::{self, Escape, Escaping, Fractal, Limited, Termination};
+use polar_mandelbrot::script::Script;
+use std::sync::Arc;
+
+fn parse(source: &str) -> Script {
+    Script::parse(source).unwrap_or_else(|error| panic!("{:?} failed to parse: {}", source, error))
+}
+
+fn error(source: &str) -> String {
+    match Script::parse(source) {
+        Ok(_) => panic!("{:?} parsed", source),
+        Err(error) => error.to_string()
+    }
+}
+
+// The value `step` takes at z for c = 0.
+fn step(expression: &str, z: f32) -> Complex<f32> {
+    parse(&format!("step = {}", expression)).step(Complex::new(z, 0.0), Complex::new(0.0, 0.0))
+}
+
+fn assert_close(expression: &str, z: f32, expected: f32) {
+    let value = step(expression, z);
+
+    assert!((value - expected).norm() < 1e-6, "{} at z = {} is {}, not {}", expression, z, value, expected);
+}
+
+#[test]
+fn operators_bind_by_precedence() {
+    // Negation applies to the power, and an exponent may be negated.
+    assert_close("-z^2", 2.0, -4.0);
+    assert_close("2^-1", 0.0, 0.5);
+
+    // Powers are right-associative and bind tighter than products, which bind tighter than sums.
+    assert_close("2^3^2", 0.0, 512.0);
+    assert_close("2 * 3^2", 0.0, 18.0);
+    assert_close("1 + 2 * 3", 0.0, 7.0);
+    assert_close("1 - 2 - 3", 0.0, -4.0);
+    assert_close("8 / 4 / 2", 0.0, 1.0);
+
+    // Comparisons bind loosest of all and yield 1 or 0.
+    assert_close("1 + 2 < 4", 0.0, 1.0);
+    assert_close("(1 + 2 < 4) * 3", 0.0, 3.0);
+}
+
+#[test]
+fn bars_take_the_modulus_before_comparing() {
+    let script = parse("step = z\nescape = |z - 1| > 2");
+
+    let terminated = |re: f32| script.terminated(Complex::new(re, 0.0), Complex::new(0.0, 0.0));
+
+    assert_eq!(terminated(3.5), Some(Termination::Escaped));
+    assert_eq!(terminated(-1.5), Some(Termination::Escaped));
+    assert_eq!(terminated(2.5), None);
+
+    assert_close("|z| * 2", -3.0, 6.0);
+}
+
+#[test]
+fn keys_are_checked() {
+    assert_eq!(error("step = z\nstep = c"), "line 2: `step` is defined twice");
+    assert_eq!(error("# a comment\nstep = z\n colour = 1"), "line 3: unknown key `colour`");
+    assert_eq!(error("start = 0\nred = 1"), "line 2: missing `step`");
+    assert_eq!(error("step z"), "line 1: expected `key = expression`");
+}
+
+#[test]
+fn expressions_are_checked() {
+    assert_eq!(error("step = w"), "line 1: unknown identifier `w`");
+    assert_eq!(error("step = (z"), "line 1: expected `)`");
+    assert_eq!(error("step = |z"), "line 1: expected `|`");
+    assert_eq!(error("step = z +"), "line 1: unexpected end of expression");
+    assert_eq!(error("step = z $ c"), "line 1: unexpected character `$`");
+    assert_eq!(error("step = 1.2.3"), "line 1: invalid number `1.2.3`");
+}
+
+#[test]
+fn nesting_is_limited() {
+    let nested = |depth: usize| format!("{}z{}", "(".repeat(depth), ")".repeat(depth));
+
+    // Each pair of parentheses nests one level inside the outermost.
+    assert_close(&nested(63), 1.5, 1.5);
+
+    assert_eq!(error(&format!("step = {}", nested(64))), "line 1: expression nested more than 64 deep");
+    assert_eq!(error(&format!("step = {}z", "-".repeat(64))), "line 1: expression nested more than 64 deep");
+}
+
+#[test]
+fn length_is_limited() {
+    let sum = |terms: usize| format!("z{}", " + z".repeat(terms - 1));
+
+    // 999 tokens, then 1001.
+    assert_close(&sum(500), 1.0, 500.0);
+
+    assert_eq!(error(&format!("step = {}", sum(501))), "line 1: expression longer than 1000 tokens");
+}
+
+#[test]
+fn n_counts_the_iterations_so_far() {
+    let script = Arc::new(parse("step = z + 1\nescape = n > 3\nred = n / limit"));
+
+    let sample = script.sample(Complex::new(0.0, 0.0));
+
+    assert_eq!((sample.iterations, sample.termination), (4, Some(Termination::Escaped)));
+    assert_eq!(sample.z, Complex::new(4.0, 0.0));
+
+    // Through the wrappers the command line adds too.
+    let limited = Limited { fractal: script.clone(), iterations: 2 };
+
+    let sample = limited.sample(Complex::new(0.0, 0.0));
+
+    assert_eq!((sample.iterations, sample.termination), (2, None));
+
+    let escaping = Escaping { fractal: Arc::new(parse("step = z\nconverge = n >= 2")), escape: Escape::Norm(100.0) };
+
+    let sample = escaping.sample(Complex::new(0.0, 0.0));
+
+    assert_eq!((sample.iterations, sample.termination), (2, Some(Termination::Converged)));
+
+    let (orbit, sample) = fractal::orbit(&*script, Complex::new(0.0, 0.0), 1000);
+
+    assert_eq!((orbit.len(), sample.iterations), (5, 4));
+
+    // In step, n is how many steps came before.
+    let (orbit, _) = fractal::orbit(&parse("step = z + n\nescape = n > 3"), Complex::new(0.0, 0.0), 1000);
+
+    assert_eq!(orbit, [0.0, 0.0, 1.0, 3.0, 6.0].map(|re| Complex::new(re, 0.0)));
+}
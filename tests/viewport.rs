@@ -0,0 +1,210 @@
+// Property tests of the mappings between pixels and the complex plane, each checked on many
+// random inputs from a fixed seed so failures reproduce.
+
+use num::complex::Complex;
+use polar_mandelbrot::random::Random;
+use polar_mandelbrot::viewport::{self, View, IMG_HEIGHT, IMG_WIDTH};
+
+const CASES: usize = 10000;
+const SEED: u64 = 0x5eed;
+
+// A random pixel of the full-size image.
+fn coordinate(random: &mut Random) -> (u32, u32) {
+    ((random.next_u64() % IMG_WIDTH as u64) as u32, (random.next_u64() % IMG_HEIGHT as u64) as u32)
+}
+
+// A view centered within the default one, from its full width down to 10⁴ times narrower, at a
+// resolution from 1 to 4096 pixels on a side.
+fn view(random: &mut Random) -> (View, (u32, u32)) {
+    let center = Complex::new(random.next_f32() * 4.0 - 2.0, random.next_f32() * 4.0 - 2.0);
+
+    let width = 4.0 * 10f32.powf(-4.0 * random.next_f32());
+
+    let size = (1 + (random.next_u64() % 4096) as u32, 1 + (random.next_u64() % 4096) as u32);
+
+    (View { center, width }, size)
+}
+
+#[test]
+fn coordinates_round_trip() {
+    let mut random = Random::new(SEED);
+
+    for _ in 0..CASES {
+        let (x, y) = coordinate(&mut random);
+
+        // A pixel maps to its top left corner, so map its middle back to avoid landing on an edge.
+        let middle = viewport::pixel_to_complex(x as f32 + 0.5, y as f32 + 0.5);
+
+        assert_eq!(viewport::complex_to_coordinate(middle), (x, y));
+    }
+}
+
+#[test]
+fn fractional_pixels_round_trip() {
+    let mut random = Random::new(SEED);
+
+    for _ in 0..CASES {
+        let (x, y) = (random.next_f32() * IMG_WIDTH as f32, random.next_f32() * IMG_HEIGHT as f32);
+
+        let (mapped_x, mapped_y) = viewport::complex_to_pixel(viewport::pixel_to_complex(x, y));
+
+        let (error_x, error_y) = ((mapped_x - x).abs(), (mapped_y - y).abs());
+
+        assert!(error_x < 1e-2 && error_y < 1e-2, "({}, {}) became ({}, {})", x, y, mapped_x, mapped_y);
+    }
+}
+
+#[test]
+fn points_in_bounds_map_to_pixels_in_bounds() {
+    let mut random = Random::new(SEED);
+
+    let (low, high) = viewport::bounds();
+
+    for _ in 0..CASES {
+        let c = low + Complex::new(random.next_f32() * (high.re - low.re), random.next_f32() * (high.im - low.im));
+
+        let (x, y) = viewport::complex_to_coordinate(c);
+
+        assert!(x < IMG_WIDTH && y < IMG_HEIGHT, "{} mapped to ({}, {})", c, x, y);
+    }
+}
+
+#[test]
+fn view_pixels_round_trip() {
+    let mut random = Random::new(SEED);
+
+    for _ in 0..CASES {
+        let (view, size) = view(&mut random);
+
+        let (x, y) = (random.next_f32() * size.0 as f32, random.next_f32() * size.1 as f32);
+
+        let (mapped_x, mapped_y) = view.complex_to_pixel(size, view.pixel_to_complex(size, (x, y)));
+
+        // Single precision resolves the plane only so finely, so the tolerance grows with how many
+        // pixels span a unit of it.
+        let tolerance = 1e-2 + 4.0 * f32::EPSILON * (view.center.norm() + view.width) * size.0 as f32 / view.width;
+
+        assert!(
+            (mapped_x - x).abs() < tolerance && (mapped_y - y).abs() < tolerance,
+            "({}, {}) in {:?} at {:?} became ({}, {})",
+            x,
+            y,
+            view,
+            size,
+            mapped_x,
+            mapped_y
+        );
+    }
+}
+
+#[test]
+fn view_pixels_stay_in_view() {
+    let mut random = Random::new(SEED);
+
+    for _ in 0..CASES {
+        let (view, size) = view(&mut random);
+
+        let (x, y) = (random.next_f32() * size.0 as f32, random.next_f32() * size.1 as f32);
+
+        let offset = view.pixel_to_complex(size, (x, y)) - view.center;
+
+        let (half_width, half_height) = (view.width / 2.0, view.width * size.1 as f32 / size.0 as f32 / 2.0);
+
+        let slack = 4.0 * f32::EPSILON * (view.center.norm() + view.width);
+
+        assert!(
+            offset.re.abs() <= half_width + slack && offset.im.abs() <= half_height + slack,
+            "({}, {}) in {:?} at {:?} landed {} from the center",
+            x,
+            y,
+            view,
+            size,
+            offset
+        );
+    }
+}
+
+// The extent in pixels of the unit circle around the origin, as the bounding box of the pixels
+// whose middles map inside it.
+fn circle_extent((width, height): (u32, u32), map: impl Fn(f32, f32) -> Complex<f32>) -> (u32, u32) {
+    let (mut low, mut high) = ((u32::MAX, u32::MAX), (0, 0));
+
+    for y in 0..height {
+        for x in 0..width {
+            if map(x as f32 + 0.5, y as f32 + 0.5).norm() <= 1.0 {
+                (low, high) = ((low.0.min(x), low.1.min(y)), (high.0.max(x), high.1.max(y)));
+            }
+        }
+    }
+
+    (high.0 + 1 - low.0, high.1 + 1 - low.1)
+}
+
+fn assert_round(name: &str, (width, height): (u32, u32), radius: f32) {
+    let diameter = 2.0 * radius;
+
+    assert!(
+        width.abs_diff(height) <= 1 && (width as f32 - diameter).abs() <= 2.0,
+        "{}: the unit circle is {}x{} pixels rather than about {} across",
+        name,
+        width,
+        height,
+        diameter
+    );
+}
+
+#[test]
+fn unit_circle_renders_round() {
+    let size = (IMG_WIDTH, IMG_HEIGHT);
+
+    let extent = circle_extent(size, viewport::pixel_to_complex);
+
+    assert_round("coordinate_to_complex", extent, IMG_WIDTH as f32 / View::default().width);
+}
+
+#[test]
+fn unit_circle_renders_round_in_every_fit() {
+    let origin = Complex::new(0.0, 0.0);
+
+    for size in [(400, 100), (100, 400), (300, 300), (257, 131)] {
+        // Wide enough and tall enough that the circle is never cut off.
+        let aspect = size.0 as f32 / size.1 as f32;
+
+        let views = [
+            ("fit_width", View::fit_width(origin, 3.0 * aspect.max(1.0))),
+            ("fit_height", View::fit_height(origin, 3.0 / aspect.min(1.0), size)),
+            ("fit_bounds", View::fit_bounds(Complex::new(-1.5, -1.5), Complex::new(1.5, 1.5), size))
+        ];
+
+        for (name, view) in views {
+            let extent = circle_extent(size, |x, y| view.pixel_to_complex(size, (x, y)));
+
+            assert_round(&format!("{} at {:?}", name, size), extent, size.0 as f32 / view.width);
+        }
+    }
+}
+
+#[test]
+fn fit_bounds_contains_the_bounds() {
+    let mut random = Random::new(SEED);
+
+    for _ in 0..CASES {
+        let mut corner = || Complex::new(random.next_f32(), random.next_f32());
+
+        let (a, b) = (corner(), corner());
+
+        let size = (1 + (random.next_u64() % 4096) as u32, 1 + (random.next_u64() % 4096) as u32);
+
+        let view = View::fit_bounds(a, b, size);
+
+        let slack = 1e-5;
+
+        let contained = [a, b].iter().all(|&corner| {
+            let offset = corner - view.center;
+
+            offset.re.abs() <= view.width / 2.0 + slack && offset.im.abs() <= view.height(size) / 2.0 + slack
+        });
+
+        assert!(contained, "{:?} at {:?} doesn't contain {} and {}", view, size, a, b);
+    }
+}